@@ -1,3 +1,4 @@
+use motion_planning::validate::{Bound, Limits, Validate};
 use motion_planning::vec::Vec3d;
 use motion_planning::{Pose3, Trajectory3};
 
@@ -24,4 +25,19 @@ fn main() {
 
 		println!("{},{},{},{}", t, pos, vel, acc);
 	}
+
+	let limits = Limits {
+		velocity: Bound {
+			axis: None,
+			norm: Some(1.5),
+		},
+		acceleration: Bound {
+			axis: Some(Vec3d(5.0, 5.0, 5.0)),
+			norm: None,
+		},
+		..Limits::default()
+	};
+
+	// The report goes to stderr, keeping stdout a plain CSV.
+	eprint!("{}", segment.validate(&limits));
 }
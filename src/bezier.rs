@@ -0,0 +1,339 @@
+//! Conversions between Hermite segments and Bézier control polygons.
+//!
+//! A Hermite segment of degree `n` and a Bézier curve of the same degree are
+//! two descriptions of the same polynomial, so the conversions here are
+//! exact.  The Bézier form is what most CAD tools and SVG speak, and its
+//! control points bound the curve: a Bézier curve always lies within the
+//! convex hull of its control polygon.
+//!
+//! Every conversion takes the `duration` of the segment, which is `1.` for
+//! the segments of a plain waypoint vector.
+
+use core::ops::{Add, Mul};
+
+use super::vec::Vec3d;
+use super::{Pose2, Pose3, Pose4};
+
+/// Converts the cubic Hermite segment from `start` to `end` into the four
+/// control points of a cubic Bézier curve.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::bezier::cubic_to_bezier;
+/// use motion_planning::Pose2;
+///
+/// let start = Pose2 { position: 0., velocity: 3. };
+/// let end = Pose2 { position: 1., velocity: 0. };
+///
+/// assert_eq!(cubic_to_bezier(&start, &end, 1.), [0., 1., 1., 1.]);
+/// ```
+pub fn cubic_to_bezier<V>(start: &Pose2<V>, end: &Pose2<V>, duration: f64) -> [V; 4]
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let h = duration;
+
+	[
+		start.position,
+		start.position + start.velocity * (h / 3.),
+		end.position + end.velocity * (-h / 3.),
+		end.position,
+	]
+}
+
+/// Converts the four control points of a cubic Bézier curve into the cubic
+/// Hermite segment lasting `duration` that traces it.
+pub fn bezier_to_cubic<V>(points: &[V; 4], duration: f64) -> (Pose2<V>, Pose2<V>)
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let [p0, p1, p2, p3] = *points;
+	let s = 3. / duration;
+
+	(
+		Pose2 {
+			position: p0,
+			velocity: (p1 + p0 * -1.) * s,
+		},
+		Pose2 {
+			position: p3,
+			velocity: (p3 + p2 * -1.) * s,
+		},
+	)
+}
+
+/// Converts the quintic Hermite segment from `start` to `end` into the six
+/// control points of a quintic Bézier curve.
+pub fn quintic_to_bezier<V>(start: &Pose3<V>, end: &Pose3<V>, duration: f64) -> [V; 6]
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let h = duration;
+	let h2 = h * h;
+
+	[
+		start.position,
+		start.position + start.velocity * (h / 5.),
+		start.position + start.velocity * (2. * h / 5.) + start.acceleration * (h2 / 20.),
+		end.position + end.velocity * (-2. * h / 5.) + end.acceleration * (h2 / 20.),
+		end.position + end.velocity * (-h / 5.),
+		end.position,
+	]
+}
+
+/// Converts the six control points of a quintic Bézier curve into the quintic
+/// Hermite segment lasting `duration` that traces it.
+pub fn bezier_to_quintic<V>(points: &[V; 6], duration: f64) -> (Pose3<V>, Pose3<V>)
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let [p0, p1, p2, p3, p4, p5] = *points;
+	let s = 5. / duration;
+	let s2 = 20. / (duration * duration);
+
+	(
+		Pose3 {
+			position: p0,
+			velocity: (p1 + p0 * -1.) * s,
+			acceleration: (p2 + p1 * -2. + p0) * s2,
+		},
+		Pose3 {
+			position: p5,
+			velocity: (p5 + p4 * -1.) * s,
+			acceleration: (p5 + p4 * -2. + p3) * s2,
+		},
+	)
+}
+
+/// Converts the septic Hermite segment from `start` to `end` into the eight
+/// control points of a septic Bézier curve.
+pub fn septic_to_bezier<V>(start: &Pose4<V>, end: &Pose4<V>, duration: f64) -> [V; 8]
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let h = duration;
+	let h2 = h * h;
+	let h3 = h2 * h;
+
+	[
+		start.position,
+		start.position + start.velocity * (h / 7.),
+		start.position + start.velocity * (2. * h / 7.) + start.acceleration * (h2 / 42.),
+		start.position
+			+ start.velocity * (3. * h / 7.)
+			+ start.acceleration * (h2 / 14.)
+			+ start.jerk * (h3 / 210.),
+		end.position
+			+ end.velocity * (-3. * h / 7.)
+			+ end.acceleration * (h2 / 14.)
+			+ end.jerk * (-h3 / 210.),
+		end.position + end.velocity * (-2. * h / 7.) + end.acceleration * (h2 / 42.),
+		end.position + end.velocity * (-h / 7.),
+		end.position,
+	]
+}
+
+/// Converts the eight control points of a septic Bézier curve into the septic
+/// Hermite segment lasting `duration` that traces it.
+pub fn bezier_to_septic<V>(points: &[V; 8], duration: f64) -> (Pose4<V>, Pose4<V>)
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let [p0, p1, p2, p3, p4, p5, p6, p7] = *points;
+	let s = 7. / duration;
+	let s2 = 42. / (duration * duration);
+	let s3 = 210. / (duration * duration * duration);
+
+	(
+		Pose4 {
+			position: p0,
+			velocity: (p1 + p0 * -1.) * s,
+			acceleration: (p2 + p1 * -2. + p0) * s2,
+			jerk: (p3 + p2 * -3. + p1 * 3. + p0 * -1.) * s3,
+		},
+		Pose4 {
+			position: p7,
+			velocity: (p7 + p6 * -1.) * s,
+			acceleration: (p7 + p6 * -2. + p5) * s2,
+			jerk: (p7 + p6 * -3. + p5 * 3. + p4 * -1.) * s3,
+		},
+	)
+}
+
+/// Evaluates the Bézier curve with the given control points at `t`, which
+/// runs from `0.` to `1.`, using de Casteljau's algorithm.
+///
+/// # Panics
+///
+/// Panics if `points` is empty.
+pub fn evaluate<V>(points: &[V], t: f64) -> V
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let mut points = points.to_vec();
+
+	while points.len() > 1 {
+		points = points
+			.windows(2)
+			.map(|pair| pair[0] * (1. - t) + pair[1] * t)
+			.collect();
+	}
+
+	points[0]
+}
+
+/// Computes the axis-aligned box containing a control polygon, and therefore
+/// the curve it controls, as its minimum and maximum corners.
+///
+/// Returns `None` if `points` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::bezier::{bounding_box, cubic_to_bezier};
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose2;
+///
+/// let start = Pose2 { position: Vec3d(0., 0., 0.), velocity: Vec3d(0., 3., 0.) };
+/// let end = Pose2 { position: Vec3d(1., 0., 0.), velocity: Vec3d(0., -3., 0.) };
+///
+/// let (min, max) = bounding_box(&cubic_to_bezier(&start, &end, 1.)).unwrap();
+///
+/// assert_eq!(min, Vec3d(0., 0., 0.));
+/// assert_eq!(max, Vec3d(1., 1., 0.));
+/// ```
+pub fn bounding_box(points: &[Vec3d<f64>]) -> Option<(Vec3d<f64>, Vec3d<f64>)> {
+	let first = *points.first()?;
+
+	Some(points.iter().fold((first, first), |(min, max), p| {
+		(
+			Vec3d(min.0.min(p.0), min.1.min(p.1), min.2.min(p.2)),
+			Vec3d(max.0.max(p.0), max.1.max(p.1), max.2.max(p.2)),
+		)
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::hermite::{cubic::h_3, quintic::h_5, septic::h_7};
+	use super::super::{assert_f64_roughly_eq, eval2, eval3, eval4};
+	use super::*;
+
+	fn assert_vec_roughly_eq(a: Vec3d<f64>, b: Vec3d<f64>) {
+		assert_f64_roughly_eq!(a.0, b.0, 1e-9);
+		assert_f64_roughly_eq!(a.1, b.1, 1e-9);
+		assert_f64_roughly_eq!(a.2, b.2, 1e-9);
+	}
+
+	fn ts() -> impl Iterator<Item = f64> {
+		(0..=10).map(|i| f64::from(i) / 10.)
+	}
+
+	#[test]
+	fn cubic_round_trip() {
+		let start = Pose2 {
+			position: Vec3d(0., 1., 2.),
+			velocity: Vec3d(1., -1., 0.5),
+		};
+		let end = Pose2 {
+			position: Vec3d(2., 0., 1.),
+			velocity: Vec3d(0., 2., -1.),
+		};
+
+		let points = cubic_to_bezier(&start, &end, 2.);
+
+		for t in ts() {
+			assert_vec_roughly_eq(evaluate(&points, t), eval2(t, &start, &end, 2., h_3, 0));
+		}
+
+		let (s, e) = bezier_to_cubic(&points, 2.);
+		assert_vec_roughly_eq(s.velocity, start.velocity);
+		assert_vec_roughly_eq(e.velocity, end.velocity);
+		assert_eq!(s.position, start.position);
+		assert_eq!(e.position, end.position);
+	}
+
+	#[test]
+	fn quintic_round_trip() {
+		let start = Pose3 {
+			position: Vec3d(0., 1., 2.),
+			velocity: Vec3d(1., -1., 0.5),
+			acceleration: Vec3d(3., 0., -2.),
+		};
+		let end = Pose3 {
+			position: Vec3d(2., 0., 1.),
+			velocity: Vec3d(0., 2., -1.),
+			acceleration: Vec3d(-1., 1., 0.),
+		};
+
+		let points = quintic_to_bezier(&start, &end, 0.5);
+
+		for t in ts() {
+			assert_vec_roughly_eq(evaluate(&points, t), eval3(t, &start, &end, 0.5, h_5, 0));
+		}
+
+		let (s, e) = bezier_to_quintic(&points, 0.5);
+		assert_vec_roughly_eq(s.velocity, start.velocity);
+		assert_vec_roughly_eq(s.acceleration, start.acceleration);
+		assert_vec_roughly_eq(e.velocity, end.velocity);
+		assert_vec_roughly_eq(e.acceleration, end.acceleration);
+	}
+
+	#[test]
+	fn septic_round_trip() {
+		let start = Pose4 {
+			position: Vec3d(0., 1., 2.),
+			velocity: Vec3d(1., -1., 0.5),
+			acceleration: Vec3d(3., 0., -2.),
+			jerk: Vec3d(0., 5., 1.),
+		};
+		let end = Pose4 {
+			position: Vec3d(2., 0., 1.),
+			velocity: Vec3d(0., 2., -1.),
+			acceleration: Vec3d(-1., 1., 0.),
+			jerk: Vec3d(2., 0., -4.),
+		};
+
+		let points = septic_to_bezier(&start, &end, 1.5);
+
+		for t in ts() {
+			assert_vec_roughly_eq(evaluate(&points, t), eval4(t, &start, &end, 1.5, h_7, 0));
+		}
+
+		let (s, e) = bezier_to_septic(&points, 1.5);
+		assert_vec_roughly_eq(s.velocity, start.velocity);
+		assert_vec_roughly_eq(s.acceleration, start.acceleration);
+		assert_vec_roughly_eq(s.jerk, start.jerk);
+		assert_vec_roughly_eq(e.velocity, end.velocity);
+		assert_vec_roughly_eq(e.acceleration, end.acceleration);
+		assert_vec_roughly_eq(e.jerk, end.jerk);
+	}
+
+	#[test]
+	fn bounding_box_contains_curve() {
+		let start = Pose3 {
+			position: Vec3d(0., 0., 0.),
+			velocity: Vec3d(4., 4., 0.),
+			acceleration: Vec3d(0., -10., 3.),
+		};
+		let end = Pose3 {
+			position: Vec3d(1., 0., 0.),
+			velocity: Vec3d(4., -4., 1.),
+			acceleration: Vec3d(0., 0., 0.),
+		};
+
+		let points = quintic_to_bezier(&start, &end, 1.);
+		let (min, max) = bounding_box(&points).unwrap();
+
+		for i in 0..=100 {
+			let p = eval3(f64::from(i) / 100., &start, &end, 1., h_5, 0);
+
+			assert!(min.0 <= p.0 && p.0 <= max.0);
+			assert!(min.1 <= p.1 && p.1 <= max.1);
+			assert!(min.2 <= p.2 && p.2 <= max.2);
+		}
+
+		assert_eq!(bounding_box(&[]), None);
+	}
+}
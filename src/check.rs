@@ -0,0 +1,308 @@
+//! Checks that waypoints describe a sensible trajectory.
+//!
+//! Malformed waypoints don't fail loudly when evaluated: a `NaN` spreads
+//! through every position near it, and two waypoints at the same place with
+//! different velocities make a tight loop between them.  [`Check`] finds
+//! such problems up front and says which waypoint each is at.
+
+use core::fmt;
+
+use super::timed::Timed;
+use super::vec::Vec3d;
+use super::{Pose2, Pose3, Pose4};
+
+/// Values that can be checked for `NaN` and infinite components.
+pub trait Finite {
+	fn is_finite(&self) -> bool;
+}
+
+impl Finite for f32 {
+	fn is_finite(&self) -> bool {
+		f32::is_finite(*self)
+	}
+}
+
+impl Finite for f64 {
+	fn is_finite(&self) -> bool {
+		f64::is_finite(*self)
+	}
+}
+
+impl<V: Finite> Finite for Vec3d<V> {
+	fn is_finite(&self) -> bool {
+		self.0.is_finite() && self.1.is_finite() && self.2.is_finite()
+	}
+}
+
+impl<V: Finite> Finite for Pose2<V> {
+	fn is_finite(&self) -> bool {
+		self.position.is_finite() && self.velocity.is_finite()
+	}
+}
+
+impl<V: Finite> Finite for Pose3<V> {
+	fn is_finite(&self) -> bool {
+		self.position.is_finite() && self.velocity.is_finite() && self.acceleration.is_finite()
+	}
+}
+
+impl<V: Finite> Finite for Pose4<V> {
+	fn is_finite(&self) -> bool {
+		self.position.is_finite()
+			&& self.velocity.is_finite()
+			&& self.acceleration.is_finite()
+			&& self.jerk.is_finite()
+	}
+}
+
+impl<P: Finite> Finite for Timed<P> {
+	fn is_finite(&self) -> bool {
+		self.time.is_finite() && self.pose.is_finite()
+	}
+}
+
+/// A problem with a waypoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Issue {
+	/// A component, or the time, is `NaN` or infinite.
+	NotFinite,
+	/// The waypoint is at the same position as the one before it, but its
+	/// derivatives differ.
+	ConflictingDuplicate,
+	/// The waypoint's time is before the one before it.
+	NonMonotonicTime,
+	/// The waypoint's time is the same as the one before it, but the poses
+	/// differ, so the trajectory jumps.
+	Discontinuity,
+}
+
+/// An [`Issue`] found at the waypoint with index `index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Problem {
+	pub index: usize,
+	pub issue: Issue,
+}
+
+impl fmt::Display for Problem {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let description = match self.issue {
+			Issue::NotFinite => "has a NaN or infinite component",
+			Issue::ConflictingDuplicate => "repeats the previous position with different derivatives",
+			Issue::NonMonotonicTime => "is timed before the previous waypoint",
+			Issue::Discontinuity => "is timed with the previous waypoint but differs from it",
+		};
+
+		write!(f, "waypoint {} {}", self.index, description)
+	}
+}
+
+/// Checking waypoints for problems.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::check::{Check, Issue, Problem};
+/// use motion_planning::Pose2;
+///
+/// let waypoints = vec![
+///     Pose2 { position: 0., velocity: 1. },
+///     Pose2 { position: 0., velocity: -1. },
+///     Pose2 { position: f64::NAN, velocity: 0. },
+/// ];
+///
+/// assert_eq!(
+///     waypoints.check(),
+///     vec![
+///         Problem { index: 1, issue: Issue::ConflictingDuplicate },
+///         Problem { index: 2, issue: Issue::NotFinite },
+///     ]
+/// );
+/// ```
+pub trait Check {
+	/// Lists every problem found, in order of waypoint.
+	fn check(&self) -> Vec<Problem>;
+}
+
+/// Checks untimed waypoints, given a way of picking out their positions.
+fn check_untimed<P, V>(waypoints: &[P], position: impl Fn(&P) -> &V) -> Vec<Problem>
+where
+	P: Finite + PartialEq,
+	V: PartialEq,
+{
+	let mut problems = Vec::new();
+
+	for (index, waypoint) in waypoints.iter().enumerate() {
+		if !waypoint.is_finite() {
+			problems.push(Problem {
+				index,
+				issue: Issue::NotFinite,
+			});
+		}
+
+		if let Some(previous) = index.checked_sub(1).map(|i| &waypoints[i]) {
+			if position(previous) == position(waypoint) && previous != waypoint {
+				problems.push(Problem {
+					index,
+					issue: Issue::ConflictingDuplicate,
+				});
+			}
+		}
+	}
+
+	problems
+}
+
+/// Checks timed waypoints.
+fn check_timed<P>(waypoints: &[Timed<P>]) -> Vec<Problem>
+where
+	P: Finite + PartialEq,
+{
+	let mut problems = Vec::new();
+
+	for (index, waypoint) in waypoints.iter().enumerate() {
+		if !waypoint.is_finite() {
+			problems.push(Problem {
+				index,
+				issue: Issue::NotFinite,
+			});
+		}
+
+		if let Some(previous) = index.checked_sub(1).map(|i| &waypoints[i]) {
+			if waypoint.time < previous.time {
+				problems.push(Problem {
+					index,
+					issue: Issue::NonMonotonicTime,
+				});
+			} else if waypoint.time == previous.time && waypoint.pose != previous.pose {
+				problems.push(Problem {
+					index,
+					issue: Issue::Discontinuity,
+				});
+			}
+		}
+	}
+
+	problems
+}
+
+impl<V: Finite + PartialEq> Check for Vec<Pose2<V>> {
+	fn check(&self) -> Vec<Problem> {
+		check_untimed(self, |pose| &pose.position)
+	}
+}
+
+impl<V: Finite + PartialEq> Check for Vec<Pose3<V>> {
+	fn check(&self) -> Vec<Problem> {
+		check_untimed(self, |pose| &pose.position)
+	}
+}
+
+impl<V: Finite + PartialEq> Check for Vec<Pose4<V>> {
+	fn check(&self) -> Vec<Problem> {
+		check_untimed(self, |pose| &pose.position)
+	}
+}
+
+impl<V: Finite + PartialEq> Check for Vec<Timed<Pose2<V>>> {
+	fn check(&self) -> Vec<Problem> {
+		check_timed(self)
+	}
+}
+
+impl<V: Finite + PartialEq> Check for Vec<Timed<Pose3<V>>> {
+	fn check(&self) -> Vec<Problem> {
+		check_timed(self)
+	}
+}
+
+impl<V: Finite + PartialEq> Check for Vec<Timed<Pose4<V>>> {
+	fn check(&self) -> Vec<Problem> {
+		check_timed(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::timed::timed;
+	use super::*;
+
+	fn at(x: f64) -> Pose3<Vec3d<f64>> {
+		Pose3 {
+			position: Vec3d(x, 0., 0.),
+			velocity: Vec3d(1., 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		}
+	}
+
+	#[test]
+	fn well_formed() {
+		let waypoints = vec![at(0.), at(1.), at(1.), at(2.)];
+
+		assert!(waypoints.check().is_empty());
+		assert!(timed(&waypoints).check().is_empty());
+	}
+
+	#[test]
+	fn not_finite() {
+		let mut waypoints = vec![at(0.), at(1.), at(2.)];
+		waypoints[1].acceleration.2 = f64::INFINITY;
+
+		assert_eq!(
+			waypoints.check(),
+			vec![Problem {
+				index: 1,
+				issue: Issue::NotFinite,
+			}]
+		);
+
+		let mut timed = timed(&[at(0.), at(1.)]);
+		timed[0].time = f64::NAN;
+
+		assert_eq!(
+			timed.check(),
+			vec![Problem {
+				index: 0,
+				issue: Issue::NotFinite,
+			}]
+		);
+	}
+
+	#[test]
+	fn conflicting_duplicate() {
+		let mut waypoints = vec![at(0.), at(1.), at(1.)];
+		waypoints[2].velocity = Vec3d(0., 1., 0.);
+
+		assert_eq!(
+			waypoints.check(),
+			vec![Problem {
+				index: 2,
+				issue: Issue::ConflictingDuplicate,
+			}]
+		);
+	}
+
+	#[test]
+	fn timing() {
+		let mut waypoints = timed(&[at(0.), at(1.), at(2.), at(3.)]);
+		waypoints[2].time = 0.5;
+		waypoints[3].time = 0.5;
+
+		assert_eq!(
+			waypoints.check(),
+			vec![
+				Problem {
+					index: 2,
+					issue: Issue::NonMonotonicTime,
+				},
+				Problem {
+					index: 3,
+					issue: Issue::Discontinuity,
+				},
+			]
+		);
+		assert_eq!(
+			waypoints.check()[1].to_string(),
+			"waypoint 3 is timed with the previous waypoint but differs from it"
+		);
+	}
+}
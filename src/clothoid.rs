@@ -0,0 +1,765 @@
+//! Clothoids, or Euler spirals: curves whose curvature changes linearly with
+//! distance along them.
+//!
+//! A vehicle steering at a steady rate while driving at a steady speed
+//! follows a clothoid, so roads and rail are laid out with them between
+//! straights and arcs.  Their points are given by the Fresnel integrals,
+//! which [`fresnel`] evaluates to full precision.
+//!
+//! [`fit_g1`] finds the single clothoid joining two poses.  [`fit_g2`] joins
+//! two poses with given curvatures by three clothoids, so that the curvature
+//! is continuous throughout.  It is a heuristic rather than a general G2
+//! fit: the outer two clothoids have a fixed length, and only their
+//! sharpnesses are solved for, so it fails where no curve of that shape
+//! fits.  Either result can be sampled, or turned into a
+//! [`PlanarTrajectory`] with [`trajectory`], to be timed and followed like
+//! the Hermite trajectories.
+
+use core::f64::consts::{FRAC_PI_2, PI};
+
+use super::planar::{wrap, PlanarTrajectory, Se2};
+use super::quadrature::points;
+use super::timed::Timed;
+use super::Pose3;
+
+/// The largest argument for which the Fresnel integrals are summed as a
+/// power series; beyond it, a continued fraction converges faster.
+const SERIES_LIMIT: f64 = 1.5;
+
+/// How many terms of the series or continued fraction may be taken.
+const FRESNEL_TERMS: usize = 100;
+
+/// Clothoids whose sharpness turns them through less than this, relative to
+/// the arc they would otherwise be, are integrated by quadrature, since
+/// through the Fresnel integrals they would be the small difference of
+/// large values.
+const NEARLY_ARC: f64 = 1e-4;
+
+/// The most the heading may turn over one quadrature panel.
+const PANEL_TURN: f64 = 0.25;
+
+/// How many Newton steps a fit may take.
+const FIT_ITERATIONS: usize = 100;
+
+/// How small the residual of a [`fit_g1`] must become.
+const FIT_TOLERANCE: f64 = 1e-12;
+
+/// How close the curvatures at the joins of a [`fit_g2`] must become.
+const CURVATURE_TOLERANCE: f64 = 1e-9;
+
+/// The step in sharpness used to differentiate a [`fit_g2`] numerically.
+const SHARPNESS_STEP: f64 = 1e-7;
+
+/// Evaluates the Fresnel integrals `C(x)` and `S(x)`, the integrals from `0`
+/// to `x` of `cos(πt²/2)` and `sin(πt²/2)`.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::clothoid::fresnel;
+///
+/// let (c, s) = fresnel(1.);
+///
+/// assert!((c - 0.779_893_400_376_822_8).abs() < 1e-15);
+/// assert!((s - 0.438_259_147_390_354_8).abs() < 1e-15);
+///
+/// // Both tend to a half.
+/// let (c, s) = fresnel(1e6);
+/// assert!((c - 0.5).abs() < 1e-6 && (s - 0.5).abs() < 1e-6);
+/// ```
+pub fn fresnel(x: f64) -> (f64, f64) {
+	let ax = x.abs();
+
+	let (c, s) = if ax <= SERIES_LIMIT {
+		// C and S are the odd and even terms of one alternating series,
+		// the nth being (πx²/2)ⁿ x / (n! (2n + 1)).
+		let fact = FRAC_PI_2 * ax * ax;
+		let mut term = ax;
+		let (mut c, mut s) = (ax, 0.);
+
+		for n in 1..FRESNEL_TERMS {
+			term *= fact / n as f64;
+
+			let value = term / (2 * n + 1) as f64;
+			let sign = if n % 4 < 2 { 1. } else { -1. };
+
+			if n % 2 == 1 {
+				s += sign * value;
+			} else {
+				c += sign * value;
+			}
+
+			if value < f64::EPSILON * c.abs().max(s.abs()) {
+				break;
+			}
+		}
+
+		(c, s)
+	} else {
+		// The complementary error function, by Lentz's method on its
+		// continued fraction.
+		let pix2 = PI * ax * ax;
+		let mut b = (1., -pix2);
+		let mut cc = (1e30, 0.);
+		let mut d = divide((1., 0.), b);
+		let mut h = d;
+
+		for k in 1..FRESNEL_TERMS {
+			let n = (2 * k - 1) as f64;
+			let a = -n * (n + 1.);
+
+			b.0 += 4.;
+			d = divide((1., 0.), add(scale(d, a), b));
+			cc = add(b, divide((a, 0.), cc));
+
+			let change = multiply(cc, d);
+			h = multiply(h, change);
+
+			if (change.0 - 1.).abs() + change.1.abs() < f64::EPSILON {
+				break;
+			}
+		}
+
+		let h = multiply((ax, -ax), h);
+		let turn = ((pix2 / 2.).cos(), (pix2 / 2.).sin());
+		multiply((0.5, 0.5), add((1., 0.), scale(multiply(turn, h), -1.)))
+	};
+
+	if x < 0. {
+		(-c, -s)
+	} else {
+		(c, s)
+	}
+}
+
+// Complex arithmetic, on (real, imaginary) pairs.
+
+fn add(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+	(a.0 + b.0, a.1 + b.1)
+}
+
+fn scale(a: (f64, f64), k: f64) -> (f64, f64) {
+	(a.0 * k, a.1 * k)
+}
+
+fn multiply(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+	(a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn divide(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+	let norm = b.0 * b.0 + b.1 * b.1;
+
+	(
+		(a.0 * b.0 + a.1 * b.1) / norm,
+		(a.1 * b.0 - a.0 * b.1) / norm,
+	)
+}
+
+/// The unit vector at `angle`.
+fn unit(angle: f64) -> (f64, f64) {
+	let (sin, cos) = angle.sin_cos();
+
+	(cos, sin)
+}
+
+/// Integrates `tᵖ` times the unit vector at `heading + curvature t +
+/// sharpness t²/2` for `t` from `0` to `length`, by quadrature.
+fn quadrature(heading: f64, curvature: f64, sharpness: f64, length: f64, p: i32) -> (f64, f64) {
+	let turn = curvature.abs() * length + sharpness.abs() * length * length / 2.;
+	let panels = (turn / PANEL_TURN).ceil().max(1.) as usize;
+
+	points(0., length, panels)
+		.map(|(t, weight)| {
+			let angle = heading + curvature * t + sharpness * t * t / 2.;
+
+			scale(unit(angle), weight * t.powi(p))
+		})
+		.fold((0., 0.), add)
+}
+
+/// Integrates the unit vector at `heading + curvature t + sharpness t²/2`
+/// for `t` from `0` to `length`, giving how far along a clothoid its end is.
+fn displacement(heading: f64, curvature: f64, sharpness: f64, length: f64) -> (f64, f64) {
+	if length == 0. {
+		return (0., 0.);
+	}
+
+	let bend = sharpness.abs() * length * length;
+
+	if bend < NEARLY_ARC || curvature * curvature > bend / (NEARLY_ARC * length * length) {
+		return quadrature(heading, curvature, sharpness, length, 0);
+	}
+
+	// Complete the square, measuring from where the curvature is zero.
+	let sign = sharpness.signum();
+	let width = (PI / sharpness.abs()).sqrt();
+	let offset = curvature / sharpness;
+	let (c0, s0) = fresnel(offset / width);
+	let (c1, s1) = fresnel((length + offset) / width);
+
+	let phase = heading - curvature * offset / 2.;
+
+	scale(multiply(unit(phase), (c1 - c0, sign * (s1 - s0))), width)
+}
+
+/// A clothoid from `start`, of `length`, with `curvature` there changing by
+/// `sharpness` per unit length.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::clothoid::Clothoid;
+/// use motion_planning::planar::Se2;
+///
+/// // Ease from straight into a turn of radius 10 over 10 m.
+/// let easement = Clothoid {
+///     start: Se2 { x: 0., y: 0., heading: 0. },
+///     curvature: 0.,
+///     sharpness: 0.01,
+///     length: 10.,
+/// };
+///
+/// assert!((easement.curvature_at(5.).unwrap() - 0.05).abs() < 1e-15);
+///
+/// // Turning through half a radian, and drifting left.
+/// let end = easement.end();
+/// assert!((end.heading - 0.5).abs() < 1e-15);
+/// assert!(end.y > 1.6 && end.y < 1.7);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clothoid {
+	pub start: Se2,
+	pub curvature: f64,
+	pub sharpness: f64,
+	pub length: f64,
+}
+
+impl Clothoid {
+	/// The pose `s` along the clothoid, with its heading unwrapped.
+	fn follow(&self, s: f64) -> Se2 {
+		let (dx, dy) = displacement(self.start.heading, self.curvature, self.sharpness, s);
+
+		Se2 {
+			x: self.start.x + dx,
+			y: self.start.y + dy,
+			heading: self.start.heading + self.curvature * s + self.sharpness * s * s / 2.,
+		}
+	}
+
+	/// The pose `s` along the clothoid, with its heading in `(-π, π]`, or
+	/// `None` if `s` is not on it.
+	pub fn pose_at(&self, s: f64) -> Option<Se2> {
+		if !(0. ..=self.length).contains(&s) {
+			return None;
+		}
+
+		let pose = self.follow(s);
+
+		Some(Se2 {
+			heading: wrap(pose.heading),
+			..pose
+		})
+	}
+
+	/// The curvature `s` along the clothoid, or `None` if `s` is not on it.
+	pub fn curvature_at(&self, s: f64) -> Option<f64> {
+		if !(0. ..=self.length).contains(&s) {
+			return None;
+		}
+
+		Some(self.curvature + self.sharpness * s)
+	}
+
+	/// The pose the clothoid ends at, with its heading in `(-π, π]`.
+	pub fn end(&self) -> Se2 {
+		let pose = self.follow(self.length);
+
+		Se2 {
+			heading: wrap(pose.heading),
+			..pose
+		}
+	}
+
+	/// The curvature the clothoid ends with.
+	pub fn end_curvature(&self) -> f64 {
+		self.curvature + self.sharpness * self.length
+	}
+
+	/// Samples the clothoid at points no more than `spacing` apart, including
+	/// both ends.
+	///
+	/// Returns `None` if `spacing` is not positive.
+	pub fn sample(&self, spacing: f64) -> Option<Vec<Se2>> {
+		if spacing <= 0. || spacing.is_nan() {
+			return None;
+		}
+
+		let steps = (self.length / spacing).ceil().max(1.) as usize;
+
+		(0..=steps)
+			.map(|i| self.pose_at(self.length * i as f64 / steps as f64))
+			.collect()
+	}
+}
+
+/// A trajectory driving along `clothoids`, one after another, at a constant
+/// `speed`, through waypoints no more than `spacing` apart, with the heading
+/// along the direction of travel.
+///
+/// Each waypoint has the path's exact velocity and acceleration.  Where the
+/// sharpness changes, at the joins between clothoids, the waypoint is given
+/// twice at the same time, with the heading's acceleration either side.
+/// Waypoints are kept within a quarter turn of each other.
+///
+/// Returns `None` if `speed` or `spacing` is not positive.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::clothoid::{fit_g1, trajectory};
+/// use motion_planning::planar::Se2;
+/// use motion_planning::Trajectory3;
+///
+/// let curve = fit_g1(
+///     Se2 { x: 0., y: 0., heading: 0. },
+///     Se2 { x: 4., y: 2., heading: 1. },
+/// )
+/// .unwrap();
+///
+/// let path = trajectory(&[curve], 2., 0.1).unwrap();
+/// let end = path.position_at(curve.length / 2.).unwrap();
+///
+/// assert!((end.x - 4.).abs() < 1e-12 && (end.y - 2.).abs() < 1e-12);
+/// ```
+pub fn trajectory(clothoids: &[Clothoid], speed: f64, spacing: f64) -> Option<PlanarTrajectory> {
+	if !(speed > 0. && spacing > 0.) {
+		return None;
+	}
+
+	let mut waypoints: Vec<Timed<Pose3<Se2>>> = Vec::new();
+	let mut start = 0.;
+	let mut previous: Option<(f64, f64)> = None;
+
+	for clothoid in clothoids {
+		let sharpest = clothoid.curvature.abs().max(clothoid.end_curvature().abs());
+		let spacing = if sharpest > 0. {
+			spacing.min(FRAC_PI_2 / sharpest)
+		} else {
+			spacing
+		};
+		let steps = (clothoid.length / spacing).ceil() as usize;
+
+		// The first clothoid, and any after a change of sharpness, need
+		// their own waypoint at their start.
+		let first = match previous {
+			Some((curvature, sharpness))
+				if sharpness == clothoid.sharpness && curvature == clothoid.curvature =>
+			{
+				1
+			}
+			_ => 0,
+		};
+
+		if steps == 0 && first == 1 {
+			continue;
+		}
+
+		for i in first..=steps {
+			let s = if steps == 0 {
+				0.
+			} else {
+				clothoid.length * i as f64 / steps as f64
+			};
+			let position = clothoid.follow(s);
+			let curvature = clothoid.curvature + clothoid.sharpness * s;
+			let (sin, cos) = position.heading.sin_cos();
+			let turn = speed * speed * curvature;
+
+			waypoints.push(Timed {
+				time: (start + s) / speed,
+				pose: Pose3 {
+					position,
+					velocity: Se2 {
+						x: speed * cos,
+						y: speed * sin,
+						heading: speed * curvature,
+					},
+					acceleration: Se2 {
+						x: -turn * sin,
+						y: turn * cos,
+						heading: speed * speed * clothoid.sharpness,
+					},
+				},
+			});
+		}
+
+		previous = Some((clothoid.end_curvature(), clothoid.sharpness));
+		start += clothoid.length;
+	}
+
+	Some(PlanarTrajectory::new(&waypoints))
+}
+
+/// Finds the clothoid from `start` to `end`, leaving and arriving with their
+/// headings, turning the shorter way relative to the line between them.
+///
+/// This is the G1 Hermite problem, solved by Newton's method as by
+/// Bertolazzi and Frego.  Returns `None` if the poses coincide or no
+/// solution is found.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::clothoid::fit_g1;
+/// use motion_planning::planar::Se2;
+///
+/// // A circular arc is a clothoid that never sharpens.
+/// let quarter = fit_g1(
+///     Se2 { x: 0., y: 0., heading: 0. },
+///     Se2 { x: 1., y: 1., heading: std::f64::consts::FRAC_PI_2 },
+/// )
+/// .unwrap();
+///
+/// assert!((quarter.curvature - 1.).abs() < 1e-12);
+/// assert!(quarter.sharpness.abs() < 1e-12);
+/// assert!((quarter.length - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+/// ```
+pub fn fit_g1(start: Se2, end: Se2) -> Option<Clothoid> {
+	let (dx, dy) = (end.x - start.x, end.y - start.y);
+	let r = dx.hypot(dy);
+
+	if r == 0. || !r.is_finite() {
+		return None;
+	}
+
+	// On a unit length, the heading relative to the chord goes from `phi0`
+	// through `phi0 + (delta - a) t + a t²` to `phi1`, and the end must lie
+	// on the chord.
+	let chord = dy.atan2(dx);
+	let phi0 = wrap(start.heading - chord);
+	let phi1 = wrap(end.heading - chord);
+	let delta = phi1 - phi0;
+
+	let mut a = 3. * (phi0 + phi1);
+	let mut converged = false;
+
+	for _ in 0..FIT_ITERATIONS {
+		let (_, g) = displacement(phi0, delta - a, 2. * a, 1.);
+
+		if g.abs() < FIT_TOLERANCE {
+			converged = true;
+			break;
+		}
+
+		let (x1, _) = quadrature(phi0, delta - a, 2. * a, 1., 1);
+		let (x2, _) = quadrature(phi0, delta - a, 2. * a, 1., 2);
+
+		a -= g / (x2 - x1);
+	}
+
+	let (x, _) = displacement(phi0, delta - a, 2. * a, 1.);
+	let length = r / x;
+
+	if !(converged && length > 0. && length.is_finite()) {
+		return None;
+	}
+
+	Some(Clothoid {
+		start,
+		curvature: (delta - a) / length,
+		sharpness: 2. * a / (length * length),
+		length,
+	})
+}
+
+/// Finds three clothoids from `start` to `end`, leaving and arriving with
+/// their headings and with `start_curvature` and `end_curvature`, so that
+/// curvature is continuous throughout.
+///
+/// This is a fixed-length heuristic, not a general G2 fit.  The first and
+/// last clothoids are each a quarter of the distance between the poses long,
+/// and only their sharpnesses are found, so that the G1 clothoid between
+/// them, from [`fit_g1`], matches their curvatures.  Returns `None` if the
+/// poses coincide, or if no three clothoids of this shape fit, even where
+/// ones with other lengths would.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::clothoid::fit_g2;
+/// use motion_planning::planar::Se2;
+///
+/// // From straight, into a turn of radius 5.
+/// let start = Se2 { x: 0., y: 0., heading: 0. };
+/// let end = Se2 { x: 6., y: 2., heading: 0.8 };
+/// let [first, middle, last] = fit_g2(start, 0., end, 0.2).unwrap();
+///
+/// assert!((first.end_curvature() - middle.curvature).abs() < 1e-9);
+/// assert!((middle.end_curvature() - last.curvature).abs() < 1e-9);
+/// assert!((last.end_curvature() - 0.2).abs() < 1e-9);
+///
+/// let arrival = last.end();
+/// assert!((arrival.x - 6.).abs() < 1e-9 && (arrival.y - 2.).abs() < 1e-9);
+/// ```
+pub fn fit_g2(
+	start: Se2,
+	start_curvature: f64,
+	end: Se2,
+	end_curvature: f64,
+) -> Option<[Clothoid; 3]> {
+	let r = (end.x - start.x).hypot(end.y - start.y);
+
+	if r == 0. || !r.is_finite() {
+		return None;
+	}
+
+	let outer = r / 4.;
+
+	// The three clothoids for the outer sharpnesses `k`, and how far the
+	// middle one's curvature is from theirs at each end.
+	let attempt = |k: (f64, f64)| {
+		let first = Clothoid {
+			start,
+			curvature: start_curvature,
+			sharpness: k.0,
+			length: outer,
+		};
+
+		// The last clothoid, followed backwards from the end.
+		let reversed = Clothoid {
+			start: Se2 {
+				heading: end.heading + PI,
+				..end
+			},
+			curvature: -end_curvature,
+			sharpness: k.1,
+			length: outer,
+		};
+		let join = reversed.follow(outer);
+		let last = Clothoid {
+			start: Se2 {
+				heading: join.heading + PI,
+				..join
+			},
+			curvature: -reversed.end_curvature(),
+			sharpness: k.1,
+			length: outer,
+		};
+
+		let middle = fit_g1(first.follow(outer), last.start)?;
+		let residual = (
+			middle.curvature - first.end_curvature(),
+			middle.end_curvature() - last.curvature,
+		);
+
+		Some(([first, middle, last], residual))
+	};
+
+	let mut k = (0., 0.);
+
+	for _ in 0..FIT_ITERATIONS {
+		let (clothoids, f) = attempt(k)?;
+
+		if f.0.abs().max(f.1.abs()) < CURVATURE_TOLERANCE {
+			return Some(clothoids);
+		}
+
+		// Newton's method, differentiating numerically.
+		let (_, f0) = attempt((k.0 + SHARPNESS_STEP, k.1))?;
+		let (_, f1) = attempt((k.0, k.1 + SHARPNESS_STEP))?;
+		let j = [
+			[(f0.0 - f.0) / SHARPNESS_STEP, (f1.0 - f.0) / SHARPNESS_STEP],
+			[(f0.1 - f.1) / SHARPNESS_STEP, (f1.1 - f.1) / SHARPNESS_STEP],
+		];
+		let determinant = j[0][0] * j[1][1] - j[0][1] * j[1][0];
+
+		if determinant == 0. || !determinant.is_finite() {
+			return None;
+		}
+
+		k.0 -= (j[1][1] * f.0 - j[0][1] * f.1) / determinant;
+		k.1 -= (j[0][0] * f.1 - j[1][0] * f.0) / determinant;
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::Trajectory3;
+	use super::*;
+
+	fn pose(x: f64, y: f64, heading: f64) -> Se2 {
+		Se2 { x, y, heading }
+	}
+
+	fn assert_same_pose(a: Se2, b: Se2, tolerance: f64) {
+		assert_f64_roughly_eq!(a.x, b.x, tolerance);
+		assert_f64_roughly_eq!(a.y, b.y, tolerance);
+		assert_f64_roughly_eq!(wrap(a.heading - b.heading), 0., tolerance);
+	}
+
+	/// Integrates the unit vector at `angle(t)` from `0` to `length` by
+	/// Simpson's rule.
+	fn simpson(angle: impl Fn(f64) -> f64, length: f64) -> (f64, f64) {
+		let n = 20_000;
+		let h = length / f64::from(n);
+
+		(0..=n)
+			.map(|i| {
+				let weight = if i == 0 || i == n {
+					1.
+				} else if i % 2 == 1 {
+					4.
+				} else {
+					2.
+				};
+
+				scale(unit(angle(f64::from(i) * h)), weight * h / 3.)
+			})
+			.fold((0., 0.), add)
+	}
+
+	#[test]
+	fn fresnel_integrals() {
+		// Either side of where the series gives way to the continued fraction.
+		for &(x, c, s) in &[
+			(0., 0., 0.),
+			(0.3, 0.299_400_976_052_047_2, 0.014_116_998_006_576_586),
+			(1.2, 0.715_437_722_923_073_4, 0.623_400_918_546_249_7),
+			(1.5, 0.445_261_176_039_821_5, 0.697_504_960_082_093),
+			(1.500_001, 0.445_260_252_161_190_7, 0.697_504_577_396_483_8),
+			(2., 0.488_253_406_075_340_8, 0.343_415_678_363_698_2),
+			(3.7, 0.541_945_662_154_487_6, 0.574_980_349_887_472_8),
+			(10., 0.499_898_694_205_515_7, 0.468_169_978_584_882_2),
+		] {
+			let (cx, sx) = fresnel(x);
+
+			assert_f64_roughly_eq!(cx, c, 1e-15);
+			assert_f64_roughly_eq!(sx, s, 1e-15);
+			assert_eq!(fresnel(-x), (-cx, -sx));
+		}
+	}
+
+	#[test]
+	fn clothoid_points() {
+		for &(curvature, sharpness, length) in &[
+			(0., 0.3, 4.),
+			(0.5, -0.2, 6.),
+			(-1., 1e-9, 3.),
+			(2., 0., 2.),
+			(0., 0., 5.),
+			(0.01, 2., 0.001),
+		] {
+			let clothoid = Clothoid {
+				start: pose(1., 2., 0.7),
+				curvature,
+				sharpness,
+				length,
+			};
+
+			for i in 0..=4 {
+				let s = length * f64::from(i) / 4.;
+				let (dx, dy) = simpson(|t| 0.7 + curvature * t + sharpness * t * t / 2., s);
+				let expected = pose(
+					1. + dx,
+					2. + dy,
+					0.7 + curvature * s + sharpness * s * s / 2.,
+				);
+
+				assert_same_pose(clothoid.pose_at(s).unwrap(), expected, 1e-10);
+				assert_f64_roughly_eq!(clothoid.curvature_at(s).unwrap(), curvature + sharpness * s);
+			}
+
+			assert_eq!(clothoid.pose_at(length * 1.5), None);
+		}
+	}
+
+	#[test]
+	fn g1_recovers_clothoids() {
+		for &(curvature, sharpness, length) in &[
+			(0., 0.3, 4.),
+			(0.5, -0.2, 6.),
+			(-0.4, 0.05, 3.),
+			(0.2, 0., 2.),
+		] {
+			let clothoid = Clothoid {
+				start: pose(-1., 0.5, 2.),
+				curvature,
+				sharpness,
+				length,
+			};
+			let fitted = fit_g1(clothoid.start, clothoid.end()).unwrap();
+
+			assert_f64_roughly_eq!(fitted.curvature, curvature, 1e-9);
+			assert_f64_roughly_eq!(fitted.sharpness, sharpness, 1e-9);
+			assert_f64_roughly_eq!(fitted.length, length, 1e-9);
+			assert_same_pose(fitted.end(), clothoid.end(), 1e-9);
+		}
+
+		let straight = fit_g1(pose(0., 0., 0.5), pose(0.5_f64.cos(), 0.5_f64.sin(), 0.5)).unwrap();
+		assert_f64_roughly_eq!(straight.length, 1., 1e-12);
+		assert_f64_roughly_eq!(straight.curvature, 0., 1e-12);
+
+		assert_eq!(fit_g1(pose(1., 1., 0.), pose(1., 1., 1.)), None);
+	}
+
+	#[test]
+	fn g2_is_curvature_continuous() {
+		for &(end, start_curvature, end_curvature) in &[
+			(pose(6., 2., 0.8), 0., 0.2),
+			(pose(5., -1., -0.3), 0.3, -0.1),
+			(pose(4., 0., 0.), 0., 0.),
+		] {
+			let start = pose(0., 0., 0.);
+			let [first, middle, last] = fit_g2(start, start_curvature, end, end_curvature).unwrap();
+
+			assert_same_pose(first.start, start, 1e-12);
+			assert_same_pose(first.end(), middle.start, 1e-12);
+			assert_same_pose(middle.end(), last.start, 1e-9);
+			assert_same_pose(last.end(), end, 1e-9);
+
+			assert_f64_roughly_eq!(first.curvature, start_curvature, 1e-12);
+			assert_f64_roughly_eq!(first.end_curvature(), middle.curvature, 1e-8);
+			assert_f64_roughly_eq!(middle.end_curvature(), last.curvature, 1e-8);
+			assert_f64_roughly_eq!(last.end_curvature(), end_curvature, 1e-9);
+		}
+	}
+
+	#[test]
+	fn driven_as_a_trajectory() {
+		let clothoids = fit_g2(pose(0., 0., 0.), 0., pose(6., 2., 0.8), 0.2).unwrap();
+		let length: f64 = clothoids.iter().map(|c| c.length).sum();
+		let path = trajectory(&clothoids, 2., 0.2).unwrap();
+
+		let mut start = 0.;
+
+		for clothoid in &clothoids {
+			for i in 0..=10 {
+				let s = clothoid.length * f64::from(i) / 10.;
+				let t = (start + s) / 2.;
+
+				assert_same_pose(
+					path.position_at(t).unwrap(),
+					clothoid.pose_at(s).unwrap(),
+					1e-7,
+				);
+
+				let velocity = path.velocity_at(t).unwrap();
+				assert_f64_roughly_eq!(velocity.x.hypot(velocity.y), 2., 1e-6);
+				assert_f64_roughly_eq!(
+					velocity.heading,
+					2. * clothoid.curvature_at(s).unwrap(),
+					1e-6
+				);
+			}
+
+			start += clothoid.length;
+		}
+
+		assert_f64_roughly_eq!(path.waypoints().last().unwrap().time, length / 2., 1e-12);
+		assert_eq!(trajectory(&clothoids, -1., 0.2), None);
+	}
+}
@@ -0,0 +1,135 @@
+//! Turning planar trajectories into commands for the wheels of a drive base.
+//!
+//! Each drive takes a trajectory of [`Se2`](crate::planar::Se2) poses, such
+//! as a [`PlanarTrajectory`](crate::planar::PlanarTrajectory), and works out
+//! what its wheels must do to follow it at given times.  Motors have a top
+//! speed, so each drive can also find where those commands ask too much of
+//! them, and either how much slower they must be followed so that none
+//! does, or how to slow all the wheels together wherever one does.  These
+//! only see the commands at the times asked for, and a wheel may peak
+//! higher between them, so sample at least as finely as the motors are
+//! commanded.  A car-like base is limited by its steering instead, and
+//! finds where a trajectory bends too tightly or too quickly for it.
+
+pub mod ackermann;
+pub mod differential;
+pub mod mecanum;
+pub mod swerve;
+
+use super::planar::Se2;
+use super::Trajectory3;
+
+/// A stretch of time over which a wheel is asked to go faster than its
+/// motor allows, or more generally over which any limit is exceeded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exceedance {
+	/// The first sample time over the limit.
+	pub start: f64,
+	/// The last sample time over the limit.
+	pub end: f64,
+	/// The fastest wheel speed, or largest value, asked for in the stretch,
+	/// as a magnitude.
+	pub worst: f64,
+	/// When the worst is asked for.
+	pub time: f64,
+}
+
+/// Finds the stretches of `(time, speed)` samples in which the magnitude of
+/// the speed is over `limit`.
+pub(crate) fn exceedances(
+	samples: impl IntoIterator<Item = (f64, f64)>,
+	limit: f64,
+) -> Vec<Exceedance> {
+	let mut found: Vec<Exceedance> = Vec::new();
+	let mut inside = false;
+
+	for (time, speed) in samples {
+		let speed = speed.abs();
+
+		if speed <= limit {
+			inside = false;
+			continue;
+		}
+
+		match found.last_mut() {
+			Some(current) if inside => {
+				current.end = time;
+
+				if speed > current.worst {
+					current.worst = speed;
+					current.time = time;
+				}
+			}
+			_ => found.push(Exceedance {
+				start: time,
+				end: time,
+				worst: speed,
+				time,
+			}),
+		}
+
+		inside = true;
+	}
+
+	found
+}
+
+/// Finds how many times as long a trajectory must take so that no speed
+/// among `speeds` goes over `limit`, which is at least `1`.  Speeds between
+/// the samples they were taken at are not seen.
+pub(crate) fn slowdown(speeds: impl IntoIterator<Item = f64>, limit: f64) -> f64 {
+	speeds
+		.into_iter()
+		.fold(1_f64, |k, speed| k.max(speed.abs() / limit))
+}
+
+/// Finds the velocity of a base following `trajectory` at time `t`, in its
+/// own frame: `x` forwards, `y` to the left, and `heading` its turn rate.
+pub(crate) fn body_velocity<T: Trajectory3<Se2>>(trajectory: &T, t: f64) -> Option<Se2> {
+	let heading = trajectory.position_at(t)?.heading;
+	let velocity = trajectory.velocity_at(t)?;
+	let (sin, cos) = heading.sin_cos();
+
+	Some(Se2 {
+		x: velocity.x * cos + velocity.y * sin,
+		y: velocity.y * cos - velocity.x * sin,
+		heading: velocity.heading,
+	})
+}
+
+/// Finds the factor, at most `1`, that brings every speed among `speeds`
+/// within `limit`, so that wheels asked for too much all slow down together
+/// and the base keeps its direction of travel.
+pub(crate) fn desaturation(speeds: impl IntoIterator<Item = f64>, limit: f64) -> f64 {
+	slowdown(speeds, limit).recip()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn finds_stretches() {
+		let samples = [(0., 1.), (1., 3.), (2., -4.), (3., 2.), (4., 2.5), (5., 0.)];
+
+		assert_eq!(
+			exceedances(samples.iter().copied(), 2.),
+			vec![
+				Exceedance {
+					start: 1.,
+					end: 2.,
+					worst: 4.,
+					time: 2.,
+				},
+				Exceedance {
+					start: 4.,
+					end: 4.,
+					worst: 2.5,
+					time: 4.,
+				},
+			]
+		);
+		assert_eq!(slowdown(samples.iter().map(|s| s.1), 2.), 2.);
+		assert_eq!(slowdown(samples.iter().map(|s| s.1), 8.), 1.);
+	}
+}
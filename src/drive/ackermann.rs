@@ -0,0 +1,405 @@
+//! Car-like steering, by the bicycle model.
+//!
+//! A car turns by steering its front wheels, and can only follow paths it
+//! can steer round.  Treating each axle as a single wheel on the centre
+//! line, the rear axle follows the path, and steering the front wheel by
+//! `atan(wheelbase × curvature)` keeps it on it.
+//!
+//! Curvatures and their rates of change are taken exactly from the Hermite
+//! segments between waypoints, whose derivatives are known up to jerk.  The
+//! rear axle's heading is taken from the direction of travel; the waypoint
+//! headings only tell whether the car is going forwards or in reverse.
+//!
+//! Unlike the other drives, which take any [`Trajectory3`], this one takes
+//! the timed waypoints themselves.  The rate of steering depends on the jerk,
+//! which a [`Trajectory3`] does not give, so the segments are rebuilt from
+//! their waypoints instead.  A [`PlanarTrajectory`] gives its waypoints with
+//! [`PlanarTrajectory::waypoints`], and plain waypoints can be given times
+//! with [`timed`](crate::timed::timed).
+//!
+//! [`Trajectory3`]: crate::Trajectory3
+//! [`PlanarTrajectory`]: crate::planar::PlanarTrajectory
+//! [`PlanarTrajectory::waypoints`]: crate::planar::PlanarTrajectory::waypoints
+
+use super::super::planar::Se2;
+use super::super::polynomial::{quintic_segment, Polynomial};
+use super::super::timed::{find_segment, Timed};
+use super::super::Pose3;
+use super::{exceedances, Exceedance};
+
+/// How many equal steps each segment is checked in.
+const SAMPLES: usize = 128;
+
+/// What the steering must do at `time`, while the rear axle moves at
+/// `speed`, forwards positive.  The `angle` is anticlockwise from straight
+/// ahead, in radians, and `rate` is its rate of change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SteeringCommand {
+	pub time: f64,
+	pub speed: f64,
+	pub angle: f64,
+	pub rate: f64,
+}
+
+/// Which of a car's limits a trajectory asks too much of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+	/// The path bends more tightly than the car can steer.
+	Curvature,
+	/// The steering must turn faster than it can.
+	SteeringRate,
+}
+
+/// A stretch of the segment after waypoint `segment` over which `limit` is
+/// exceeded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Infeasibility {
+	pub limit: Limit,
+	pub segment: usize,
+	pub exceedance: Exceedance,
+}
+
+/// A car-like base with its axles `wheelbase` apart, able to follow paths
+/// curving by `max_curvature` at most and to steer at `max_steering_rate` at
+/// most.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::drive::ackermann::{Ackermann, Limit};
+/// use motion_planning::planar::Se2;
+/// use motion_planning::timed::timed;
+/// use motion_planning::Pose3;
+///
+/// let cart = Ackermann { wheelbase: 1.2, max_curvature: 0.5, max_steering_rate: 1. };
+///
+/// // A sharp jink to the left, 1 m across in 4 m.
+/// let drive = |x, y| Pose3 {
+///     position: Se2 { x, y, heading: 0. },
+///     velocity: Se2 { x: 2., y: 0., heading: 0. },
+///     acceleration: Se2::default(),
+/// };
+/// let jink = timed(&[drive(0., 0.), drive(2., 0.), drive(4., 1.), drive(6., 1.)]);
+///
+/// let command = cart.command_at(&jink, 1.).unwrap();
+/// assert!((command.speed - 2.).abs() < 1e-12);
+/// assert_eq!(command.angle, 0.);
+///
+/// let problems = cart.check(&jink);
+/// assert!(problems.iter().all(|p| p.segment == 1));
+/// assert!(problems.iter().any(|p| p.limit == Limit::Curvature));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ackermann {
+	pub wheelbase: f64,
+	pub max_curvature: f64,
+	pub max_steering_rate: f64,
+}
+
+/// A segment's `x`, `y` and heading as polynomials in the fraction of the way
+/// through it, with the derivatives of `x` and `y` up to the third.
+struct Piece {
+	x: [Polynomial; 4],
+	y: [Polynomial; 4],
+	heading: Polynomial,
+	duration: f64,
+}
+
+impl Piece {
+	fn new(prec: &Pose3<Se2>, succ: &Pose3<Se2>, duration: f64) -> Self {
+		let axis = |pick: fn(&Se2) -> f64| {
+			let along = |pose: &Pose3<Se2>| Pose3 {
+				position: pick(&pose.position),
+				velocity: pick(&pose.velocity),
+				acceleration: pick(&pose.acceleration),
+			};
+
+			quintic_segment(&along(prec), &along(succ), duration)
+		};
+		let derivatives = |p: Polynomial| {
+			let p1 = p.derivative();
+			let p2 = p1.derivative();
+			let p3 = p2.derivative();
+
+			[p, p1, p2, p3]
+		};
+
+		Piece {
+			x: derivatives(axis(|s| s.x)),
+			y: derivatives(axis(|s| s.y)),
+			heading: axis(|s| s.heading),
+			duration,
+		}
+	}
+
+	/// Finds the signed speed, and the curvature relative to the car's
+	/// forwards direction and its rate of change, a fraction `u` of the way
+	/// through.
+	fn at(&self, u: f64) -> (f64, f64, f64) {
+		let d = |p: &[Polynomial; 4], n: usize| p[n].eval(u) * self.duration.powi(-(n as i32));
+
+		let (vx, vy) = (d(&self.x, 1), d(&self.y, 1));
+		let (ax, ay) = (d(&self.x, 2), d(&self.y, 2));
+		let (jx, jy) = (d(&self.x, 3), d(&self.y, 3));
+
+		let (sin, cos) = self.heading.eval(u).sin_cos();
+		let direction = if vx * cos + vy * sin < 0. { -1. } else { 1. };
+
+		let speed = vx.hypot(vy);
+
+		if speed == 0. {
+			return (0., 0., 0.);
+		}
+
+		let cross = vx * ay - vy * ax;
+		let curvature = cross / speed.powi(3);
+		let rate =
+			(vx * jy - vy * jx) / speed.powi(3) - 3. * cross * (vx * ax + vy * ay) / speed.powi(5);
+
+		(direction * speed, direction * curvature, direction * rate)
+	}
+}
+
+impl Ackermann {
+	/// Finds the steering command from a signed speed, curvature and rate of
+	/// change of curvature.
+	fn steer(&self, time: f64, (speed, curvature, rate): (f64, f64, f64)) -> SteeringCommand {
+		let lever = self.wheelbase * curvature;
+
+		SteeringCommand {
+			time,
+			speed,
+			angle: lever.atan(),
+			rate: self.wheelbase * rate / (1. + lever * lever),
+		}
+	}
+
+	/// Finds how the car must steer to follow `waypoints` at time `t`.
+	///
+	/// Where the car is at rest its steering is undefined, and is given as
+	/// straight ahead.  Returns `None` if `t` is outside the trajectory.
+	///
+	/// This takes waypoints rather than a trajectory, as the steering rate
+	/// needs the jerk between them; see the [module docs](self).
+	pub fn command_at(&self, waypoints: &[Timed<Pose3<Se2>>], t: f64) -> Option<SteeringCommand> {
+		let (prec, succ, u, duration) = find_segment(waypoints, t)?;
+		let piece = Piece::new(&waypoints[prec].pose, &waypoints[succ].pose, duration);
+
+		Some(self.steer(t, piece.at(u)))
+	}
+
+	/// Finds how the car must steer to follow `waypoints` at each of `times`.
+	///
+	/// Returns `None` if any time is outside the trajectory.
+	pub fn commands(
+		&self,
+		waypoints: &[Timed<Pose3<Se2>>],
+		times: &[f64],
+	) -> Option<Vec<SteeringCommand>> {
+		times
+			.iter()
+			.map(|&t| self.command_at(waypoints, t))
+			.collect()
+	}
+
+	/// Finds every stretch of `waypoints` that bends more tightly than
+	/// `max_curvature` or needs steering faster than `max_steering_rate`,
+	/// in order of segment.
+	///
+	/// Each segment is checked at evenly spaced times, so stretches are
+	/// found to within a small fraction of the segment.
+	pub fn check(&self, waypoints: &[Timed<Pose3<Se2>>]) -> Vec<Infeasibility> {
+		let mut found = Vec::new();
+
+		for (segment, pair) in waypoints.windows(2).enumerate() {
+			let duration = pair[1].time - pair[0].time;
+
+			if duration <= 0. {
+				continue;
+			}
+
+			let piece = Piece::new(&pair[0].pose, &pair[1].pose, duration);
+			let commands: Vec<(SteeringCommand, f64)> = (0..=SAMPLES)
+				.map(|i| {
+					let u = i as f64 / SAMPLES as f64;
+					let state = piece.at(u);
+
+					(self.steer(pair[0].time + u * duration, state), state.1)
+				})
+				.collect();
+
+			let curvature = commands.iter().map(|(c, curvature)| (c.time, *curvature));
+			let rate = commands.iter().map(|(c, _)| (c.time, c.rate));
+
+			for (limit, exceedance) in exceedances(curvature, self.max_curvature)
+				.into_iter()
+				.map(|e| (Limit::Curvature, e))
+				.chain(
+					exceedances(rate, self.max_steering_rate)
+						.into_iter()
+						.map(|e| (Limit::SteeringRate, e)),
+				) {
+				found.push(Infeasibility {
+					limit,
+					segment,
+					exceedance,
+				});
+			}
+		}
+
+		found
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::super::assert_f64_roughly_eq;
+	use super::super::super::planar::PlanarTrajectory;
+	use super::super::super::timed::timed;
+	use super::*;
+
+	const CART: Ackermann = Ackermann {
+		wheelbase: 1.,
+		max_curvature: 0.8,
+		max_steering_rate: 2.,
+	};
+
+	/// Waypoints on a circle of radius `radius`, facing anticlockwise and
+	/// travelled at `speed`, so backwards when `speed` is negative.
+	fn circle(radius: f64, speed: f64) -> Vec<Timed<Pose3<Se2>>> {
+		let w = speed / radius;
+
+		let at = |t: f64| {
+			let (sin, cos) = (w * t).sin_cos();
+
+			Pose3 {
+				position: Se2 {
+					x: radius * cos,
+					y: radius * sin,
+					heading: w * t + core::f64::consts::FRAC_PI_2,
+				},
+				velocity: Se2 {
+					x: -radius * w * sin,
+					y: radius * w * cos,
+					heading: w,
+				},
+				acceleration: Se2 {
+					x: -radius * w * w * cos,
+					y: -radius * w * w * sin,
+					heading: 0.,
+				},
+			}
+		};
+
+		timed(&[at(0.), at(1.), at(2.)])
+	}
+
+	#[test]
+	fn steering_round_a_circle() {
+		let forwards = circle(2., 1.);
+
+		for t in [0., 0.5, 1., 2.] {
+			let command = CART.command_at(&forwards, t).unwrap();
+
+			assert_f64_roughly_eq!(command.speed, 1., 1e-3);
+			assert_f64_roughly_eq!(command.angle, 0.5_f64.atan(), 1e-3);
+			assert!(command.rate.abs() < 1e-2);
+		}
+
+		// The same waypoints given through a planar trajectory steer the same.
+		let planar = PlanarTrajectory::new(&forwards);
+		assert_eq!(
+			CART.command_at(planar.waypoints(), 0.5),
+			CART.command_at(&forwards, 0.5)
+		);
+
+		// Backing round the same circle the other way steers the same way.
+		let reverse = circle(2., -1.);
+		let command = CART.command_at(&reverse, 1.).unwrap();
+
+		assert_f64_roughly_eq!(command.speed, -1., 1e-12);
+		assert_f64_roughly_eq!(command.angle, 0.5_f64.atan(), 1e-12);
+
+		assert!(CART.check(&forwards).is_empty());
+		assert_eq!(CART.command_at(&forwards, 2.5), None);
+	}
+
+	#[test]
+	fn rates_match_differences() {
+		let jink = timed(&[
+			Pose3 {
+				position: Se2 {
+					x: 0.,
+					y: 0.,
+					heading: 0.,
+				},
+				velocity: Se2 {
+					x: 2.,
+					y: 0.,
+					heading: 0.,
+				},
+				acceleration: Se2::default(),
+			},
+			Pose3 {
+				position: Se2 {
+					x: 3.,
+					y: 0.5,
+					heading: 0.3,
+				},
+				velocity: Se2 {
+					x: 1.8,
+					y: 0.5,
+					heading: 0.,
+				},
+				acceleration: Se2 {
+					x: 0.,
+					y: -0.5,
+					heading: 0.,
+				},
+			},
+		]);
+		let dt = 1e-6;
+
+		for t in [0.1, 0.4, 0.8] {
+			let command = CART.command_at(&jink, t).unwrap();
+			let after = CART.command_at(&jink, t + dt).unwrap();
+			let before = CART.command_at(&jink, t - dt).unwrap();
+
+			assert_f64_roughly_eq!(command.rate, (after.angle - before.angle) / (2. * dt), 1e-6);
+		}
+	}
+
+	#[test]
+	fn flags_tight_and_quick_bends() {
+		// Too tight a circle for the cart.
+		let tight = circle(1., 1.);
+		let problems = CART.check(&tight);
+
+		assert_eq!(problems.len(), 2);
+		assert!(problems.iter().all(|p| p.limit == Limit::Curvature));
+		assert_eq!(problems[1].segment, 1);
+		assert_f64_roughly_eq!(problems[0].exceedance.worst, 1., 1e-2);
+		assert_f64_roughly_eq!(problems[0].exceedance.start, 0.);
+		assert_f64_roughly_eq!(problems[0].exceedance.end, 1.);
+
+		// A gentle circle, but driven fast enough to need steering quickly
+		// at its ends, where it meets straight lines.
+		let straight = |x: f64, y: f64, vx: f64, vy: f64| Pose3 {
+			position: Se2 {
+				x,
+				y,
+				heading: vy.atan2(vx),
+			},
+			velocity: Se2 {
+				x: vx,
+				y: vy,
+				heading: 0.,
+			},
+			acceleration: Se2::default(),
+		};
+		let swerve = timed(&[straight(0., 0., 4., 0.), straight(4., 1., 4., 0.)]);
+		let problems = CART.check(&swerve);
+
+		assert!(problems.iter().any(|p| p.limit == Limit::SteeringRate));
+	}
+}
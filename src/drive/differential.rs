@@ -0,0 +1,309 @@
+//! Differential drive: a wheel either side on a common axle, steered by
+//! driving them at different speeds.
+//!
+//! Such a base can only move along its heading, forwards or backwards, and
+//! turn.  Its trajectory's headings must therefore follow its direction of
+//! travel; any sideways velocity is ignored, as the wheels cannot provide
+//! it.
+
+use super::super::planar::Se2;
+use super::super::Trajectory3;
+use super::{body_velocity, exceedances, slowdown, Exceedance};
+
+/// A value for each wheel of a differential drive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wheels {
+	pub left: f64,
+	pub right: f64,
+}
+
+/// What the wheels must do at `time`: their surface speeds, forwards
+/// positive, and the rates of change of those.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WheelCommand {
+	pub time: f64,
+	pub velocity: Wheels,
+	pub acceleration: Wheels,
+}
+
+/// A differential drive with its wheels `track_width` apart, each able to
+/// reach `max_wheel_speed` at most.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::drive::differential::DifferentialDrive;
+/// use motion_planning::planar::{PlanarTrajectory, Se2};
+/// use motion_planning::Pose3;
+///
+/// // Drive 1 m forwards along x, turning a quarter turn left on the way.
+/// let path = PlanarTrajectory::untimed(&[
+///     Pose3 {
+///         position: Se2 { x: 0., y: 0., heading: 0. },
+///         velocity: Se2 { x: 1., y: 0., heading: 0. },
+///         acceleration: Se2::default(),
+///     },
+///     Pose3 {
+///         position: Se2 { x: 1., y: 1., heading: std::f64::consts::FRAC_PI_2 },
+///         velocity: Se2 { x: 0., y: 1., heading: 1. },
+///         acceleration: Se2::default(),
+///     },
+/// ]);
+/// let drive = DifferentialDrive { track_width: 0.6, max_wheel_speed: 1.2 };
+///
+/// let end = drive.command_at(&path, 1.).unwrap();
+/// assert!((end.velocity.left - 0.7).abs() < 1e-12);
+/// assert!((end.velocity.right - 1.3).abs() < 1e-12);
+///
+/// // Somewhere, a wheel needs more than 1.2 m/s.
+/// let times: Vec<f64> = (0..=100).map(|i| f64::from(i) / 100.).collect();
+/// let commands = drive.commands(&path, &times).unwrap();
+/// assert!(!drive.exceedances(&commands).is_empty());
+///
+/// // Following the path more slowly fixes that.
+/// let slower = drive.retime(&commands);
+/// assert!(drive.exceedances(&slower).is_empty());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifferentialDrive {
+	pub track_width: f64,
+	pub max_wheel_speed: f64,
+}
+
+impl DifferentialDrive {
+	/// Finds what the wheels must do to follow `trajectory` at time `t`.
+	///
+	/// Returns `None` if `t` is outside the trajectory.
+	pub fn command_at<T: Trajectory3<Se2>>(&self, trajectory: &T, t: f64) -> Option<WheelCommand> {
+		let velocity = body_velocity(trajectory, t)?;
+		let heading = trajectory.position_at(t)?.heading;
+		let acceleration = trajectory.acceleration_at(t)?;
+
+		let (sin, cos) = heading.sin_cos();
+
+		// The rate of change of the speed along the heading, which includes
+		// the heading turning towards any sideways velocity.
+		let forward_rate = acceleration.x * cos + acceleration.y * sin + velocity.heading * velocity.y;
+
+		let half = self.track_width / 2.;
+
+		Some(WheelCommand {
+			time: t,
+			velocity: Wheels {
+				left: velocity.x - velocity.heading * half,
+				right: velocity.x + velocity.heading * half,
+			},
+			acceleration: Wheels {
+				left: forward_rate - acceleration.heading * half,
+				right: forward_rate + acceleration.heading * half,
+			},
+		})
+	}
+
+	/// Finds what the wheels must do to follow `trajectory` at each of
+	/// `times`.
+	///
+	/// Returns `None` if any time is outside the trajectory.
+	pub fn commands<T: Trajectory3<Se2>>(
+		&self,
+		trajectory: &T,
+		times: &[f64],
+	) -> Option<Vec<WheelCommand>> {
+		times
+			.iter()
+			.map(|&t| self.command_at(trajectory, t))
+			.collect()
+	}
+
+	/// Finds the stretches of `commands` in which either wheel is asked to
+	/// go faster than `max_wheel_speed`.
+	///
+	/// Only the commands themselves are checked, so a wheel speed peaking
+	/// over the limit between two of them is missed.  Sample the trajectory
+	/// at least as finely as the wheels are commanded.
+	pub fn exceedances(&self, commands: &[WheelCommand]) -> Vec<Exceedance> {
+		exceedances(
+			commands
+				.iter()
+				.map(|c| (c.time, c.velocity.left.abs().max(c.velocity.right.abs()))),
+			self.max_wheel_speed,
+		)
+	}
+
+	/// Finds how many times as long `commands` must be made to take so that
+	/// neither wheel goes faster than `max_wheel_speed` in any of them, which
+	/// is at least `1`.  As with
+	/// [`exceedances`](DifferentialDrive::exceedances), speeds between the
+	/// commands are not seen.
+	///
+	/// Scaling the trajectory itself by this much, with
+	/// [`scale_time`](crate::transform::scale_time), gives the same
+	/// commands as [`retime`](DifferentialDrive::retime).
+	pub fn slowdown(&self, commands: &[WheelCommand]) -> f64 {
+		slowdown(
+			commands
+				.iter()
+				.flat_map(|c| [c.velocity.left, c.velocity.right]),
+			self.max_wheel_speed,
+		)
+	}
+
+	/// Stretches `commands` in time, about the first, just enough that
+	/// neither wheel goes faster than `max_wheel_speed` in any of them.
+	pub fn retime(&self, commands: &[WheelCommand]) -> Vec<WheelCommand> {
+		let k = self.slowdown(commands);
+		let start = commands.first().map_or(0., |first| first.time);
+
+		let scaled = |wheels: Wheels, factor: f64| Wheels {
+			left: wheels.left * factor,
+			right: wheels.right * factor,
+		};
+
+		commands
+			.iter()
+			.map(|c| WheelCommand {
+				time: start + (c.time - start) * k,
+				velocity: scaled(c.velocity, k.recip()),
+				acceleration: scaled(c.acceleration, k.powi(-2)),
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::super::assert_f64_roughly_eq;
+	use super::super::super::planar::PlanarTrajectory;
+	use super::super::super::timed::Timed;
+	use super::super::super::transform::scale_time;
+	use super::super::super::Pose3;
+	use super::*;
+
+	const DRIVE: DifferentialDrive = DifferentialDrive {
+		track_width: 0.5,
+		max_wheel_speed: 1.6,
+	};
+
+	/// An S-bend, with the heading following the direction of travel.
+	fn s_bend() -> PlanarTrajectory {
+		let at = |time, x, y, heading: f64, speed: f64, turn| Timed {
+			time,
+			pose: Pose3 {
+				position: Se2 { x, y, heading },
+				velocity: Se2 {
+					x: speed * heading.cos(),
+					y: speed * heading.sin(),
+					heading: turn,
+				},
+				acceleration: Se2::default(),
+			},
+		};
+
+		PlanarTrajectory::new(&[
+			at(0., 0., 0., 0., 0., 0.),
+			at(1.5, 1.5, 0.5, 0.6, 1.5, 0.8),
+			at(3., 3., 0.5, -0.6, 1.5, -0.8),
+			at(4., 4., 0., 0., 0., 0.),
+		])
+	}
+
+	#[test]
+	fn wheel_speeds() {
+		let path = PlanarTrajectory::untimed(&[
+			Pose3 {
+				position: Se2::default(),
+				velocity: Se2 {
+					x: 0.,
+					y: 0.,
+					heading: 2.,
+				},
+				acceleration: Se2::default(),
+			},
+			Pose3 {
+				position: Se2 {
+					x: 0.,
+					y: 0.,
+					heading: 2.,
+				},
+				velocity: Se2 {
+					x: -1.,
+					y: 0.,
+					heading: 0.,
+				},
+				acceleration: Se2::default(),
+			},
+		]);
+
+		// Turning in place drives the wheels in opposite directions.
+		let start = DRIVE.command_at(&path, 0.).unwrap();
+		assert_f64_roughly_eq!(start.velocity.left, -0.5);
+		assert_f64_roughly_eq!(start.velocity.right, 0.5);
+
+		// Only the part of the velocity along the heading reaches the wheels.
+		let end = DRIVE.command_at(&path, 1.).unwrap();
+		assert_f64_roughly_eq!(end.velocity.left, -2_f64.cos(), 1e-15);
+		assert_f64_roughly_eq!(end.velocity.right, -2_f64.cos(), 1e-15);
+
+		assert_eq!(DRIVE.command_at(&path, 1.5), None);
+	}
+
+	#[test]
+	fn accelerations_match_differences() {
+		let path = s_bend();
+		let dt = 1e-6;
+
+		for t in [0.4, 1.2, 2.1, 3.5] {
+			let command = DRIVE.command_at(&path, t).unwrap();
+			let after = DRIVE.command_at(&path, t + dt).unwrap();
+			let before = DRIVE.command_at(&path, t - dt).unwrap();
+
+			let left = (after.velocity.left - before.velocity.left) / (2. * dt);
+			let right = (after.velocity.right - before.velocity.right) / (2. * dt);
+
+			assert_f64_roughly_eq!(command.acceleration.left, left, 1e-6);
+			assert_f64_roughly_eq!(command.acceleration.right, right, 1e-6);
+		}
+	}
+
+	#[test]
+	fn limits_and_retiming() {
+		let path = s_bend();
+		let times: Vec<f64> = (0..=400).map(|i| f64::from(i) / 100.).collect();
+		let commands = DRIVE.commands(&path, &times).unwrap();
+
+		let found = DRIVE.exceedances(&commands);
+		assert!(!found.is_empty());
+
+		for exceedance in &found {
+			assert!(exceedance.worst > DRIVE.max_wheel_speed);
+			assert!(exceedance.start <= exceedance.time && exceedance.time <= exceedance.end);
+		}
+
+		let k = DRIVE.slowdown(&commands);
+		let retimed = DRIVE.retime(&commands);
+		let peak = retimed
+			.iter()
+			.map(|c| c.velocity.left.abs().max(c.velocity.right.abs()))
+			.fold(0., f64::max);
+
+		assert!(k > 1.);
+		assert_f64_roughly_eq!(peak, DRIVE.max_wheel_speed, 1e-12);
+		assert!(DRIVE.exceedances(&retimed).is_empty());
+
+		// The same as slowing down the trajectory itself.
+		let slow = PlanarTrajectory::new(&scale_time(path.waypoints(), k).unwrap());
+
+		for command in retimed.iter().step_by(37) {
+			let expected = DRIVE.command_at(&slow, command.time).unwrap();
+
+			assert_f64_roughly_eq!(command.velocity.left, expected.velocity.left, 1e-12);
+			assert_f64_roughly_eq!(
+				command.acceleration.right,
+				expected.acceleration.right,
+				1e-12
+			);
+		}
+
+		assert_eq!(DRIVE.commands(&path, &[1., 5.]), None);
+	}
+}
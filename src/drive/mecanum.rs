@@ -0,0 +1,244 @@
+//! Mecanum drive: four wheels with rollers at 45°, which let the base move
+//! in any direction without steering.
+//!
+//! The rollers are taken to be arranged in the usual X pattern seen from
+//! above, so that driving the front-left and rear-right wheels forwards and
+//! the others backwards moves the base to the right.
+
+use super::super::planar::Se2;
+use super::super::Trajectory3;
+use super::{body_velocity, desaturation};
+
+/// A value for each wheel of a mecanum drive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MecanumWheels {
+	pub front_left: f64,
+	pub front_right: f64,
+	pub rear_left: f64,
+	pub rear_right: f64,
+}
+
+impl MecanumWheels {
+	fn speeds(&self) -> [f64; 4] {
+		[
+			self.front_left,
+			self.front_right,
+			self.rear_left,
+			self.rear_right,
+		]
+	}
+}
+
+/// The surface speeds the wheels must drive at, forwards positive, at
+/// `time`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MecanumCommand {
+	pub time: f64,
+	pub wheels: MecanumWheels,
+}
+
+/// A mecanum drive with its front and rear axles `wheelbase` apart, its left
+/// and right wheels `track_width` apart, and each wheel able to reach
+/// `max_wheel_speed` at most.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::drive::mecanum::MecanumDrive;
+/// use motion_planning::planar::{PlanarTrajectory, Se2};
+/// use motion_planning::Pose3;
+///
+/// let drive = MecanumDrive { wheelbase: 0.5, track_width: 0.5, max_wheel_speed: 3. };
+///
+/// // Strafe right at 1 m/s.
+/// let strafe = |y| Pose3 {
+///     position: Se2 { x: 0., y, heading: 0. },
+///     velocity: Se2 { x: 0., y: -1., heading: 0. },
+///     acceleration: Se2::default(),
+/// };
+/// let path = PlanarTrajectory::untimed(&[strafe(0.), strafe(-1.)]);
+///
+/// let wheels = drive.command_at(&path, 0.5).unwrap().wheels;
+///
+/// assert!((wheels.front_left - 1.).abs() < 1e-12);
+/// assert!((wheels.front_right + 1.).abs() < 1e-12);
+/// assert!((wheels.rear_left + 1.).abs() < 1e-12);
+/// assert!((wheels.rear_right - 1.).abs() < 1e-12);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MecanumDrive {
+	pub wheelbase: f64,
+	pub track_width: f64,
+	pub max_wheel_speed: f64,
+}
+
+impl MecanumDrive {
+	/// Finds what the wheels must do to follow `trajectory` at time `t`.
+	///
+	/// Where any wheel would go faster than `max_wheel_speed`, all of them
+	/// are slowed in proportion.
+	///
+	/// Returns `None` if `t` is outside the trajectory.
+	pub fn command_at<T: Trajectory3<Se2>>(&self, trajectory: &T, t: f64) -> Option<MecanumCommand> {
+		let velocity = body_velocity(trajectory, t)?;
+
+		// How far each wheel's rollers move for the base to turn at unit rate.
+		let lever = (self.wheelbase + self.track_width) / 2.;
+		let turn = velocity.heading * lever;
+
+		let wheels = MecanumWheels {
+			front_left: velocity.x - velocity.y - turn,
+			front_right: velocity.x + velocity.y + turn,
+			rear_left: velocity.x + velocity.y - turn,
+			rear_right: velocity.x - velocity.y + turn,
+		};
+
+		let factor = desaturation(wheels.speeds(), self.max_wheel_speed);
+
+		Some(MecanumCommand {
+			time: t,
+			wheels: MecanumWheels {
+				front_left: wheels.front_left * factor,
+				front_right: wheels.front_right * factor,
+				rear_left: wheels.rear_left * factor,
+				rear_right: wheels.rear_right * factor,
+			},
+		})
+	}
+
+	/// Finds what the wheels must do to follow `trajectory` at each of
+	/// `times`.
+	///
+	/// Returns `None` if any time is outside the trajectory.
+	pub fn commands<T: Trajectory3<Se2>>(
+		&self,
+		trajectory: &T,
+		times: &[f64],
+	) -> Option<Vec<MecanumCommand>> {
+		times
+			.iter()
+			.map(|&t| self.command_at(trajectory, t))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::super::assert_f64_roughly_eq;
+	use super::super::super::planar::PlanarTrajectory;
+	use super::super::super::Pose3;
+	use super::*;
+
+	const DRIVE: MecanumDrive = MecanumDrive {
+		wheelbase: 0.6,
+		track_width: 0.4,
+		max_wheel_speed: 2.,
+	};
+
+	fn path(velocity: Se2, heading: f64) -> PlanarTrajectory {
+		let at = |t: f64| Pose3 {
+			position: Se2 {
+				x: velocity.x * t,
+				y: velocity.y * t,
+				heading: heading + velocity.heading * t,
+			},
+			velocity,
+			acceleration: Se2::default(),
+		};
+
+		PlanarTrajectory::untimed(&[at(0.), at(1.)])
+	}
+
+	#[test]
+	fn driving_and_turning() {
+		let forward = DRIVE
+			.command_at(
+				&path(
+					Se2 {
+						x: 1.,
+						y: 0.,
+						heading: 0.,
+					},
+					0.,
+				),
+				0.5,
+			)
+			.unwrap();
+
+		assert_eq!(forward.wheels.speeds(), [1.; 4]);
+
+		let spin = DRIVE
+			.command_at(
+				&path(
+					Se2 {
+						x: 0.,
+						y: 0.,
+						heading: 1.,
+					},
+					0.,
+				),
+				0.5,
+			)
+			.unwrap();
+
+		assert_eq!(spin.wheels.speeds(), [-0.5, 0.5, -0.5, 0.5]);
+	}
+
+	#[test]
+	fn heading_changes_wheel_speeds() {
+		// Moving along the field's y axis while facing along it is driving
+		// forwards.
+		let wheels = DRIVE
+			.command_at(
+				&path(
+					Se2 {
+						x: 0.,
+						y: 1.,
+						heading: 0.,
+					},
+					core::f64::consts::FRAC_PI_2,
+				),
+				0.5,
+			)
+			.unwrap()
+			.wheels;
+
+		for speed in wheels.speeds().iter() {
+			assert_f64_roughly_eq!(*speed, 1., 1e-12);
+		}
+	}
+
+	#[test]
+	fn desaturates_together() {
+		let command = DRIVE
+			.command_at(
+				&path(
+					Se2 {
+						x: 2.,
+						y: 1.,
+						heading: 0.,
+					},
+					0.,
+				),
+				0.,
+			)
+			.unwrap();
+
+		// Unlimited, the wheels would go 1, 3, 3 and 1.
+		let expected = [2. / 3., 2., 2., 2. / 3.];
+
+		for (speed, expected) in command.wheels.speeds().iter().zip(&expected) {
+			assert_f64_roughly_eq!(speed, expected, 1e-15);
+		}
+
+		let times = [0., 0.5, 1.];
+		assert_eq!(
+			DRIVE
+				.commands(&path(Se2::default(), 0.), &times)
+				.unwrap()
+				.len(),
+			3
+		);
+		assert_eq!(DRIVE.commands(&path(Se2::default(), 0.), &[2.]), None);
+	}
+}
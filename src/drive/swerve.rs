@@ -0,0 +1,257 @@
+//! Swerve drive: wheels on modules that each steer independently.
+//!
+//! Each module's wheel must roll along the velocity of the point of the base
+//! it is mounted at, which is the base's velocity plus the part due to its
+//! turning.  A module can point either way along that line, driving its
+//! wheel forwards or backwards; it is kept pointing whichever way is nearer
+//! to where it pointed before, so that it never has to turn more than a
+//! quarter turn between samples.
+
+use core::f64::consts::{FRAC_PI_2, PI};
+
+use super::super::planar::{wrap, Se2};
+use super::super::Trajectory3;
+use super::{body_velocity, desaturation};
+
+/// Wheel speeds below this are taken as standing still, leaving the module
+/// pointing as it was.
+const STANDING: f64 = 1e-9;
+
+/// What one module must do: drive its wheel at `speed`, forwards positive,
+/// while pointing at `angle`, in `(-π, π]` anticlockwise from the base's
+/// forwards direction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModuleState {
+	pub speed: f64,
+	pub angle: f64,
+}
+
+/// What every module must do at `time`, in the order the modules were given.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwerveCommand {
+	pub time: f64,
+	pub modules: Vec<ModuleState>,
+}
+
+/// A swerve drive with modules at `modules`, each as `(x, y)` from the
+/// centre of the base, with `x` forwards and `y` to the left, and each able
+/// to drive its wheel at `max_wheel_speed` at most.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::drive::swerve::SwerveDrive;
+/// use motion_planning::planar::{PlanarTrajectory, Se2};
+/// use motion_planning::Pose3;
+///
+/// let drive = SwerveDrive {
+///     modules: vec![(0.3, 0.3), (0.3, -0.3), (-0.3, 0.3), (-0.3, -0.3)],
+///     max_wheel_speed: 4.,
+/// };
+///
+/// // Strafe left at 1 m/s without turning.
+/// let strafe = |y| Pose3 {
+///     position: Se2 { x: 0., y, heading: 0. },
+///     velocity: Se2 { x: 0., y: 1., heading: 0. },
+///     acceleration: Se2::default(),
+/// };
+/// let path = PlanarTrajectory::untimed(&[strafe(0.), strafe(1.)]);
+///
+/// let command = drive.command_at(&path, 0.5, None).unwrap();
+///
+/// for module in &command.modules {
+///     assert!((module.speed - 1.).abs() < 1e-12);
+///     assert!((module.angle - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwerveDrive {
+	pub modules: Vec<(f64, f64)>,
+	pub max_wheel_speed: f64,
+}
+
+impl SwerveDrive {
+	/// Finds what the modules must do to follow `trajectory` at time `t`.
+	///
+	/// Each module points whichever way is nearer to how it pointed in
+	/// `previous`, or to straight ahead if there is no previous command.
+	/// Where any wheel would go faster than `max_wheel_speed`, all of them
+	/// are slowed in proportion.
+	///
+	/// Returns `None` if `t` is outside the trajectory.
+	pub fn command_at<T: Trajectory3<Se2>>(
+		&self,
+		trajectory: &T,
+		t: f64,
+		previous: Option<&SwerveCommand>,
+	) -> Option<SwerveCommand> {
+		let velocity = body_velocity(trajectory, t)?;
+
+		let mut modules: Vec<ModuleState> = self
+			.modules
+			.iter()
+			.enumerate()
+			.map(|(i, &(x, y))| {
+				let before = previous
+					.and_then(|command| command.modules.get(i))
+					.map_or(0., |module| module.angle);
+
+				let vx = velocity.x - velocity.heading * y;
+				let vy = velocity.y + velocity.heading * x;
+				let speed = vx.hypot(vy);
+
+				if speed < STANDING {
+					return ModuleState {
+						speed: 0.,
+						angle: before,
+					};
+				}
+
+				let angle = vy.atan2(vx);
+
+				// Turning more than a quarter turn is never needed, since
+				// pointing the other way and reversing the wheel is the same.
+				if wrap(angle - before).abs() > FRAC_PI_2 {
+					ModuleState {
+						speed: -speed,
+						angle: wrap(angle + PI),
+					}
+				} else {
+					ModuleState { speed, angle }
+				}
+			})
+			.collect();
+
+		let factor = desaturation(modules.iter().map(|m| m.speed), self.max_wheel_speed);
+
+		for module in &mut modules {
+			module.speed *= factor;
+		}
+
+		Some(SwerveCommand { time: t, modules })
+	}
+
+	/// Finds what the modules must do to follow `trajectory` at each of
+	/// `times`, with each module pointing whichever way is nearer to how it
+	/// pointed at the time before.
+	///
+	/// Returns `None` if any time is outside the trajectory.
+	pub fn commands<T: Trajectory3<Se2>>(
+		&self,
+		trajectory: &T,
+		times: &[f64],
+	) -> Option<Vec<SwerveCommand>> {
+		let mut commands: Vec<SwerveCommand> = Vec::with_capacity(times.len());
+
+		for &t in times {
+			let command = self.command_at(trajectory, t, commands.last())?;
+
+			commands.push(command);
+		}
+
+		Some(commands)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::super::assert_f64_roughly_eq;
+	use super::super::super::planar::PlanarTrajectory;
+	use super::super::super::Pose3;
+	use super::*;
+
+	fn drive() -> SwerveDrive {
+		SwerveDrive {
+			modules: vec![(0.3, 0.4), (0.3, -0.4), (-0.3, 0.4), (-0.3, -0.4)],
+			max_wheel_speed: 3.,
+		}
+	}
+
+	fn moving(x: f64, y: f64, heading: f64, velocity: Se2) -> Pose3<Se2> {
+		Pose3 {
+			position: Se2 { x, y, heading },
+			velocity,
+			acceleration: Se2::default(),
+		}
+	}
+
+	#[test]
+	fn spinning_in_place() {
+		let spin = Se2 {
+			x: 0.,
+			y: 0.,
+			heading: 2.,
+		};
+		let path = PlanarTrajectory::untimed(&[moving(0., 0., 0., spin), moving(0., 0., 2., spin)]);
+
+		let command = drive().command_at(&path, 0.5, None).unwrap();
+
+		for (module, &(x, y)) in command.modules.iter().zip(&drive().modules) {
+			// Each wheel rolls at right angles to the line from the centre.
+			assert_f64_roughly_eq!(module.speed.abs(), 2. * 0.5, 1e-12);
+			assert_f64_roughly_eq!(module.angle.cos() * x + module.angle.sin() * y, 0., 1e-12);
+		}
+	}
+
+	#[test]
+	fn heading_changes_module_angles() {
+		// Drive along x while facing to its left, so the modules point right.
+		let forward = Se2 {
+			x: 1.,
+			y: 0.,
+			heading: 0.,
+		};
+		let path =
+			PlanarTrajectory::untimed(&[moving(0., 0., 0.5, forward), moving(1., 0., 0.5, forward)]);
+
+		let command = drive().command_at(&path, 0.25, None).unwrap();
+
+		for module in &command.modules {
+			assert_f64_roughly_eq!(module.speed, 1., 1e-12);
+			assert_f64_roughly_eq!(module.angle, -0.5, 1e-12);
+		}
+	}
+
+	#[test]
+	fn no_half_turns() {
+		// Forwards along x, to a stop, then backwards.
+		let path = PlanarTrajectory::untimed(&[
+			moving(0., 0., 0., Se2::default()),
+			moving(1., 0., 0., Se2::default()),
+			moving(0., 0., 0., Se2::default()),
+		]);
+		let times: Vec<f64> = (0..=20).map(|i| f64::from(i) / 10.).collect();
+
+		let commands = drive().commands(&path, &times).unwrap();
+
+		for command in &commands {
+			for module in &command.modules {
+				assert_eq!(module.angle, 0.);
+			}
+		}
+
+		assert!(commands[5].modules[0].speed > 0.);
+		assert!(commands[15].modules[0].speed < 0.);
+		assert_eq!(drive().commands(&path, &[0., 2.5]), None);
+	}
+
+	#[test]
+	fn desaturates_together() {
+		let fast = Se2 {
+			x: 3.,
+			y: 0.,
+			heading: 2.,
+		};
+		let path = PlanarTrajectory::untimed(&[moving(0., 0., 0., fast), moving(3., 0., 2., fast)]);
+
+		let command = drive().command_at(&path, 0., None).unwrap();
+		let speeds: Vec<f64> = command.modules.iter().map(|m| m.speed.abs()).collect();
+
+		// Turning slows the left modules and speeds up the right ones.
+		let ratio = 2.2_f64.hypot(0.6) / 3.8_f64.hypot(0.6);
+
+		assert_f64_roughly_eq!(speeds[0], 3. * ratio, 1e-12);
+		assert_f64_roughly_eq!(speeds[1], 3., 1e-12);
+		assert_f64_roughly_eq!(speeds[2] / speeds[3], ratio, 1e-12);
+	}
+}
@@ -0,0 +1,524 @@
+//! Dubins paths: the shortest way between two poses for a vehicle that only
+//! drives forwards and turns no tighter than a minimum radius.
+//!
+//! Every such shortest path is made of at most three pieces, each a turn at
+//! the minimum radius or a straight line, in one of six [`Word`]s: a turn,
+//! a straight and a turn, or three turns alternating in direction.  Each
+//! word is solved in closed form, and the shortest is the answer.
+//!
+//! A [`DubinsPath`] can be sampled by distance along it, or turned into a
+//! [`PlanarTrajectory`] driven at a constant speed, to be retimed and
+//! followed like any other trajectory in the crate.
+
+use core::f64::consts::{FRAC_PI_2, TAU};
+
+use super::planar::{wrap, PlanarTrajectory, Se2};
+use super::timed::Timed;
+use super::Pose3;
+
+/// Which way one piece of a Dubins path steers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Steer {
+	Left,
+	Straight,
+	Right,
+}
+
+impl Steer {
+	/// The curvature of a piece steering this way, on a unit turning radius.
+	pub(crate) fn curvature(self) -> f64 {
+		match self {
+			Steer::Left => 1.,
+			Steer::Straight => 0.,
+			Steer::Right => -1.,
+		}
+	}
+}
+
+/// The pattern of pieces a Dubins path is made of, with `L` a left turn, `R`
+/// a right turn and `S` a straight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Word {
+	Lsl,
+	Rsr,
+	Lsr,
+	Rsl,
+	Rlr,
+	Lrl,
+}
+
+impl Word {
+	/// Every word, in the order the shortest path is chosen among equals.
+	pub const ALL: [Word; 6] = [
+		Word::Lsl,
+		Word::Rsr,
+		Word::Lsr,
+		Word::Rsl,
+		Word::Rlr,
+		Word::Lrl,
+	];
+
+	/// The way each piece steers.
+	pub fn steering(self) -> [Steer; 3] {
+		use Steer::{Left, Right, Straight};
+
+		match self {
+			Word::Lsl => [Left, Straight, Left],
+			Word::Rsr => [Right, Straight, Right],
+			Word::Lsr => [Left, Straight, Right],
+			Word::Rsl => [Right, Straight, Left],
+			Word::Rlr => [Right, Left, Right],
+			Word::Lrl => [Left, Right, Left],
+		}
+	}
+}
+
+/// A path from `start` of the pieces of `word`, of `lengths`, turning at
+/// `radius`.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::dubins::{shortest, Word};
+/// use motion_planning::planar::Se2;
+/// use motion_planning::Trajectory3;
+///
+/// // Turn round to go back the way we came, a little to the left.
+/// let start = Se2 { x: 0., y: 0., heading: 0. };
+/// let end = Se2 { x: 0., y: 2., heading: std::f64::consts::PI };
+///
+/// let path = shortest(start, end, 1.).unwrap();
+/// assert_eq!(path.word, Word::Lsl);
+/// assert!((path.length() - std::f64::consts::PI).abs() < 1e-12);
+///
+/// // Driven at 2 m/s, it takes half as many seconds as it is metres long.
+/// let trajectory = path.trajectory(2., 0.1).unwrap();
+/// let quarter = trajectory.position_at(path.length() / 4.).unwrap();
+///
+/// assert!((quarter.x - 1.).abs() < 1e-6 && (quarter.y - 1.).abs() < 1e-6);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DubinsPath {
+	pub start: Se2,
+	pub radius: f64,
+	pub word: Word,
+	pub lengths: [f64; 3],
+}
+
+/// Reduces `angle` into `[0, 2π)`.
+fn positive(angle: f64) -> f64 {
+	let reduced = angle.rem_euclid(TAU);
+
+	// Rounding can leave just under 2π as exactly 2π.
+	if reduced == TAU {
+		0.
+	} else {
+		reduced
+	}
+}
+
+/// Finds the path of `word` from `start` to `end`, turning at `radius`, or
+/// `None` if no path of that word joins them.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::dubins::{path, Word};
+/// use motion_planning::planar::Se2;
+///
+/// let start = Se2 { x: 0., y: 0., heading: 0. };
+///
+/// // Three turns can only join poses close together.
+/// let ahead = Se2 { x: 10., y: 0., heading: 0. };
+/// assert!(path(start, ahead, 1., Word::Rlr).is_none());
+/// assert!(path(start, ahead, 1., Word::Lsl).is_some());
+/// ```
+pub fn path(start: Se2, end: Se2, radius: f64, word: Word) -> Option<DubinsPath> {
+	if !(radius > 0. && radius.is_finite()) {
+		return None;
+	}
+
+	// Work in units of the radius, in the frame along the line from `start`
+	// to `end`.
+	let (dx, dy) = (end.x - start.x, end.y - start.y);
+	let d = dx.hypot(dy) / radius;
+	let theta = if d == 0. { 0. } else { dy.atan2(dx) };
+	let a = positive(start.heading - theta);
+	let b = positive(end.heading - theta);
+
+	let (sa, ca) = a.sin_cos();
+	let (sb, cb) = b.sin_cos();
+	let cab = (a - b).cos();
+
+	let [t, p, q] = match word {
+		Word::Lsl => {
+			let p2 = 2. + d * d - 2. * cab + 2. * d * (sa - sb);
+			let tmp = (cb - ca).atan2(d + sa - sb);
+
+			[positive(tmp - a), p2.sqrt(), positive(b - tmp)]
+		}
+		Word::Rsr => {
+			let p2 = 2. + d * d - 2. * cab + 2. * d * (sb - sa);
+			let tmp = (ca - cb).atan2(d - sa + sb);
+
+			[positive(a - tmp), p2.sqrt(), positive(tmp - b)]
+		}
+		Word::Lsr => {
+			let p2 = -2. + d * d + 2. * cab + 2. * d * (sa + sb);
+			let p = p2.sqrt();
+			let tmp = (-ca - cb).atan2(d + sa + sb) - (-2_f64).atan2(p);
+
+			[positive(tmp - a), p, positive(tmp - b)]
+		}
+		Word::Rsl => {
+			let p2 = -2. + d * d + 2. * cab - 2. * d * (sa + sb);
+			let p = p2.sqrt();
+			let tmp = (ca + cb).atan2(d - sa - sb) - 2_f64.atan2(p);
+
+			[positive(a - tmp), p, positive(b - tmp)]
+		}
+		Word::Rlr => {
+			let tmp = (6. - d * d + 2. * cab + 2. * d * (sa - sb)) / 8.;
+			let p = positive(TAU - tmp.acos());
+			let t = positive(a - (ca - cb).atan2(d - sa + sb) + p / 2.);
+
+			[t, p, positive(a - b - t + p)]
+		}
+		Word::Lrl => {
+			let tmp = (6. - d * d + 2. * cab + 2. * d * (sb - sa)) / 8.;
+			let p = positive(TAU - tmp.acos());
+			let t = positive(-a - (ca - cb).atan2(d + sa - sb) + p / 2.);
+
+			[t, p, positive(b - a - t + p)]
+		}
+	};
+
+	// Words that can't join the poses come out with a square root or arc
+	// cosine out of range.
+	if !(t.is_finite() && p.is_finite() && q.is_finite()) {
+		return None;
+	}
+
+	Some(DubinsPath {
+		start,
+		radius,
+		word,
+		lengths: [t * radius, p * radius, q * radius],
+	})
+}
+
+/// Finds the shortest path from `start` to `end` for a vehicle turning no
+/// tighter than `radius`.
+///
+/// Returns `None` if `radius` is not positive and finite.
+pub fn shortest(start: Se2, end: Se2, radius: f64) -> Option<DubinsPath> {
+	Word::ALL
+		.iter()
+		.filter_map(|&word| path(start, end, radius, word))
+		.fold(None, |best: Option<DubinsPath>, candidate| match best {
+			Some(best) if best.length() <= candidate.length() => Some(best),
+			_ => Some(candidate),
+		})
+}
+
+impl DubinsPath {
+	pub fn length(&self) -> f64 {
+		self.lengths.iter().sum()
+	}
+
+	/// Each piece's starting pose, with its heading unwrapped, curvature and
+	/// length.
+	fn pieces(&self) -> [(Se2, f64, f64); 3] {
+		let mut pose = self.start;
+
+		[0, 1, 2].map(|i| {
+			let curvature = self.word.steering()[i].curvature() / self.radius;
+			let piece = (pose, curvature, self.lengths[i]);
+
+			pose = advance(pose, curvature, self.lengths[i]);
+			piece
+		})
+	}
+
+	/// The pose `s` along the path, with its heading unwrapped.
+	fn follow(&self, s: f64) -> Se2 {
+		let mut start = 0.;
+
+		for (i, &(pose, curvature, length)) in self.pieces().iter().enumerate() {
+			if s < start + length || i == 2 {
+				return advance(pose, curvature, s - start);
+			}
+
+			start += length;
+		}
+
+		unreachable!()
+	}
+
+	/// The pose `s` along the path, with its heading in `(-π, π]`, or `None`
+	/// if `s` is not on the path.
+	pub fn pose_at(&self, s: f64) -> Option<Se2> {
+		if !(0. ..=self.length()).contains(&s) {
+			return None;
+		}
+
+		let pose = self.follow(s);
+
+		Some(Se2 {
+			heading: wrap(pose.heading),
+			..pose
+		})
+	}
+
+	/// The pose the path ends at.
+	pub fn end(&self) -> Se2 {
+		let pose = self.follow(self.length());
+
+		Se2 {
+			heading: wrap(pose.heading),
+			..pose
+		}
+	}
+
+	/// Samples the path at points no more than `spacing` apart, including
+	/// both ends and the joins between pieces.
+	///
+	/// Returns `None` if `spacing` is not positive.
+	pub fn sample(&self, spacing: f64) -> Option<Vec<Se2>> {
+		if spacing <= 0. || spacing.is_nan() {
+			return None;
+		}
+
+		Some(
+			distances(&self.lengths, spacing)
+				.into_iter()
+				.filter_map(|s| self.pose_at(s.min(self.length())))
+				.collect(),
+		)
+	}
+
+	/// A trajectory driving the path at a constant `speed`, through waypoints
+	/// no more than `spacing` apart, with the heading along the direction of
+	/// travel.
+	///
+	/// Each waypoint has the path's exact velocity and acceleration.  Where
+	/// the curvature changes, at the joins between pieces, the waypoint is
+	/// given twice at the same time, with the curvature either side, so that
+	/// each piece is followed on its own.  Waypoints on turns are kept within
+	/// a quarter turn of each other.
+	///
+	/// Returns `None` if `speed` or `spacing` is not positive.
+	pub fn trajectory(&self, speed: f64, spacing: f64) -> Option<PlanarTrajectory> {
+		if !(speed > 0. && spacing > 0.) {
+			return None;
+		}
+
+		let spacing = spacing.min(self.radius * FRAC_PI_2);
+		let mut waypoints: Vec<Timed<Pose3<Se2>>> = Vec::new();
+		let mut start = 0.;
+		let mut previous: Option<f64> = None;
+
+		for &(pose, curvature, length) in &self.pieces() {
+			let steps = (length / spacing).ceil() as usize;
+
+			// The first piece, and any after a change of curvature, need their
+			// own waypoint at their start.
+			let first = if previous == Some(curvature) { 1 } else { 0 };
+
+			if steps == 0 && first == 1 {
+				continue;
+			}
+
+			for i in first..=steps {
+				let along = if steps == 0 {
+					0.
+				} else {
+					length * i as f64 / steps as f64
+				};
+				let position = advance(pose, curvature, along);
+				let (sin, cos) = position.heading.sin_cos();
+				let turn = speed * speed * curvature;
+
+				waypoints.push(Timed {
+					time: (start + along) / speed,
+					pose: Pose3 {
+						position,
+						velocity: Se2 {
+							x: speed * cos,
+							y: speed * sin,
+							heading: speed * curvature,
+						},
+						acceleration: Se2 {
+							x: -turn * sin,
+							y: turn * cos,
+							heading: 0.,
+						},
+					},
+				});
+			}
+
+			previous = Some(curvature);
+			start += length;
+		}
+
+		Some(PlanarTrajectory::new(&waypoints))
+	}
+}
+
+/// The distances along a path of pieces of `lengths` to sample at, no more
+/// than `spacing` apart and including the ends of every piece.
+pub(crate) fn distances(lengths: &[f64], spacing: f64) -> Vec<f64> {
+	let mut distances = vec![0.];
+	let mut start = 0.;
+
+	for &length in lengths {
+		let steps = (length / spacing).ceil() as usize;
+
+		distances.extend((1..=steps).map(|i| start + length * i as f64 / steps as f64));
+		start += length;
+	}
+
+	distances
+}
+
+/// Moves `along` from `pose` on a path of constant `curvature`, backwards
+/// if `along` is negative.
+pub(crate) fn advance(pose: Se2, curvature: f64, along: f64) -> Se2 {
+	let heading = pose.heading + curvature * along;
+
+	if curvature == 0. {
+		Se2 {
+			x: pose.x + along * pose.heading.cos(),
+			y: pose.y + along * pose.heading.sin(),
+			heading,
+		}
+	} else {
+		Se2 {
+			x: pose.x + (heading.sin() - pose.heading.sin()) / curvature,
+			y: pose.y - (heading.cos() - pose.heading.cos()) / curvature,
+			heading,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::f64::consts::PI;
+
+	use super::super::assert_f64_roughly_eq;
+	use super::super::Trajectory3;
+	use super::*;
+
+	fn pose(x: f64, y: f64, heading: f64) -> Se2 {
+		Se2 { x, y, heading }
+	}
+
+	fn assert_same_pose_within(a: Se2, b: Se2, tolerance: f64) {
+		assert_f64_roughly_eq!(a.x, b.x, tolerance);
+		assert_f64_roughly_eq!(a.y, b.y, tolerance);
+		assert_f64_roughly_eq!(wrap(a.heading - b.heading), 0., tolerance);
+	}
+
+	fn assert_same_pose(a: Se2, b: Se2) {
+		assert_same_pose_within(a, b, 1e-9);
+	}
+
+	#[test]
+	fn every_word_reaches_the_goal() {
+		let start = pose(1., -2., 0.3);
+		let mut found = 0;
+
+		for end in [
+			pose(6., 3., -2.),
+			pose(1.5, -1., 2.5),
+			pose(-4., 0., PI),
+			pose(0., -3., 0.),
+		] {
+			for word in Word::ALL.iter().copied() {
+				if let Some(path) = path(start, end, 1.5, word) {
+					assert_eq!(path.word, word);
+					assert!(path.lengths.iter().all(|&l| l >= 0.));
+					assert_same_pose(path.end(), end);
+
+					found += 1;
+				}
+			}
+
+			let best = shortest(start, end, 1.5).unwrap();
+
+			for word in Word::ALL.iter().copied() {
+				if let Some(path) = path(start, end, 1.5, word) {
+					assert!(best.length() <= path.length());
+				}
+			}
+		}
+
+		// The three-turn words are only possible when the poses are close.
+		assert!(found > 16);
+	}
+
+	#[test]
+	fn straight_ahead() {
+		let path = shortest(
+			pose(0., 0., 0.5),
+			pose(4. * 0.5_f64.cos(), 4. * 0.5_f64.sin(), 0.5),
+			2.,
+		)
+		.unwrap();
+
+		assert_f64_roughly_eq!(path.length(), 4., 1e-12);
+		assert_f64_roughly_eq!(path.lengths[1], 4., 1e-12);
+
+		let halfway = path.pose_at(2.).unwrap();
+		assert_same_pose(halfway, pose(2. * 0.5_f64.cos(), 2. * 0.5_f64.sin(), 0.5));
+		assert_eq!(path.pose_at(4.5), None);
+		assert_eq!(shortest(pose(0., 0., 0.), pose(1., 0., 0.), 0.), None);
+	}
+
+	#[test]
+	fn turning_around_close_by() {
+		// Facing back the way it came, just to the right: too close to turn
+		// straight round, so the car swings out left first.
+		let path = shortest(pose(0., 0., 0.), pose(0., -0.5, PI), 1.).unwrap();
+
+		assert_eq!(path.word, Word::Lrl);
+		assert_same_pose(path.end(), pose(0., -0.5, PI));
+	}
+
+	#[test]
+	fn sampling() {
+		let path = shortest(pose(0., 0., 0.), pose(3., 4., -1.), 1.).unwrap();
+		let samples = path.sample(0.25).unwrap();
+
+		assert_same_pose(samples[0], path.start);
+		assert_same_pose(*samples.last().unwrap(), path.end());
+
+		for pair in samples.windows(2) {
+			assert!((pair[1].x - pair[0].x).hypot(pair[1].y - pair[0].y) <= 0.25 + 1e-12);
+		}
+
+		assert_eq!(path.sample(0.), None);
+	}
+
+	#[test]
+	fn driven_as_a_trajectory() {
+		let path = shortest(pose(0., 0., 0.), pose(3., 4., -1.), 1.).unwrap();
+		let trajectory = path.trajectory(2., 0.2).unwrap();
+		let end = path.length() / 2.;
+
+		for i in 0..=50 {
+			let t = end * f64::from(i) / 50.;
+			let expected = path.pose_at(2. * t).unwrap();
+
+			assert_same_pose_within(trajectory.position_at(t).unwrap(), expected, 1e-6);
+
+			let velocity = trajectory.velocity_at(t).unwrap();
+			assert_f64_roughly_eq!(velocity.x.hypot(velocity.y), 2., 1e-5);
+		}
+
+		assert_eq!(path.trajectory(0., 0.2), None);
+	}
+}
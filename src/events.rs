@@ -0,0 +1,267 @@
+//! Finding the times at which a trajectory crosses a condition.
+//!
+//! Each [`Condition`] is a function of position or velocity that changes
+//! sign where the condition is crossed.  On each segment that function is a
+//! polynomial, so every crossing is found as a root, however briefly the
+//! trajectory stays across.
+
+use super::extrema::{pieces, Axis, Derivative};
+use super::polynomial::Polynomial;
+use super::timed::Timed;
+use super::vec::Vec3d;
+use super::Pose3;
+
+/// A condition that a trajectory can cross.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Condition {
+	/// The position passes through the plane through `point` perpendicular
+	/// to `normal`.  Rising crossings travel the way `normal` points.
+	Plane {
+		point: Vec3d<f64>,
+		normal: Vec3d<f64>,
+	},
+	/// The position passes through the surface of a sphere.  Rising
+	/// crossings leave it.
+	Sphere { centre: Vec3d<f64>, radius: f64 },
+	/// One coordinate of the position passes through `value`.  Rising
+	/// crossings go from below it to above it.
+	Coordinate { axis: Axis, value: f64 },
+	/// The speed passes through a value.  Rising crossings speed up.
+	Speed(f64),
+}
+
+/// Which way a condition is crossed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+	Rising,
+	Falling,
+}
+
+/// A time at which a trajectory crosses a condition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crossing {
+	pub time: f64,
+	pub direction: Direction,
+}
+
+/// Sums the squares of `polynomials`.
+fn squared(polynomials: &[Polynomial; 3]) -> Polynomial {
+	let [x, y, z] = polynomials.clone();
+
+	x.clone() * x + y.clone() * y + z.clone() * z
+}
+
+/// Finds every time at which `waypoints` crosses `condition`, in order.
+///
+/// Places where the trajectory only touches the condition without crossing
+/// it are not reported.  Where it runs along the condition for a while, the
+/// crossing is reported at the start of that stretch.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::events::{crossings, Condition, Direction};
+/// use motion_planning::extrema::Axis;
+/// use motion_planning::timed::timed;
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose3;
+///
+/// let rest = |z| Pose3 {
+///     position: Vec3d(0., 0., z),
+///     velocity: Vec3d(0., 0., 0.),
+///     acceleration: Vec3d(0., 0., 0.),
+/// };
+/// let descent = timed(&[rest(2.), rest(0.)]);
+///
+/// // Deploy the gear once below 1 m.
+/// let gear = Condition::Coordinate { axis: Axis::Z, value: 1. };
+/// let events = crossings(&descent, gear);
+///
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].direction, Direction::Falling);
+/// assert!((events[0].time - 0.5).abs() < 1e-9);
+/// ```
+pub fn crossings(waypoints: &[Timed<Pose3<Vec3d<f64>>>], condition: Condition) -> Vec<Crossing> {
+	let derivative = match condition {
+		Condition::Speed(_) => Derivative::Velocity,
+		_ => Derivative::Position,
+	};
+
+	// The stretches of time over which the condition's function keeps one
+	// sign, with the sign, leaving out where it is zero throughout.
+	let mut stretches: Vec<(f64, f64, bool)> = Vec::new();
+
+	for piece in pieces(waypoints, derivative) {
+		let [x, y, z] = &piece.axes;
+
+		let g = match condition {
+			Condition::Plane { point, normal } => {
+				x.clone() * normal.0
+					+ y.clone() * normal.1
+					+ z.clone() * normal.2
+					+ Polynomial(vec![-normal.dot(&point)])
+			}
+			Condition::Sphere { centre, radius } => {
+				squared(&[
+					x.clone() + Polynomial(vec![-centre.0]),
+					y.clone() + Polynomial(vec![-centre.1]),
+					z.clone() + Polynomial(vec![-centre.2]),
+				]) + Polynomial(vec![-radius * radius])
+			}
+			Condition::Coordinate { axis, value } => {
+				piece.axes[axis as usize].clone() + Polynomial(vec![-value])
+			}
+			Condition::Speed(speed) => squared(&piece.axes) + Polynomial(vec![-speed * speed]),
+		};
+
+		let mut cuts = vec![0.];
+		cuts.extend(g.roots(0., 1.));
+		cuts.push(1.);
+		cuts.dedup();
+
+		for pair in cuts.windows(2) {
+			let (a, b) = (pair[0], pair[1]);
+			let value = g.eval(a + (b - a) / 2.);
+
+			if value != 0. {
+				stretches.push((piece.time(a), piece.time(b), value > 0.));
+			}
+		}
+	}
+
+	stretches
+		.windows(2)
+		.filter(|pair| pair[0].2 != pair[1].2)
+		.map(|pair| Crossing {
+			time: pair[0].1,
+			direction: if pair[1].2 {
+				Direction::Rising
+			} else {
+				Direction::Falling
+			},
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::timed::timed;
+	use super::super::Trajectory3;
+	use super::*;
+
+	fn waypoints() -> Vec<Timed<Pose3<Vec3d<f64>>>> {
+		timed(&[
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(1., 0., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 1., 0.),
+				velocity: Vec3d(1., 1., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(4., 0.5, 0.),
+				velocity: Vec3d(0., -1., 0.),
+				acceleration: Vec3d(-1., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 1.2, 0.),
+				velocity: Vec3d(-1., 0., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+		])
+	}
+
+	/// Counts sign changes of `f` over dense samples, as a cross-check.
+	fn sampled(f: impl Fn(f64) -> f64) -> usize {
+		let values: Vec<f64> = (0..=30000).map(|i| f(f64::from(i) * 1e-4)).collect();
+
+		values
+			.windows(2)
+			.filter(|w| (w[0] > 0.) != (w[1] > 0.))
+			.count()
+	}
+
+	#[test]
+	fn plane_crossings() {
+		let path = waypoints();
+		let plane = Condition::Plane {
+			point: Vec3d(3., 0., 0.),
+			normal: Vec3d(2., 0., 0.),
+		};
+
+		let events = crossings(&path, plane);
+
+		assert_eq!(events.len(), 2);
+		assert_eq!(events[0].direction, Direction::Rising);
+		assert_eq!(events[1].direction, Direction::Falling);
+
+		for event in &events {
+			assert_f64_roughly_eq!(path.position_at(event.time).unwrap().0, 3., 1e-9);
+		}
+	}
+
+	#[test]
+	fn sphere_and_speed_match_sampling() {
+		let path = waypoints();
+		let centre = Vec3d(2.5, 0.8, 0.);
+
+		let sphere = crossings(
+			&path,
+			Condition::Sphere {
+				centre,
+				radius: 0.6,
+			},
+		);
+		let expected = sampled(|t| (path.position_at(t).unwrap() + centre * -1.).norm() - 0.6);
+		assert_eq!(sphere.len(), expected);
+
+		for event in &sphere {
+			let distance = (path.position_at(event.time).unwrap() + centre * -1.).norm();
+			assert_f64_roughly_eq!(distance, 0.6, 1e-9);
+		}
+
+		let speed = crossings(&path, Condition::Speed(1.5));
+		assert_eq!(
+			speed.len(),
+			sampled(|t| path.velocity_at(t).unwrap().norm() - 1.5)
+		);
+		assert!(speed
+			.windows(2)
+			.all(|w| w[0].time < w[1].time && w[0].direction != w[1].direction));
+	}
+
+	#[test]
+	fn brief_and_touching_crossings() {
+		let rest = |x| Pose3 {
+			position: Vec3d(x, 0., 0.),
+			velocity: Vec3d(0., 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		};
+		let there_and_back = timed(&[rest(0.), rest(1.), rest(0.)]);
+
+		// x reaches exactly 1 at the waypoint and turns back: a touch.
+		let touch = Condition::Coordinate {
+			axis: Axis::X,
+			value: 1.,
+		};
+		assert!(crossings(&there_and_back, touch).is_empty());
+
+		// Just short of the turn, there are two crossings close together.
+		let brief = crossings(
+			&there_and_back,
+			Condition::Coordinate {
+				axis: Axis::X,
+				value: 1. - 1e-6,
+			},
+		);
+
+		assert_eq!(brief.len(), 2);
+		assert_eq!(brief[0].direction, Direction::Rising);
+		assert_eq!(brief[1].direction, Direction::Falling);
+		assert!(brief[1].time - brief[0].time < 0.05);
+	}
+}
@@ -0,0 +1,301 @@
+//! The exact extremes of a trajectory's position and derivatives.
+//!
+//! Each segment of a trajectory is a polynomial, so each component of its
+//! position and derivatives is too, as is the square of their norms.  Their
+//! extremes therefore lie either at the ends of a segment or at a root of
+//! the derivative, which [`Polynomial::roots`] finds exactly; no sampling is
+//! involved.
+
+use super::polynomial::{quintic_segment, Polynomial};
+use super::timed::Timed;
+use super::vec::Vec3d;
+use super::Pose3;
+
+/// A component of a [`Vec3d`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+	X,
+	Y,
+	Z,
+}
+
+impl Axis {
+	pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+	/// Picks this component out of `v`.
+	pub fn of(self, v: Vec3d<f64>) -> f64 {
+		match self {
+			Axis::X => v.0,
+			Axis::Y => v.1,
+			Axis::Z => v.2,
+		}
+	}
+}
+
+/// A quantity along a trajectory, by how many times position is
+/// differentiated to reach it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Derivative {
+	Position,
+	Velocity,
+	Acceleration,
+	Jerk,
+}
+
+impl Derivative {
+	pub fn order(self) -> usize {
+		match self {
+			Derivative::Position => 0,
+			Derivative::Velocity => 1,
+			Derivative::Acceleration => 2,
+			Derivative::Jerk => 3,
+		}
+	}
+}
+
+/// A value taken by a trajectory, and the time at which it is taken.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Extremum {
+	pub time: f64,
+	pub value: f64,
+}
+
+/// A segment of a trajectory, with each component of one of its derivatives
+/// as a polynomial in the fraction of the way through the segment.
+pub(crate) struct Piece {
+	pub(crate) start: f64,
+	pub(crate) end: f64,
+	pub(crate) axes: [Polynomial; 3],
+}
+
+impl Piece {
+	/// The time at the fraction `u` of the way through the piece, which is
+	/// exactly the next waypoint's at the end.
+	pub(crate) fn time(&self, u: f64) -> f64 {
+		if u == 1. {
+			self.end
+		} else {
+			self.start + u * (self.end - self.start)
+		}
+	}
+}
+
+/// Splits `waypoints` into pieces carrying `derivative` of each segment.
+pub(crate) fn pieces(waypoints: &[Timed<Pose3<Vec3d<f64>>>], derivative: Derivative) -> Vec<Piece> {
+	let order = derivative.order();
+
+	waypoints
+		.windows(2)
+		.map(|pair| {
+			let (prec, succ) = (&pair[0], &pair[1]);
+			let duration = succ.time - prec.time;
+
+			let axis = |axis: Axis| {
+				let component = |pose: &Pose3<Vec3d<f64>>| Pose3 {
+					position: axis.of(pose.position),
+					velocity: axis.of(pose.velocity),
+					acceleration: axis.of(pose.acceleration),
+				};
+
+				let p = (0..order).fold(
+					quintic_segment(&component(&prec.pose), &component(&succ.pose), duration),
+					|p, _| p.derivative(),
+				);
+
+				p * duration.powi(-(order as i32))
+			};
+
+			Piece {
+				start: prec.time,
+				end: succ.time,
+				axes: [axis(Axis::X), axis(Axis::Y), axis(Axis::Z)],
+			}
+		})
+		.collect()
+}
+
+/// The ends of the segment, and the places within it where `p` is
+/// stationary.
+fn candidates(p: &Polynomial) -> impl Iterator<Item = f64> {
+	[0., 1.].iter().copied().chain(p.derivative().roots(0., 1.))
+}
+
+/// Finds the greatest norm that `derivative` reaches along `waypoints`, such
+/// as the top speed when `derivative` is [`Derivative::Velocity`].
+///
+/// Returns `None` if there are fewer than two waypoints.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::extrema::{norm_maximum, Derivative};
+/// use motion_planning::timed::timed;
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose3;
+///
+/// let rest = |x| Pose3 {
+///     position: Vec3d(x, 0., 0.),
+///     velocity: Vec3d(0., 0., 0.),
+///     acceleration: Vec3d(0., 0., 0.),
+/// };
+///
+/// let top = norm_maximum(&timed(&[rest(0.), rest(1.)]), Derivative::Velocity).unwrap();
+///
+/// assert!((top.time - 0.5).abs() < 1e-9);
+/// assert!((top.value - 1.875).abs() < 1e-9);
+/// ```
+pub fn norm_maximum(
+	waypoints: &[Timed<Pose3<Vec3d<f64>>>],
+	derivative: Derivative,
+) -> Option<Extremum> {
+	pieces(waypoints, derivative)
+		.iter()
+		.flat_map(|piece| {
+			let [x, y, z] = piece.axes.clone();
+			let squared = x.clone() * x + y.clone() * y + z.clone() * z;
+
+			candidates(&squared)
+				.map(|u| Extremum {
+					time: piece.time(u),
+					value: squared.eval(u).max(0.).sqrt(),
+				})
+				.collect::<Vec<_>>()
+		})
+		.reduce(|best, e| if e.value > best.value { e } else { best })
+}
+
+/// Finds the least and greatest values that one component of `derivative`
+/// takes along `waypoints`.
+///
+/// Returns `None` if there are fewer than two waypoints.
+pub fn component_extrema(
+	waypoints: &[Timed<Pose3<Vec3d<f64>>>],
+	derivative: Derivative,
+	axis: Axis,
+) -> Option<(Extremum, Extremum)> {
+	pieces(waypoints, derivative)
+		.iter()
+		.flat_map(|piece| {
+			let p = &piece.axes[axis as usize];
+
+			candidates(p)
+				.map(|u| Extremum {
+					time: piece.time(u),
+					value: p.eval(u),
+				})
+				.collect::<Vec<_>>()
+		})
+		.fold(None, |extremes, e| match extremes {
+			None => Some((e, e)),
+			Some((min, max)) => Some((
+				if e.value < min.value { e } else { min },
+				if e.value > max.value { e } else { max },
+			)),
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::timed::timed;
+	use super::super::Trajectory3;
+	use super::*;
+
+	fn waypoints() -> Vec<Timed<Pose3<Vec3d<f64>>>> {
+		let poses = [
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(0., 1., 0.),
+				acceleration: Vec3d(1., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(1., 1., 0.),
+				velocity: Vec3d(1., 0., 0.),
+				acceleration: Vec3d(0., -1., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 0., 1.),
+				velocity: Vec3d(0., -1., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+		];
+
+		timed(&poses)
+			.into_iter()
+			.zip(&[0., 0.8, 2.5])
+			.map(|(waypoint, &time)| Timed { time, ..waypoint })
+			.collect()
+	}
+
+	fn sample(
+		waypoints: &Vec<Timed<Pose3<Vec3d<f64>>>>,
+		derivative: Derivative,
+		t: f64,
+	) -> Vec3d<f64> {
+		match derivative {
+			Derivative::Position => waypoints.position_at(t),
+			Derivative::Velocity => waypoints.velocity_at(t),
+			Derivative::Acceleration => waypoints.acceleration_at(t),
+			Derivative::Jerk => unreachable!(),
+		}
+		.unwrap()
+	}
+
+	#[test]
+	fn rest_to_rest_acceleration() {
+		let rest = |x| Pose3 {
+			position: Vec3d(x, 0., 0.),
+			velocity: Vec3d(0., 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		};
+		let waypoints = timed(&[rest(0.), rest(1.)]);
+
+		let (min, max) = component_extrema(&waypoints, Derivative::Acceleration, Axis::X).unwrap();
+
+		assert_f64_roughly_eq!(max.value, 10. * 3_f64.sqrt() / 3., 1e-9);
+		assert_f64_roughly_eq!(max.time, (3. - 3_f64.sqrt()) / 6., 1e-9);
+		assert_f64_roughly_eq!(min.value, -max.value, 1e-9);
+		assert_f64_roughly_eq!(min.time, 1. - max.time, 1e-9);
+
+		let jerk = norm_maximum(&waypoints, Derivative::Jerk).unwrap();
+		assert_f64_roughly_eq!(jerk.value, 60., 1e-9);
+	}
+
+	#[test]
+	fn bounds_every_sample() {
+		let waypoints = waypoints();
+
+		for derivative in [
+			Derivative::Position,
+			Derivative::Velocity,
+			Derivative::Acceleration,
+		] {
+			let top = norm_maximum(&waypoints, derivative).unwrap();
+			assert_f64_roughly_eq!(
+				sample(&waypoints, derivative, top.time).norm(),
+				top.value,
+				1e-9
+			);
+
+			for axis in Axis::ALL {
+				let (min, max) = component_extrema(&waypoints, derivative, axis).unwrap();
+
+				for i in 0..=1000 {
+					let v = sample(&waypoints, derivative, f64::from(i) * 0.0025);
+
+					assert!(v.norm() <= top.value + 1e-9);
+					assert!(axis.of(v) >= min.value - 1e-9);
+					assert!(axis.of(v) <= max.value + 1e-9);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn too_few_waypoints() {
+		let waypoints = waypoints();
+
+		assert_eq!(norm_maximum(&waypoints[..1], Derivative::Velocity), None);
+		assert_eq!(component_extrema(&[], Derivative::Velocity, Axis::Y), None);
+	}
+}
@@ -111,6 +111,48 @@ pub fn h_3p(t: f64, n: usize) -> f64 {
 	}
 }
 
+/// Computes the value of the second time-derivative of the corresponding
+/// cubic Hermite basis function.
+///
+/// In other words, this function is the first time-derivative of the
+/// [`h_3p`](crate::hermite::cubic::h_3p) function.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::hermite::cubic::h_3pp;
+///
+/// // h_n^3'' is linear in t, so its values at the ends describe it fully.
+/// assert_eq!(h_3pp(0., 0), -6.);
+/// assert_eq!(h_3pp(0., 1), -4.);
+/// assert_eq!(h_3pp(0., 2), -2.);
+/// assert_eq!(h_3pp(0., 3), 6.);
+///
+/// assert_eq!(h_3pp(1., 0), 6.);
+/// assert_eq!(h_3pp(1., 1), 2.);
+/// assert_eq!(h_3pp(1., 2), 4.);
+/// assert_eq!(h_3pp(1., 3), -6.);
+/// ```
+///
+/// # Panics
+///
+/// If `n` is not one of `0`, `1`, `2`, or `3` this function panics.
+///
+/// ```should_panic
+/// use motion_planning::hermite::cubic::h_3pp;
+/// # let t = 0.5_f64;
+/// h_3pp(t, 7);
+/// ```
+pub fn h_3pp(t: f64, n: usize) -> f64 {
+	match n {
+		0 => t.mul_add(12., -6.),
+		1 => t.mul_add(6., -4.),
+		2 => t.mul_add(6., -2.),
+		3 => t.mul_add(-12., 6.),
+		_ => unimplemented!(),
+	}
+}
+
 #[cfg(test)]
 use super::assert_f64_roughly_eq;
 
@@ -157,4 +199,22 @@ mod tests {
 			assert_f64_roughly_eq!(h_3p(1.0, 3), 0.);
 		}
 	}
+
+	#[cfg(test)]
+	mod h_3pp {
+		use super::super::{assert_f64_roughly_eq, h_3p, h_3pp};
+
+		#[test]
+		fn matches_difference_of_h_3p() {
+			let dt = 1e-6;
+
+			for n in 0..4 {
+				for &t in &[0.1, 0.5, 0.9] {
+					let difference = (h_3p(t + dt, n) - h_3p(t - dt, n)) / (2. * dt);
+
+					assert_f64_roughly_eq!(h_3pp(t, n), difference, 1e-6);
+				}
+			}
+		}
+	}
 }
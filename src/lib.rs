@@ -1,32 +1,164 @@
 use core::ops::{Add, Mul};
 use std::vec::Vec;
 
+pub mod bezier;
+pub mod check;
+pub mod clothoid;
+pub mod drive;
+pub mod dubins;
+pub mod events;
+pub mod extrema;
 pub mod hermite;
 use hermite::{
 	cubic::{h_3, h_3p},
 	quintic::{h_5, h_5p, h_5pp},
+	septic::{h_7, h_7p, h_7pp, h_7ppp},
 };
 
+mod linalg;
+pub mod markers;
+pub mod optimize;
+pub mod otg;
+pub mod planar;
+pub mod polynomial;
+pub mod project;
+mod quadrature;
+pub mod quadrotor;
+pub mod reeds_shepp;
+pub mod rotation;
+pub mod se3;
+pub mod splice;
+pub mod spline;
+pub mod tangents;
+pub mod timed;
+pub mod transform;
+pub mod validate;
 pub mod vec;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pose3<V> {
 	pub position: V,
 	pub velocity: V,
 	pub acceleration: V,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pose2<V> {
 	pub position: V,
 	pub velocity: V,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pose4<V> {
+	pub position: V,
+	pub velocity: V,
+	pub acceleration: V,
+	pub jerk: V,
+}
+
+/// Evaluates the `order`th time-derivative of a cubic Hermite segment lasting
+/// `duration`, at the fraction `t` of the way through it.
+///
+/// `h` is the basis function for that derivative, such as [`h_3p`] for the
+/// first.  Velocities are scaled by `duration` so that the segment has them
+/// as its true time-derivatives whatever its length.
+pub(crate) fn eval2<V>(
+	t: f64,
+	prec: &Pose2<V>,
+	succ: &Pose2<V>,
+	duration: f64,
+	h: fn(f64, usize) -> f64,
+	order: i32,
+) -> V
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let p0 = prec.position;
+	let v0 = prec.velocity * duration;
+
+	let p1 = succ.position;
+	let v1 = succ.velocity * duration;
+
+	((p0 * h(t, 0)) + (v0 * h(t, 1)) + (v1 * h(t, 2)) + (p1 * h(t, 3))) * duration.powi(-order)
+}
+
+/// Evaluates the `order`th time-derivative of a quintic Hermite segment
+/// lasting `duration`, like [`eval2`].
+pub(crate) fn eval3<V>(
+	t: f64,
+	prec: &Pose3<V>,
+	succ: &Pose3<V>,
+	duration: f64,
+	h: fn(f64, usize) -> f64,
+	order: i32,
+) -> V
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let d2 = duration * duration;
+
+	let p0 = prec.position;
+	let v0 = prec.velocity * duration;
+	let a0 = prec.acceleration * d2;
+
+	let p1 = succ.position;
+	let v1 = succ.velocity * duration;
+	let a1 = succ.acceleration * d2;
+
+	((p0 * h(t, 0))
+		+ (v0 * h(t, 1))
+		+ (a0 * h(t, 2))
+		+ (a1 * h(t, 3))
+		+ (v1 * h(t, 4))
+		+ (p1 * h(t, 5)))
+		* duration.powi(-order)
+}
+
+/// Evaluates the `order`th time-derivative of a septic Hermite segment
+/// lasting `duration`, like [`eval2`].
+pub(crate) fn eval4<V>(
+	t: f64,
+	prec: &Pose4<V>,
+	succ: &Pose4<V>,
+	duration: f64,
+	h: fn(f64, usize) -> f64,
+	order: i32,
+) -> V
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let d2 = duration * duration;
+	let d3 = d2 * duration;
+
+	let p0 = prec.position;
+	let v0 = prec.velocity * duration;
+	let a0 = prec.acceleration * d2;
+	let j0 = prec.jerk * d3;
+
+	let p1 = succ.position;
+	let v1 = succ.velocity * duration;
+	let a1 = succ.acceleration * d2;
+	let j1 = succ.jerk * d3;
+
+	((p0 * h(t, 0))
+		+ (v0 * h(t, 1))
+		+ (a0 * h(t, 2))
+		+ (j0 * h(t, 3))
+		+ (j1 * h(t, 4))
+		+ (a1 * h(t, 5))
+		+ (v1 * h(t, 6))
+		+ (p1 * h(t, 7)))
+		* duration.powi(-order)
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Segment2<'a, V>(f64, &'a Pose2<V>, &'a Pose2<V>);
 
 pub trait Trajectory2<V> {
-	fn get_segment(&self, t: f64) -> Option<Segment2<V>>;
+	fn get_segment(&self, t: f64) -> Option<Segment2<'_, V>>;
 	fn position_at(&self, t: f64) -> Option<V>;
 	fn velocity_at(&self, t: f64) -> Option<V>;
 }
@@ -35,7 +167,7 @@ impl<V> Trajectory2<V> for Vec<Pose2<V>>
 where
 	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
 {
-	fn get_segment(&self, t: f64) -> Option<Segment2<V>> {
+	fn get_segment(&self, t: f64) -> Option<Segment2<'_, V>> {
 		let length = self.len();
 
 		// If our container (Vec) has length 0, we cannot find a segment!.
@@ -62,18 +194,7 @@ where
 		let anchors = self.get_segment(t);
 
 		if let Some(Segment2(t, prec, succ)) = anchors {
-			let p0 = &prec.position;
-			let v0 = &prec.velocity;
-
-			let p1 = &succ.position;
-			let v1 = &succ.velocity;
-
-			let h03 = h_3(t, 0);
-			let h13 = h_3(t, 1);
-			let h23 = h_3(t, 2);
-			let h33 = h_3(t, 3);
-
-			return Some((*p0 * h03) + (*v0 * h13) + (*v1 * h23) + (*p1 * h33));
+			return Some(eval2(t, prec, succ, 1., h_3, 0));
 		}
 
 		None
@@ -83,18 +204,7 @@ where
 		let anchors = self.get_segment(t);
 
 		if let Some(Segment2(t, prec, succ)) = anchors {
-			let p0 = &prec.position;
-			let v0 = &prec.velocity;
-
-			let p1 = &succ.position;
-			let v1 = &succ.velocity;
-
-			let h03p = h_3p(t, 0);
-			let h13p = h_3p(t, 1);
-			let h23p = h_3p(t, 2);
-			let h33p = h_3p(t, 3);
-
-			return Some((*p0 * h03p) + (*v0 * h13p) + (*v1 * h23p) + (*p1 * h33p));
+			return Some(eval2(t, prec, succ, 1., h_3p, 1));
 		}
 
 		None
@@ -105,7 +215,7 @@ where
 pub struct Segment3<'a, V>(f64, &'a Pose3<V>, &'a Pose3<V>);
 
 pub trait Trajectory3<V> {
-	fn get_segment(&self, t: f64) -> Option<Segment3<V>>;
+	fn get_segment(&self, t: f64) -> Option<Segment3<'_, V>>;
 	fn position_at(&self, t: f64) -> Option<V>;
 	fn velocity_at(&self, t: f64) -> Option<V>;
 	fn acceleration_at(&self, t: f64) -> Option<V>;
@@ -115,7 +225,7 @@ impl<V> Trajectory3<V> for Vec<Pose3<V>>
 where
 	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
 {
-	fn get_segment(&self, t: f64) -> Option<Segment3<V>> {
+	fn get_segment(&self, t: f64) -> Option<Segment3<'_, V>> {
 		let length = self.len();
 
 		// If our container (Vec) has length 0, we cannot find a segment!.
@@ -142,24 +252,7 @@ where
 		let anchors = self.get_segment(t);
 
 		if let Some(Segment3(t, prec, succ)) = anchors {
-			let p0 = &prec.position;
-			let v0 = &prec.velocity;
-			let a0 = &prec.acceleration;
-
-			let p1 = &succ.position;
-			let v1 = &succ.velocity;
-			let a1 = &succ.acceleration;
-
-			let h05 = h_5(t, 0);
-			let h15 = h_5(t, 1);
-			let h25 = h_5(t, 2);
-			let h35 = h_5(t, 3);
-			let h45 = h_5(t, 4);
-			let h55 = h_5(t, 5);
-
-			return Some(
-				(*p0 * h05) + (*v0 * h15) + (*a0 * h25) + (*a1 * h35) + (*v1 * h45) + (*p1 * h55),
-			);
+			return Some(eval3(t, prec, succ, 1., h_5, 0));
 		}
 
 		None
@@ -169,24 +262,7 @@ where
 		let anchors = self.get_segment(t);
 
 		if let Some(Segment3(t, prec, succ)) = anchors {
-			let p0 = &prec.position;
-			let v0 = &prec.velocity;
-			let a0 = &prec.acceleration;
-
-			let p1 = &succ.position;
-			let v1 = &succ.velocity;
-			let a1 = &succ.acceleration;
-
-			let h05p = h_5p(t, 0);
-			let h15p = h_5p(t, 1);
-			let h25p = h_5p(t, 2);
-			let h35p = h_5p(t, 3);
-			let h45p = h_5p(t, 4);
-			let h55p = h_5p(t, 5);
-
-			return Some(
-				(*p0 * h05p) + (*v0 * h15p) + (*a0 * h25p) + (*a1 * h35p) + (*v1 * h45p) + (*p1 * h55p),
-			);
+			return Some(eval3(t, prec, succ, 1., h_5p, 1));
 		}
 
 		None
@@ -196,29 +272,86 @@ where
 		let anchors = self.get_segment(t);
 
 		if let Some(Segment3(t, prec, succ)) = anchors {
-			let p0 = &prec.position;
-			let v0 = &prec.velocity;
-			let a0 = &prec.acceleration;
-
-			let p1 = &succ.position;
-			let v1 = &succ.velocity;
-			let a1 = &succ.acceleration;
-
-			let h05pp = h_5pp(t, 0);
-			let h15pp = h_5pp(t, 1);
-			let h25pp = h_5pp(t, 2);
-			let h35pp = h_5pp(t, 3);
-			let h45pp = h_5pp(t, 4);
-			let h55pp = h_5pp(t, 5);
-
-			return Some(
-				(*p0 * h05pp)
-					+ (*v0 * h15pp)
-					+ (*a0 * h25pp)
-					+ (*a1 * h35pp)
-					+ (*v1 * h45pp)
-					+ (*p1 * h55pp),
-			);
+			return Some(eval3(t, prec, succ, 1., h_5pp, 2));
+		}
+
+		None
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Segment4<'a, V>(f64, &'a Pose4<V>, &'a Pose4<V>);
+
+pub trait Trajectory4<V> {
+	fn get_segment(&self, t: f64) -> Option<Segment4<'_, V>>;
+	fn position_at(&self, t: f64) -> Option<V>;
+	fn velocity_at(&self, t: f64) -> Option<V>;
+	fn acceleration_at(&self, t: f64) -> Option<V>;
+	fn jerk_at(&self, t: f64) -> Option<V>;
+}
+
+impl<V> Trajectory4<V> for Vec<Pose4<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	fn get_segment(&self, t: f64) -> Option<Segment4<'_, V>> {
+		let length = self.len();
+
+		// If our container (Vec) has length 0, we cannot find a segment!.
+		if let 0 = length {
+			return None;
+		}
+
+		// `t` ranges from `0.` to `length * 1.`;
+
+		let prec_idx = t.floor() as usize;
+		let succ_idx = t.ceil() as usize;
+
+		let prec: &Pose4<V> = &self[prec_idx];
+		let succ: &Pose4<V> = &self[succ_idx];
+
+		let t = t.fract();
+
+		assert!((0.0_f64..=1.0_f64).contains(&t), "{} not in [0., 1.]", t);
+
+		Some(Segment4(t, prec, succ))
+	}
+
+	fn position_at(&self, t: f64) -> Option<V> {
+		let anchors = self.get_segment(t);
+
+		if let Some(Segment4(t, prec, succ)) = anchors {
+			return Some(eval4(t, prec, succ, 1., h_7, 0));
+		}
+
+		None
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<V> {
+		let anchors = self.get_segment(t);
+
+		if let Some(Segment4(t, prec, succ)) = anchors {
+			return Some(eval4(t, prec, succ, 1., h_7p, 1));
+		}
+
+		None
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<V> {
+		let anchors = self.get_segment(t);
+
+		if let Some(Segment4(t, prec, succ)) = anchors {
+			return Some(eval4(t, prec, succ, 1., h_7pp, 2));
+		}
+
+		None
+	}
+
+	fn jerk_at(&self, t: f64) -> Option<V> {
+		let anchors = self.get_segment(t);
+
+		if let Some(Segment4(t, prec, succ)) = anchors {
+			return Some(eval4(t, prec, succ, 1., h_7ppp, 3));
 		}
 
 		None
@@ -234,4 +367,13 @@ macro_rules! assert_f64_roughly_eq {
 	($left:expr, $right:expr) => {
 		assert!(($right - $left).abs() < f64::EPSILON)
 	};
+	($left:expr, $right:expr, $tolerance:expr) => {
+		assert!(
+			($right - $left).abs() < $tolerance,
+			"{} != {} (tolerance {})",
+			$left,
+			$right,
+			$tolerance
+		)
+	};
 }
@@ -0,0 +1,124 @@
+//! Small dense linear algebra, enough for the solvers in this crate.
+
+use core::ops::{Add, Mul};
+
+/// Pivots smaller than this, relative to the largest entry of the matrix, are
+/// taken to mean the system is singular.
+const SINGULAR: f64 = 1e-12;
+
+/// Solves the square system `matrix * x = rhs` by Gaussian elimination with
+/// partial pivoting.
+///
+/// The matrix is of scalars, while the right-hand side and solution may be of
+/// any vector type.  Returns `None` if the matrix is singular.
+pub(crate) fn solve<V>(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<V>) -> Option<Vec<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let n = rhs.len();
+
+	let scale = matrix
+		.iter()
+		.flatten()
+		.fold(0_f64, |max, entry| max.max(entry.abs()));
+
+	for column in 0..n {
+		let pivot = (column..n).max_by(|&a, &b| {
+			matrix[a][column]
+				.abs()
+				.partial_cmp(&matrix[b][column].abs())
+				.unwrap_or(core::cmp::Ordering::Equal)
+		})?;
+
+		if matrix[pivot][column].abs() <= SINGULAR * scale {
+			return None;
+		}
+
+		matrix.swap(column, pivot);
+		rhs.swap(column, pivot);
+
+		for row in (column + 1)..n {
+			let factor = matrix[row][column] / matrix[column][column];
+
+			if factor == 0. {
+				continue;
+			}
+
+			let (above, below) = matrix.split_at_mut(row);
+			for (entry, pivot_entry) in below[0][column..].iter_mut().zip(&above[column][column..]) {
+				*entry = factor.mul_add(-pivot_entry, *entry);
+			}
+
+			rhs[row] = rhs[row] + rhs[column] * -factor;
+		}
+	}
+
+	let mut solution: Vec<V> = Vec::with_capacity(n);
+
+	for row in (0..n).rev() {
+		let mut sum = rhs[row];
+
+		for (offset, x) in solution.iter().rev().enumerate() {
+			sum = sum + *x * -matrix[row][row + 1 + offset];
+		}
+
+		solution.push(sum * (1. / matrix[row][row]));
+	}
+
+	solution.reverse();
+	Some(solution)
+}
+
+/// Inverts a square matrix, returning `None` if it is singular.
+pub(crate) fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+	let n = matrix.len();
+
+	let columns: Vec<Vec<f64>> = (0..n)
+		.map(|j| {
+			let unit: Vec<f64> = (0..n).map(|i| if i == j { 1. } else { 0. }).collect();
+			solve(matrix.to_vec(), unit)
+		})
+		.collect::<Option<_>>()?;
+
+	Some(
+		(0..n)
+			.map(|i| (0..n).map(|j| columns[j][i]).collect())
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::*;
+
+	#[test]
+	fn solves_with_pivoting() {
+		// The leading zero forces a row swap.
+		let x = solve(
+			vec![vec![0., 2., 1.], vec![1., 1., 1.], vec![2., 0., 3.]],
+			vec![7., 6., 11.],
+		)
+		.unwrap();
+
+		assert_f64_roughly_eq!(x[0], 1., 1e-12);
+		assert_f64_roughly_eq!(x[1], 2., 1e-12);
+		assert_f64_roughly_eq!(x[2], 3., 1e-12);
+	}
+
+	#[test]
+	fn detects_singular_matrices() {
+		assert_eq!(solve(vec![vec![1., 2.], vec![2., 4.]], vec![1., 2.]), None);
+		assert_eq!(invert(&[vec![0.]]), None);
+	}
+
+	#[test]
+	fn inverts() {
+		let inverse = invert(&[vec![4., 7.], vec![2., 6.]]).unwrap();
+
+		assert_f64_roughly_eq!(inverse[0][0], 0.6, 1e-12);
+		assert_f64_roughly_eq!(inverse[0][1], -0.7, 1e-12);
+		assert_f64_roughly_eq!(inverse[1][0], -0.2, 1e-12);
+		assert_f64_roughly_eq!(inverse[1][1], 0.4, 1e-12);
+	}
+}
@@ -0,0 +1,375 @@
+//! Named events placed along a trajectory.
+//!
+//! A [`Marker`] names something to do at a point along a trajectory, such as
+//! opening a gripper, anchored to a time, a waypoint or a distance travelled.
+//! A [`Playback`] resolves every marker to a time and hands each one out once
+//! as the playhead passes it, however coarsely the trajectory is sampled.
+//!
+//! With the `serde` feature, markers and [`Annotated`] trajectories can be
+//! serialized.
+
+use super::quadrature::points;
+use super::timed::Timed;
+use super::vec::Vec3d;
+use super::{Pose3, Trajectory3};
+
+/// How many equal parts each segment is integrated in.
+const SUBINTERVALS: usize = 8;
+
+/// How many times the search for a distance's time halves its bracket.
+const BISECTION_ITERATIONS: usize = 64;
+
+/// Where along a trajectory a [`Marker`] is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Anchor {
+	/// At a time.
+	Time(f64),
+	/// At the waypoint with this index.
+	Waypoint(usize),
+	/// After travelling this far along the path from its start.
+	Distance(f64),
+}
+
+/// A named event at a point along a trajectory.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Marker {
+	pub name: String,
+	pub anchor: Anchor,
+}
+
+/// A trajectory together with its markers, to be stored or sent as one.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annotated<T> {
+	pub trajectory: T,
+	pub markers: Vec<Marker>,
+}
+
+/// Trajectories whose waypoints have known times.
+pub trait Timeline {
+	/// The time of each waypoint, in order.
+	fn waypoint_times(&self) -> Vec<f64>;
+}
+
+impl<V> Timeline for Vec<Pose3<V>> {
+	fn waypoint_times(&self) -> Vec<f64> {
+		(0..self.len()).map(|i| i as f64).collect()
+	}
+}
+
+impl<V> Timeline for Vec<Timed<Pose3<V>>> {
+	fn waypoint_times(&self) -> Vec<f64> {
+		self.iter().map(|waypoint| waypoint.time).collect()
+	}
+}
+
+/// Integrates the speed of `trajectory` from `from` to `to`, which must lie
+/// within one segment.
+fn distance_between<T>(trajectory: &T, from: f64, to: f64) -> f64
+where
+	T: Trajectory3<Vec3d<f64>>,
+{
+	points(from, to, SUBINTERVALS)
+		.map(|(t, weight)| weight * trajectory.velocity_at(t).map_or(0., |v| v.norm()))
+		.sum()
+}
+
+/// Computes the distance travelled along `trajectory` from its start until
+/// time `t`.
+///
+/// Returns `None` if `t` is outside the trajectory.
+pub fn arc_length<T>(trajectory: &T, t: f64) -> Option<f64>
+where
+	T: Trajectory3<Vec3d<f64>> + Timeline,
+{
+	let times = trajectory.waypoint_times();
+
+	if !(*times.first()?..=*times.last()?).contains(&t) {
+		return None;
+	}
+
+	let mut travelled = 0.;
+
+	for pair in times.windows(2) {
+		if t <= pair[1] {
+			return Some(travelled + distance_between(trajectory, pair[0], t));
+		}
+
+		travelled += distance_between(trajectory, pair[0], pair[1]);
+	}
+
+	Some(travelled)
+}
+
+/// Finds the time at which `trajectory` has travelled `distance` from its
+/// start.
+///
+/// Returns `None` if the trajectory is shorter than `distance`, or
+/// `distance` is negative.
+pub fn time_at_distance<T>(trajectory: &T, distance: f64) -> Option<f64>
+where
+	T: Trajectory3<Vec3d<f64>> + Timeline,
+{
+	let times = trajectory.waypoint_times();
+
+	if distance < 0. {
+		return None;
+	}
+
+	if distance == 0. {
+		return times.first().copied();
+	}
+
+	let mut travelled = 0.;
+
+	for pair in times.windows(2) {
+		let length = distance_between(trajectory, pair[0], pair[1]);
+
+		if travelled + length >= distance {
+			let (mut lo, mut hi) = (pair[0], pair[1]);
+
+			for _ in 0..BISECTION_ITERATIONS {
+				let mid = lo + (hi - lo) / 2.;
+
+				if travelled + distance_between(trajectory, pair[0], mid) < distance {
+					lo = mid;
+				} else {
+					hi = mid;
+				}
+			}
+
+			return Some(hi);
+		}
+
+		travelled += length;
+	}
+
+	None
+}
+
+/// Finds the time at which `anchor` lies along `trajectory`.
+///
+/// Returns `None` if the anchor lies outside the trajectory.
+pub fn resolve<T>(trajectory: &T, anchor: Anchor) -> Option<f64>
+where
+	T: Trajectory3<Vec3d<f64>> + Timeline,
+{
+	let times = trajectory.waypoint_times();
+
+	match anchor {
+		Anchor::Time(t) => {
+			if (*times.first()?..=*times.last()?).contains(&t) {
+				Some(t)
+			} else {
+				None
+			}
+		}
+		Anchor::Waypoint(index) => times.get(index).copied(),
+		Anchor::Distance(distance) => time_at_distance(trajectory, distance),
+	}
+}
+
+/// Hands out the markers of a trajectory as a playhead moves along it.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::markers::{Anchor, Marker, Playback};
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose3;
+///
+/// let rest = |x| Pose3 {
+///     position: Vec3d(x, 0., 0.),
+///     velocity: Vec3d(0., 0., 0.),
+///     acceleration: Vec3d(0., 0., 0.),
+/// };
+/// let trajectory = vec![rest(0.), rest(1.), rest(2.)];
+/// let markers = vec![
+///     Marker { name: "open gripper".into(), anchor: Anchor::Time(0.25) },
+///     Marker { name: "close gripper".into(), anchor: Anchor::Waypoint(1) },
+/// ];
+///
+/// let mut playback = Playback::new(&trajectory, &markers).unwrap();
+///
+/// assert!(playback.advance(0.2).is_empty());
+/// assert_eq!(playback.advance(1.5)[0].1.name, "open gripper");
+/// assert!(playback.advance(1.5).is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Playback {
+	/// Every marker with its time, in order of time.
+	events: Vec<(f64, Marker)>,
+	/// The number of events handed out so far.
+	passed: usize,
+}
+
+impl Playback {
+	/// Resolves each of `markers` along `trajectory`.
+	///
+	/// Returns `None` if any marker lies outside the trajectory.
+	pub fn new<T>(trajectory: &T, markers: &[Marker]) -> Option<Self>
+	where
+		T: Trajectory3<Vec3d<f64>> + Timeline,
+	{
+		let mut events = markers
+			.iter()
+			.map(|marker| Some((resolve(trajectory, marker.anchor)?, marker.clone())))
+			.collect::<Option<Vec<_>>>()?;
+
+		// A stable sort keeps markers at the same time in the order given.
+		events.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		Some(Playback { events, passed: 0 })
+	}
+
+	/// Moves the playhead to `t`, giving back the markers it passes on the
+	/// way, with their times.
+	///
+	/// Markers are given back only once; moving the playhead backwards gives
+	/// back nothing until it passes new markers.
+	pub fn advance(&mut self, t: f64) -> &[(f64, Marker)] {
+		let start = self.passed;
+
+		while self.passed < self.events.len() && self.events[self.passed].0 <= t {
+			self.passed += 1;
+		}
+
+		&self.events[start..self.passed]
+	}
+
+	/// Moves the playhead back to the start, so that every marker will be
+	/// given back again.
+	pub fn reset(&mut self) {
+		self.passed = 0;
+	}
+
+	/// Every marker with its time, in order of time.
+	pub fn events(&self) -> &[(f64, Marker)] {
+		&self.events
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::timed::timed;
+	use super::*;
+
+	fn cruise(x: f64) -> Pose3<Vec3d<f64>> {
+		Pose3 {
+			position: Vec3d(x, 0., 0.),
+			velocity: Vec3d(2., 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		}
+	}
+
+	fn marker(name: &str, anchor: Anchor) -> Marker {
+		Marker {
+			name: name.into(),
+			anchor,
+		}
+	}
+
+	#[test]
+	fn arc_length_of_straight_line() {
+		let line = vec![cruise(0.), cruise(2.), cruise(4.)];
+
+		assert_f64_roughly_eq!(arc_length(&line, 2.).unwrap(), 4., 1e-9);
+		assert_f64_roughly_eq!(arc_length(&line, 0.75).unwrap(), 1.5, 1e-9);
+		assert_f64_roughly_eq!(time_at_distance(&line, 3.).unwrap(), 1.5, 1e-9);
+		assert_eq!(time_at_distance(&line, 4.5), None);
+		assert_eq!(arc_length(&line, 2.5), None);
+	}
+
+	#[test]
+	fn arc_length_of_curve() {
+		// A quarter circle of radius 1, approximately, over two seconds.
+		let s = core::f64::consts::FRAC_1_SQRT_2;
+		let w = core::f64::consts::FRAC_PI_4;
+		let arc = vec![
+			Pose3 {
+				position: Vec3d(1., 0., 0.),
+				velocity: Vec3d(0., w, 0.),
+				acceleration: Vec3d(-w * w, 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(s, s, 0.),
+				velocity: Vec3d(-w * s, w * s, 0.),
+				acceleration: Vec3d(-w * w * s, -w * w * s, 0.),
+			},
+			Pose3 {
+				position: Vec3d(0., 1., 0.),
+				velocity: Vec3d(-w, 0., 0.),
+				acceleration: Vec3d(0., -w * w, 0.),
+			},
+		];
+
+		assert_f64_roughly_eq!(arc_length(&arc, 2.).unwrap(), 2. * w, 1e-4);
+	}
+
+	#[test]
+	fn playback_emits_each_marker_once() {
+		let line = timed(&[cruise(0.), cruise(2.), cruise(4.)]);
+		let markers = vec![
+			marker("end", Anchor::Waypoint(2)),
+			marker("quarter", Anchor::Distance(1.)),
+			marker("start", Anchor::Time(0.)),
+			marker("halfway", Anchor::Waypoint(1)),
+		];
+
+		let mut playback = Playback::new(&line, &markers).unwrap();
+		let names = |events: &[(f64, Marker)]| {
+			events
+				.iter()
+				.map(|(_, m)| m.name.clone())
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(names(playback.advance(0.)), vec!["start"]);
+		assert_eq!(names(playback.advance(1.2)), vec!["quarter", "halfway"]);
+		assert!(playback.advance(0.5).is_empty());
+		assert!(playback.advance(1.9).is_empty());
+		assert_eq!(names(playback.advance(10.)), vec!["end"]);
+		assert!(playback.advance(10.).is_empty());
+
+		playback.reset();
+		assert_eq!(playback.advance(10.).len(), 4);
+	}
+
+	#[test]
+	fn unresolvable_markers() {
+		let line = vec![cruise(0.), cruise(2.)];
+
+		assert_eq!(
+			Playback::new(&line, &[marker("late", Anchor::Time(1.5))]),
+			None
+		);
+		assert_eq!(
+			Playback::new(&line, &[marker("missing", Anchor::Waypoint(2))]),
+			None
+		);
+		assert_eq!(
+			Playback::new(&line, &[marker("far", Anchor::Distance(2.5))]),
+			None
+		);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn annotated_round_trip() {
+		let annotated = Annotated {
+			trajectory: timed(&[cruise(0.), cruise(2.)]),
+			markers: vec![
+				marker("open gripper", Anchor::Time(0.5)),
+				marker("close gripper", Anchor::Distance(1.5)),
+			],
+		};
+
+		let json = serde_json::to_string(&annotated).unwrap();
+		let back: Annotated<Vec<Timed<Pose3<Vec3d<f64>>>>> = serde_json::from_str(&json).unwrap();
+
+		assert_eq!(back, annotated);
+	}
+}
@@ -0,0 +1,714 @@
+//! Minimum-jerk and minimum-snap polynomial trajectories.
+//!
+//! These are the trajectories that pass through a list of waypoints at given
+//! times while keeping the integral of the squared jerk (or snap) as small as
+//! possible, as is standard for quadrotors.  A minimum-jerk trajectory is
+//! piecewise quintic and a minimum-snap one piecewise septic, so each segment
+//! is exactly a quintic or septic Hermite segment and the result is returned
+//! as [`Timed`] waypoints.
+//!
+//! Each segment is parameterized by the derivatives at its two ends, which
+//! makes the trajectory continuous up to acceleration (or jerk) by
+//! construction.  The derivatives not fixed by the caller are then found by
+//! solving the linear system that minimizes the cost.
+
+use core::ops::{Add, Mul};
+
+use super::linalg;
+use super::timed::Timed;
+use super::vec::Vec3d;
+use super::{Pose3, Pose4};
+
+/// The relative step used to estimate the gradient of the cost with respect to
+/// segment durations.
+const GRADIENT_STEP: f64 = 1e-6;
+
+/// The number of times a step is halved before duration optimization gives
+/// up on improving the cost.
+const LINE_SEARCH_STEPS: usize = 30;
+
+/// A waypoint to pass through, with any derivatives it must have there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Waypoint<V> {
+	pub position: V,
+	pub velocity: Option<V>,
+	pub acceleration: Option<V>,
+	pub jerk: Option<V>,
+}
+
+impl<V> Waypoint<V> {
+	/// A waypoint at `position`, with all of its derivatives free.
+	pub fn new(position: V) -> Self {
+		Self {
+			position,
+			velocity: None,
+			acceleration: None,
+			jerk: None,
+		}
+	}
+}
+
+/// The derivative whose integrated square is minimized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Objective {
+	Jerk,
+	Snap,
+}
+
+impl Objective {
+	/// The order of the minimized derivative, which is also the number of
+	/// derivatives (counting position) held at each end of a segment.
+	fn order(self) -> usize {
+		match self {
+			Self::Jerk => 3,
+			Self::Snap => 4,
+		}
+	}
+}
+
+fn factorial_ratio(n: usize, k: usize) -> f64 {
+	// n! / (n - k)!
+	((n - k + 1)..=n).map(|i| i as f64).product()
+}
+
+/// Computes the cost matrix of a single unit-duration Hermite segment.
+///
+/// Entry `(k, l)` is the integral over the segment of the product of the
+/// `r`th derivatives of Hermite basis functions `k` and `l`, ordered as in
+/// [`hermite`](crate::hermite): the derivatives at the start in increasing
+/// order, then those at the end in decreasing order.
+fn segment_cost(r: usize) -> Option<Vec<Vec<f64>>> {
+	let size = 2 * r;
+
+	// The derivative of each basis function at each end, per monomial.
+	let conditions: Vec<Vec<f64>> = (0..size)
+		.map(|k| {
+			let (d, at_end) = if k < r {
+				(k, false)
+			} else {
+				(size - 1 - k, true)
+			};
+
+			(0..size)
+				.map(|j| match (j >= d, at_end) {
+					(false, _) => 0.,
+					(true, true) => factorial_ratio(j, d),
+					(true, false) if j == d => factorial_ratio(j, d),
+					(true, false) => 0.,
+				})
+				.collect()
+		})
+		.collect();
+
+	// Column `k` holds the monomial coefficients of basis function `k`.
+	let basis = linalg::invert(&conditions)?;
+
+	let monomial_cost = |a: usize, b: usize| -> f64 {
+		if a < r || b < r {
+			0.
+		} else {
+			factorial_ratio(a, r) * factorial_ratio(b, r) / ((a + b - 2 * r + 1) as f64)
+		}
+	};
+
+	Some(
+		(0..size)
+			.map(|k| {
+				(0..size)
+					.map(|l| {
+						let mut sum = 0.;
+						for a in 0..size {
+							for b in 0..size {
+								sum += basis[a][k] * monomial_cost(a, b) * basis[b][l];
+							}
+						}
+						sum
+					})
+					.collect()
+			})
+			.collect(),
+	)
+}
+
+/// The derivatives found at every waypoint, with the matrix of the quadratic
+/// cost they minimize.
+struct Solution<V> {
+	derivatives: Vec<Vec<V>>,
+	cost: Vec<Vec<f64>>,
+}
+
+/// Finds the derivatives minimizing the cost.
+///
+/// Derivative `d` of waypoint `i` is variable `i * r + d` of the cost matrix.
+fn solve<V>(
+	waypoints: &[Waypoint<V>],
+	durations: &[f64],
+	objective: Objective,
+) -> Option<Solution<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let r = objective.order();
+	let n = waypoints.len();
+
+	if n < 2 || durations.len() != n - 1 {
+		return None;
+	}
+
+	if durations.iter().any(|d| !(d.is_finite() && *d > 0.)) {
+		return None;
+	}
+
+	if r < 4 && waypoints.iter().any(|w| w.jerk.is_some()) {
+		return None;
+	}
+
+	let unit = segment_cost(r)?;
+	let size = n * r;
+	let mut cost = vec![vec![0.; size]; size];
+
+	for (i, &duration) in durations.iter().enumerate() {
+		let variable = |k: usize| -> (usize, i32) {
+			if k < r {
+				(i * r + k, k as i32)
+			} else {
+				let d = 2 * r - 1 - k;
+				((i + 1) * r + d, d as i32)
+			}
+		};
+
+		for (k, unit_row) in unit.iter().enumerate() {
+			let (row, dk) = variable(k);
+
+			for (l, entry) in unit_row.iter().enumerate() {
+				let (column, dl) = variable(l);
+
+				cost[row][column] += entry * duration.powi(1 - 2 * r as i32 + dk + dl);
+			}
+		}
+	}
+
+	let fixed: Vec<Option<V>> = waypoints
+		.iter()
+		.flat_map(|w| {
+			let derivatives = [Some(w.position), w.velocity, w.acceleration, w.jerk];
+			derivatives[..r].to_vec()
+		})
+		.collect();
+
+	let free: Vec<usize> = (0..size).filter(|&i| fixed[i].is_none()).collect();
+	let zero = waypoints[0].position * 0.;
+
+	// The entries go as powers of the durations, so uneven durations spread
+	// them over many orders of magnitude.  Solving for each free derivative
+	// divided by the square root of its diagonal entry brings the diagonal to
+	// one, so that the singularity test sees the shape of the system rather
+	// than its units.
+	let scale: Vec<f64> = free.iter().map(|&i| cost[i][i].sqrt().recip()).collect();
+
+	if scale.iter().any(|s| !s.is_finite()) {
+		return None;
+	}
+
+	let matrix: Vec<Vec<f64>> = free
+		.iter()
+		.zip(&scale)
+		.map(|(&row, &row_scale)| {
+			free
+				.iter()
+				.zip(&scale)
+				.map(|(&column, &column_scale)| cost[row][column] * row_scale * column_scale)
+				.collect()
+		})
+		.collect();
+
+	let rhs: Vec<V> = free
+		.iter()
+		.zip(&scale)
+		.map(|(&row, &row_scale)| {
+			fixed
+				.iter()
+				.enumerate()
+				.filter_map(|(column, value)| value.map(|value| value * -cost[row][column]))
+				.fold(zero, |sum, term| sum + term)
+				* row_scale
+		})
+		.collect();
+
+	let solved = linalg::solve(matrix, rhs)?;
+
+	let mut values: Vec<V> = fixed.iter().map(|value| value.unwrap_or(zero)).collect();
+	for ((&index, value), &s) in free.iter().zip(solved).zip(&scale) {
+		values[index] = value * s;
+	}
+
+	Some(Solution {
+		derivatives: values.chunks(r).map(<[V]>::to_vec).collect(),
+		cost,
+	})
+}
+
+/// Accumulates the times at which each waypoint is reached.
+fn times(durations: &[f64]) -> impl Iterator<Item = f64> + '_ {
+	core::iter::once(0.).chain(durations.iter().scan(0., |time, duration| {
+		*time += duration;
+		Some(*time)
+	}))
+}
+
+/// Computes the minimum-jerk trajectory through `waypoints`, spending
+/// `durations[i]` going from waypoint `i` to waypoint `i + 1`.
+///
+/// The trajectory starts at time zero.  Derivatives given in the waypoints are
+/// matched exactly; the rest are free, and where a waypoint leaves them all
+/// free the trajectory is also continuous in jerk and snap there.
+///
+/// Returns `None` if there are fewer than two waypoints, if there is not
+/// exactly one positive duration per segment, if a waypoint constrains jerk
+/// (which a quintic segment cannot hold), or if the waypoints constrain too
+/// little for the minimum to be unique.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::optimize::{minimum_jerk, Waypoint};
+/// use motion_planning::Trajectory3;
+///
+/// let rest = |position| Waypoint {
+///     velocity: Some(0.),
+///     acceleration: Some(0.),
+///     ..Waypoint::new(position)
+/// };
+///
+/// let trajectory = minimum_jerk(&[rest(0.), Waypoint::new(1.), rest(3.)], &[1., 2.]).unwrap();
+///
+/// assert_eq!(trajectory[2].time, 3.);
+/// assert_eq!(trajectory.position_at(1.), Some(1.));
+/// ```
+pub fn minimum_jerk<V>(waypoints: &[Waypoint<V>], durations: &[f64]) -> Option<Vec<Timed<Pose3<V>>>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let solution = solve(waypoints, durations, Objective::Jerk)?;
+
+	Some(
+		solution
+			.derivatives
+			.iter()
+			.zip(times(durations))
+			.map(|(d, time)| Timed {
+				time,
+				pose: Pose3 {
+					position: d[0],
+					velocity: d[1],
+					acceleration: d[2],
+				},
+			})
+			.collect(),
+	)
+}
+
+/// Computes the minimum-snap trajectory through `waypoints`, spending
+/// `durations[i]` going from waypoint `i` to waypoint `i + 1`.
+///
+/// This behaves like [`minimum_jerk`], except that jerk may be constrained
+/// too, and a waypoint leaving all of its derivatives free is continuous up to
+/// the sixth derivative.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::optimize::{minimum_snap, Waypoint};
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Trajectory4;
+///
+/// let hover = |position| Waypoint {
+///     velocity: Some(Vec3d(0., 0., 0.)),
+///     acceleration: Some(Vec3d(0., 0., 0.)),
+///     jerk: Some(Vec3d(0., 0., 0.)),
+///     ..Waypoint::new(position)
+/// };
+///
+/// let waypoints = [
+///     hover(Vec3d(0., 0., 1.)),
+///     Waypoint::new(Vec3d(1., 1., 1.5)),
+///     hover(Vec3d(2., 0., 1.)),
+/// ];
+/// let trajectory = minimum_snap(&waypoints, &[2., 2.]).unwrap();
+///
+/// assert_eq!(trajectory.position_at(2.), Some(Vec3d(1., 1., 1.5)));
+/// ```
+pub fn minimum_snap<V>(waypoints: &[Waypoint<V>], durations: &[f64]) -> Option<Vec<Timed<Pose4<V>>>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let solution = solve(waypoints, durations, Objective::Snap)?;
+
+	Some(
+		solution
+			.derivatives
+			.iter()
+			.zip(times(durations))
+			.map(|(d, time)| Timed {
+				time,
+				pose: Pose4 {
+					position: d[0],
+					velocity: d[1],
+					acceleration: d[2],
+					jerk: d[3],
+				},
+			})
+			.collect(),
+	)
+}
+
+/// Computes the integrated squared jerk or snap of the optimal trajectory.
+fn cost(
+	waypoints: &[Waypoint<Vec3d<f64>>],
+	durations: &[f64],
+	objective: Objective,
+) -> Option<f64> {
+	let Solution { derivatives, cost } = solve(waypoints, durations, objective)?;
+	let values: Vec<Vec3d<f64>> = derivatives.concat();
+
+	let mut total = 0.;
+
+	for (a, row) in cost.iter().enumerate() {
+		for (b, entry) in row.iter().enumerate() {
+			total += entry * values[a].dot(&values[b]);
+		}
+	}
+
+	Some(total)
+}
+
+/// Redistributes the total of `durations` among the segments so as to lower
+/// the cost of the optimal trajectory.
+///
+/// This runs up to `iterations` steps of projected gradient descent, keeping
+/// the total duration fixed and every duration positive, and returns the
+/// improved durations.  The gradient is estimated by finite differences, so
+/// each step costs one solve per segment.  Returns `None` under the same
+/// conditions as [`minimum_snap`].
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::optimize::{optimize_durations, Objective, Waypoint};
+/// use motion_planning::vec::Vec3d;
+///
+/// let rest = |position| Waypoint {
+///     velocity: Some(Vec3d(0., 0., 0.)),
+///     acceleration: Some(Vec3d(0., 0., 0.)),
+///     ..Waypoint::new(position)
+/// };
+/// let waypoints = [
+///     rest(Vec3d(0., 0., 0.)),
+///     Waypoint::new(Vec3d(1., 0., 0.)),
+///     rest(Vec3d(2., 0., 0.)),
+/// ];
+///
+/// // Symmetric waypoints are best served by symmetric durations.
+/// let durations = optimize_durations(&waypoints, &[0.5, 3.5], Objective::Jerk, 100).unwrap();
+///
+/// assert!((durations[0] - 2.).abs() < 1e-2);
+/// ```
+pub fn optimize_durations(
+	waypoints: &[Waypoint<Vec3d<f64>>],
+	durations: &[f64],
+	objective: Objective,
+	iterations: usize,
+) -> Option<Vec<f64>> {
+	let mut durations = durations.to_vec();
+	let mut current = cost(waypoints, &durations, objective)?;
+
+	for _ in 0..iterations {
+		let gradient: Vec<f64> = (0..durations.len())
+			.map(|i| {
+				let step = durations[i] * GRADIENT_STEP;
+				let mut nudged = durations.clone();
+				nudged[i] += step;
+
+				cost(waypoints, &nudged, objective).map(|c| (c - current) / step)
+			})
+			.collect::<Option<_>>()?;
+
+		// Project onto the directions that keep the total duration fixed.
+		let mean = gradient.iter().sum::<f64>() / gradient.len() as f64;
+		let direction: Vec<f64> = gradient.iter().map(|g| g - mean).collect();
+
+		// Start with a step that changes no duration by more than a tenth.
+		let mut step = durations
+			.iter()
+			.zip(&direction)
+			.filter(|(_, d)| d.abs() > 0.)
+			.map(|(t, d)| 0.1 * t / d.abs())
+			.fold(f64::INFINITY, f64::min);
+
+		if !step.is_finite() {
+			break;
+		}
+
+		let mut improved = false;
+
+		for _ in 0..LINE_SEARCH_STEPS {
+			let candidate: Vec<f64> = durations
+				.iter()
+				.zip(&direction)
+				.map(|(t, d)| t - step * d)
+				.collect();
+
+			if let Some(c) = cost(waypoints, &candidate, objective) {
+				if c < current {
+					durations = candidate;
+					current = c;
+					improved = true;
+					break;
+				}
+			}
+
+			step /= 2.;
+		}
+
+		if !improved {
+			break;
+		}
+	}
+
+	Some(durations)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::spline::{quintic, Boundary};
+	use super::super::{assert_f64_roughly_eq, Trajectory3, Trajectory4};
+	use super::*;
+
+	fn rest(position: f64) -> Waypoint<f64> {
+		Waypoint {
+			velocity: Some(0.),
+			acceleration: Some(0.),
+			..Waypoint::new(position)
+		}
+	}
+
+	#[test]
+	fn segment_cost_is_symmetric_and_matches_known_entries() {
+		let jerk = segment_cost(3).unwrap();
+
+		// The jerk of the position basis function is 60 - 360 t + 360 t^2,
+		// whose square integrates to 720.
+		assert_f64_roughly_eq!(jerk[0][0], 720., 1e-6);
+
+		for r in &[3, 4] {
+			let cost = segment_cost(*r).unwrap();
+			for (k, row) in cost.iter().enumerate() {
+				for (l, entry) in row.iter().enumerate() {
+					assert_f64_roughly_eq!(*entry, cost[l][k], 1e-6);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn minimum_jerk_matches_quintic_spline() {
+		let positions = [0., 1., -1., 2., 2.5];
+		let waypoints: Vec<Waypoint<f64>> = positions
+			.iter()
+			.enumerate()
+			.map(|(i, &p)| {
+				if i == 0 || i == 4 {
+					rest(p)
+				} else {
+					Waypoint::new(p)
+				}
+			})
+			.collect();
+
+		let trajectory = minimum_jerk(&waypoints, &[1.; 4]).unwrap();
+
+		let clamped = Boundary {
+			velocity: Some(0.),
+			acceleration: Some(0.),
+		};
+		let spline = quintic(&positions, clamped, clamped);
+
+		for (timed, pose) in trajectory.iter().zip(&spline) {
+			assert_f64_roughly_eq!(timed.pose.velocity, pose.velocity, 1e-9);
+			assert_f64_roughly_eq!(timed.pose.acceleration, pose.acceleration, 1e-9);
+		}
+	}
+
+	#[test]
+	fn minimum_jerk_respects_interior_constraints() {
+		let waypoints = [
+			rest(0.),
+			Waypoint {
+				velocity: Some(2.),
+				..Waypoint::new(1.)
+			},
+			rest(3.),
+		];
+
+		let trajectory = minimum_jerk(&waypoints, &[0.5, 2.]).unwrap();
+
+		assert_eq!(trajectory[1].time, 0.5);
+		assert_eq!(trajectory.position_at(0.5), Some(1.));
+		assert_eq!(trajectory.velocity_at(0.5), Some(2.));
+		assert_eq!(trajectory.velocity_at(2.5), Some(0.));
+	}
+
+	#[test]
+	fn minimum_snap_is_smooth_at_free_waypoints() {
+		let hover = |position| Waypoint {
+			velocity: Some(0.),
+			acceleration: Some(0.),
+			jerk: Some(0.),
+			..Waypoint::new(position)
+		};
+		let waypoints = [hover(0.), Waypoint::new(2.), Waypoint::new(1.), hover(4.)];
+
+		let trajectory = minimum_snap(&waypoints, &[1., 2., 1.5]).unwrap();
+
+		for waypoint in &trajectory {
+			assert_eq!(
+				trajectory.position_at(waypoint.time),
+				Some(waypoint.pose.position)
+			);
+		}
+
+		// Snap, by differences of jerk, is continuous across the free waypoints.
+		let h = 1e-6;
+		for &t in &[1., 3.] {
+			let before = (trajectory.jerk_at(t).unwrap() - trajectory.jerk_at(t - h).unwrap()) / h;
+			let after = (trajectory.jerk_at(t + h).unwrap() - trajectory.jerk_at(t).unwrap()) / h;
+
+			assert_f64_roughly_eq!(before, after, 1e-2);
+		}
+	}
+
+	#[test]
+	fn minimum_snap_minimizes_cost() {
+		let vec = |x: f64| Vec3d(x, -x, 0.5 * x);
+		let hover = |x: f64| Waypoint {
+			velocity: Some(vec(0.)),
+			acceleration: Some(vec(0.)),
+			jerk: Some(vec(0.)),
+			..Waypoint::new(vec(x))
+		};
+		let waypoints = [hover(0.), Waypoint::new(vec(1.)), hover(3.)];
+		let durations = [1., 1.5];
+
+		let optimum = cost(&waypoints, &durations, Objective::Snap).unwrap();
+		let Solution { derivatives, cost } = solve(&waypoints, &durations, Objective::Snap).unwrap();
+
+		let quadratic = |values: &[Vec3d<f64>]| -> f64 {
+			let mut total = 0.;
+			for (a, row) in cost.iter().enumerate() {
+				for (b, entry) in row.iter().enumerate() {
+					total += entry * values[a].dot(&values[b]);
+				}
+			}
+			total
+		};
+
+		let values: Vec<Vec3d<f64>> = derivatives.concat();
+		assert_f64_roughly_eq!(quadratic(&values), optimum, 1e-9);
+
+		// Nudging any free derivative of the middle waypoint costs more.
+		for d in 1..4 {
+			let mut nudged = values.clone();
+			nudged[4 + d] = nudged[4 + d] + Vec3d(0.1, 0., 0.);
+			assert!(quadratic(&nudged) > optimum);
+		}
+	}
+
+	#[test]
+	fn minimum_snap_with_uneven_durations() {
+		let hover = |position| Waypoint {
+			velocity: Some(0.),
+			acceleration: Some(0.),
+			jerk: Some(0.),
+			..Waypoint::new(position)
+		};
+		let waypoints = [hover(0.), Waypoint::new(0.1), Waypoint::new(5.), hover(5.5)];
+
+		for durations in [[0.02, 50., 1.], [0.01, 100., 1.], [0.05, 20., 1.]] {
+			let trajectory = minimum_snap(&waypoints, &durations).unwrap();
+
+			for (timed, waypoint) in trajectory.iter().zip(&waypoints) {
+				assert_f64_roughly_eq!(
+					trajectory.position_at(timed.time).unwrap(),
+					waypoint.position,
+					1e-9
+				);
+			}
+
+			// Snap, by differences of jerk, is continuous across the free
+			// waypoints, relative to its size there.
+			for timed in &trajectory[1..3] {
+				let (t, h) = (timed.time, 1e-6);
+				let jerk = |t| trajectory.jerk_at(t).unwrap();
+				let before = (jerk(t) - jerk(t - h)) / h;
+				let after = (jerk(t + h) - jerk(t)) / h;
+
+				assert_f64_roughly_eq!(before, after, 1e-3 * before.abs().max(1.));
+			}
+		}
+	}
+
+	#[test]
+	fn rejects_bad_inputs() {
+		let waypoints = [rest(0.), rest(1.)];
+
+		assert!(minimum_jerk(&waypoints, &[1.]).is_some());
+		assert_eq!(minimum_jerk(&waypoints, &[]), None);
+		assert_eq!(minimum_jerk(&waypoints, &[0.]), None);
+		assert_eq!(minimum_jerk(&waypoints[..1], &[]), None);
+
+		let with_jerk = [
+			Waypoint {
+				jerk: Some(1.),
+				..rest(0.)
+			},
+			rest(1.),
+		];
+		assert_eq!(minimum_jerk(&with_jerk, &[1.]), None);
+		assert!(minimum_snap(&with_jerk, &[1.]).is_some());
+
+		// Nothing pins down the velocity of an unconstrained pair.
+		assert_eq!(
+			minimum_snap(&[Waypoint::new(0.), Waypoint::new(1.)], &[1.]),
+			None
+		);
+	}
+
+	#[test]
+	fn optimized_durations_keep_total_and_lower_cost() {
+		let rest = |x: f64, y: f64| Waypoint {
+			velocity: Some(Vec3d(0., 0., 0.)),
+			acceleration: Some(Vec3d(0., 0., 0.)),
+			jerk: Some(Vec3d(0., 0., 0.)),
+			..Waypoint::new(Vec3d(x, y, 0.))
+		};
+		let waypoints = [
+			rest(0., 0.),
+			Waypoint::new(Vec3d(1., 0., 0.)),
+			Waypoint::new(Vec3d(1., 3., 0.)),
+			rest(0., 3.),
+		];
+		let initial = [1., 1., 1.];
+
+		let optimized = optimize_durations(&waypoints, &initial, Objective::Snap, 50).unwrap();
+
+		assert_f64_roughly_eq!(optimized.iter().sum::<f64>(), 3., 1e-9);
+		assert!(optimized.iter().all(|&d| d > 0.));
+
+		// The long middle leg should be given more time.
+		assert!(optimized[1] > optimized[0]);
+		assert!(
+			cost(&waypoints, &optimized, Objective::Snap).unwrap()
+				< cost(&waypoints, &initial, Objective::Snap).unwrap()
+		);
+	}
+}
@@ -0,0 +1,823 @@
+//! Online, jerk-limited trajectory generation.
+//!
+//! Unlike the Hermite trajectories, which interpolate between fully-specified
+//! waypoints, the generator here starts from whatever state the system is in
+//! right now and computes a jerk-limited profile to a target state, of any
+//! position, velocity and acceleration within the limits.  It is cheap
+//! enough to be rerun every control cycle, so the usual pattern is to call
+//! [`step`] with the measured state and apply the state it returns.
+//!
+//! Each axis gets its own profile, and the faster axes are then slowed to
+//! take as long as the slowest, so that all of them arrive together.
+
+use super::vec::Vec3d;
+use super::Pose3;
+
+/// The most iterations used to find the peak velocity of a profile that
+/// never reaches its velocity limit, or the cruise velocity of one that
+/// takes a given time.  Both usually stop well before this.
+const MAX_ITERATIONS: usize = 128;
+
+/// The number of equal steps across the velocity limits at which the peak
+/// velocity is sampled, to bracket each way of reaching the target.
+const PEAK_SAMPLES: usize = 32;
+
+/// How close, relative to a unit or to the distance if longer, the fastest
+/// profile must come to the target position.
+const POSITION_TOLERANCE: f64 = 1e-12;
+
+/// How close, relative to a second or to itself if longer, a profile found
+/// by [`Profile::with_duration`] must come to the duration asked for.  The
+/// last of the difference is taken up in the cruise, which moves the end
+/// position by as much times the cruise velocity.
+const DURATION_TOLERANCE: f64 = 1e-12;
+
+/// Kinematic limits on the magnitude of velocity, acceleration and jerk.
+///
+/// `Limits<f64>` bounds a single degree of freedom, while `Limits<Vec3d<f64>>`
+/// bounds each axis of a [`Vec3d`] independently.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits<V> {
+	pub velocity: V,
+	pub acceleration: V,
+	pub jerk: V,
+}
+
+/// A jerk-limited motion profile for a single degree of freedom.
+///
+/// The profile is stored as its initial state and seven phases, each of
+/// which applies a constant jerk for some duration.  It brings the initial
+/// acceleration to a peak, settles on a cruise velocity (holding it if the
+/// velocity limit is reached), and then brings the acceleration to another
+/// peak and on to the target, arriving at the target position with the
+/// target velocity and acceleration.
+///
+/// [`Profile::new`] finds the time-optimal profile of this shape, and
+/// [`Profile::with_duration`] a slower one, cruising more slowly, that
+/// takes a given time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+	start: Pose3<f64>,
+	phases: [(f64, f64); 7],
+}
+
+/// Integrates a state forward through a phase of constant `jerk`.
+fn integrate(state: &Pose3<f64>, duration: f64, jerk: f64) -> Pose3<f64> {
+	let d = duration;
+	let d2 = d * d;
+	let d3 = d2 * d;
+
+	Pose3 {
+		position: state.position + state.velocity * d + state.acceleration * d2 / 2. + jerk * d3 / 6.,
+		velocity: state.velocity + state.acceleration * d + jerk * d2 / 2.,
+		acceleration: state.acceleration + jerk * d,
+	}
+}
+
+/// Computes the phases that take `velocity` and `acceleration` to `target`
+/// velocity and `target_acceleration`, as quickly as `limits` allow.
+fn velocity_phases(
+	velocity: f64,
+	acceleration: f64,
+	target: f64,
+	target_acceleration: f64,
+	limits: &Limits<f64>,
+) -> [(f64, f64); 3] {
+	let jerk = limits.jerk;
+
+	// The velocity we would settle on if we ramped straight to the target
+	// acceleration decides which way we have to push.
+	let settled = velocity
+		+ (acceleration + target_acceleration) * (target_acceleration - acceleration).abs()
+			/ (2. * jerk);
+	let dir = if target >= settled { 1. } else { -1. };
+
+	// From here on, work as if we were speeding up.
+	let (v0, a0, vt, at) = (
+		velocity * dir,
+		acceleration * dir,
+		target * dir,
+		target_acceleration * dir,
+	);
+
+	let mut peak = (jerk * (vt - v0) + (a0 * a0 + at * at) / 2.).max(0.).sqrt();
+	let mut hold = 0.;
+
+	if peak > limits.acceleration {
+		peak = limits.acceleration;
+
+		let ramp_up = (peak + a0) * (peak - a0).abs() / (2. * jerk);
+		let ramp_down = (peak + at) * (peak - at) / (2. * jerk);
+
+		hold = ((vt - v0 - ramp_up - ramp_down) / peak).max(0.);
+	}
+
+	let first_jerk = if peak >= a0 { jerk } else { -jerk };
+
+	[
+		((peak - a0).abs() / jerk, first_jerk * dir),
+		(hold, 0.),
+		((peak - at) / jerk, -jerk * dir),
+	]
+}
+
+/// Computes the phases from `current` to `target`, cruising at
+/// `cruise_velocity` for `cruise` seconds on the way.
+fn phases(
+	current: &Pose3<f64>,
+	target: &Pose3<f64>,
+	limits: &Limits<f64>,
+	cruise_velocity: f64,
+	cruise: f64,
+) -> [(f64, f64); 7] {
+	let [up, peak, down] = velocity_phases(
+		current.velocity,
+		current.acceleration,
+		cruise_velocity,
+		0.,
+		limits,
+	);
+	let [leave, hold, arrive] = velocity_phases(
+		cruise_velocity,
+		0.,
+		target.velocity,
+		target.acceleration,
+		limits,
+	);
+
+	[up, peak, down, (cruise, 0.), leave, hold, arrive]
+}
+
+/// The total duration of `phases`.
+fn total(phases: &[(f64, f64)]) -> f64 {
+	phases.iter().map(|(duration, _)| duration).sum()
+}
+
+/// Narrows the bracket from `lo` to `hi`, each a point and the value of `f`
+/// there, around where `f` changes sign, until `close` accepts the value at
+/// either end or the bracket can't be split any further.
+///
+/// Each step is by regula falsi, halving the value kept at an end that has
+/// not moved for two steps so that both ends close in, which takes far fewer
+/// steps than bisection.  While either value is infinite, it bisects.
+fn narrow(
+	f: impl Fn(f64) -> f64,
+	mut lo: (f64, f64),
+	mut hi: (f64, f64),
+	close: impl Fn(f64) -> bool,
+) -> ((f64, f64), (f64, f64)) {
+	// Whether the last step moved `lo`, or `hi`.
+	let mut moved_lo = None;
+
+	for _ in 0..MAX_ITERATIONS {
+		if close(lo.1) || close(hi.1) {
+			break;
+		}
+
+		let mid = if lo.1.is_finite() && hi.1.is_finite() {
+			(lo.0 * hi.1 - hi.0 * lo.1) / (hi.1 - lo.1)
+		} else {
+			(lo.0 + hi.0) / 2.
+		};
+
+		if mid <= lo.0 || mid >= hi.0 || mid.is_nan() {
+			break;
+		}
+
+		let value = f(mid);
+
+		if (value <= 0.) == (lo.1 <= 0.) {
+			lo = (mid, value);
+
+			if moved_lo == Some(true) {
+				hi.1 /= 2.;
+			}
+
+			moved_lo = Some(true);
+		} else {
+			hi = (mid, value);
+
+			if moved_lo == Some(false) {
+				lo.1 /= 2.;
+			}
+
+			moved_lo = Some(false);
+		}
+	}
+
+	(lo, hi)
+}
+
+impl Profile {
+	/// Computes the profile from `current` to `target`.
+	///
+	/// Returns `None` if the limits are not all positive, or if the target
+	/// can't be reached within them: if its velocity or acceleration exceeds
+	/// its limit, or if its velocity when its acceleration was last zero
+	/// would have.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use motion_planning::otg::{Limits, Profile};
+	/// use motion_planning::Pose3;
+	///
+	/// let rest = |position| Pose3 {
+	///     position,
+	///     velocity: 0.,
+	///     acceleration: 0.,
+	/// };
+	/// let limits = Limits {
+	///     velocity: 1.,
+	///     acceleration: 1.,
+	///     jerk: 1.,
+	/// };
+	///
+	/// let profile = Profile::new(&rest(0.), &rest(10.), &limits).unwrap();
+	///
+	/// // Two seconds to reach full speed, eight seconds of cruise, two more
+	/// // seconds to stop.
+	/// assert!((profile.duration() - 12.).abs() < 1e-9);
+	/// assert!((profile.state_at(12.).position - 10.).abs() < 1e-9);
+	/// ```
+	pub fn new(current: &Pose3<f64>, target: &Pose3<f64>, limits: &Limits<f64>) -> Option<Self> {
+		if limits.velocity <= 0. || limits.acceleration <= 0. || limits.jerk <= 0. {
+			return None;
+		}
+
+		// Arriving with an acceleration means having passed through zero
+		// acceleration at this velocity.
+		let approach =
+			target.velocity - target.acceleration * target.acceleration.abs() / (2. * limits.jerk);
+
+		if target.velocity.abs() > limits.velocity
+			|| target.acceleration.abs() > limits.acceleration
+			|| approach.abs() > limits.velocity
+		{
+			return None;
+		}
+
+		let displacement = |cruise_velocity: f64| -> f64 {
+			Self {
+				start: *current,
+				phases: phases(current, target, limits, cruise_velocity, 0.),
+			}
+			.end()
+			.position
+				- current.position
+		};
+
+		let distance = target.position - current.position;
+
+		let fastest = displacement(limits.velocity);
+		let slowest = displacement(-limits.velocity);
+
+		let phases = if distance >= fastest {
+			phases(
+				current,
+				target,
+				limits,
+				limits.velocity,
+				(distance - fastest) / limits.velocity,
+			)
+		} else if distance <= slowest {
+			phases(
+				current,
+				target,
+				limits,
+				-limits.velocity,
+				(slowest - distance) / limits.velocity,
+			)
+		} else {
+			// The displacement need not be monotonic in the peak velocity,
+			// since braking in two steps covers more ground than braking in
+			// one, so every crossing is bracketed and the quickest kept.
+			// The velocities settled on from the current and target states
+			// are where the direction of the pushes changes.
+			let settled =
+				current.velocity + current.acceleration * current.acceleration.abs() / (2. * limits.jerk);
+			let mut samples: Vec<f64> = (0..=PEAK_SAMPLES)
+				.map(|i| limits.velocity * (2. * i as f64 / PEAK_SAMPLES as f64 - 1.))
+				.chain([settled, approach])
+				.filter(|v| v.abs() <= limits.velocity)
+				.collect();
+			samples.sort_by(f64::total_cmp);
+
+			let excess = |peak: f64| displacement(peak) - distance;
+			let tolerance = POSITION_TOLERANCE * distance.abs().max(1.);
+			let excesses: Vec<(f64, f64)> = samples.iter().map(|&peak| (peak, excess(peak))).collect();
+
+			excesses
+				.windows(2)
+				.filter(|pair| (pair[0].1 <= 0.) != (pair[1].1 <= 0.))
+				.map(|pair| {
+					let (lo, hi) = narrow(excess, pair[0], pair[1], |e| e.abs() <= tolerance);
+					let peak = if lo.1.abs() <= hi.1.abs() { lo.0 } else { hi.0 };
+
+					phases(current, target, limits, peak, 0.)
+				})
+				.min_by(|a, b| total(a).total_cmp(&total(b)))?
+		};
+
+		Some(Self {
+			start: *current,
+			phases,
+		})
+	}
+
+	/// Computes a profile from `current` to `target` that takes `duration`,
+	/// by cruising more slowly than [`Profile::new`] would.
+	///
+	/// Returns `None` under the same conditions as [`Profile::new`], or if no
+	/// profile of this shape takes exactly `duration`: if it is shorter than
+	/// the fastest, or if slowing the cruise can't stretch the profile that
+	/// far, as when the target is reached without cruising at all.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use motion_planning::otg::{Limits, Profile};
+	/// use motion_planning::Pose3;
+	///
+	/// let rest = |position| Pose3 {
+	///     position,
+	///     velocity: 0.,
+	///     acceleration: 0.,
+	/// };
+	/// let limits = Limits {
+	///     velocity: 1.,
+	///     acceleration: 1.,
+	///     jerk: 1.,
+	/// };
+	///
+	/// let profile = Profile::with_duration(&rest(0.), &rest(10.), &limits, 15.).unwrap();
+	///
+	/// assert!((profile.duration() - 15.).abs() < 1e-9);
+	/// assert!((profile.state_at(15.).position - 10.).abs() < 1e-9);
+	///
+	/// assert_eq!(Profile::with_duration(&rest(0.), &rest(10.), &limits, 11.), None);
+	/// ```
+	pub fn with_duration(
+		current: &Pose3<f64>,
+		target: &Pose3<f64>,
+		limits: &Limits<f64>,
+		duration: f64,
+	) -> Option<Self> {
+		Self::new(current, target, limits)?.stretch(target, limits, duration)
+	}
+
+	/// Stretches this profile, the fastest to `target`, to take `duration`,
+	/// as [`Profile::with_duration`] does.
+	fn stretch(&self, target: &Pose3<f64>, limits: &Limits<f64>, duration: f64) -> Option<Self> {
+		let current = &self.start;
+		let tolerance = DURATION_TOLERANCE * duration.max(1.);
+
+		if duration < self.duration() {
+			return None;
+		}
+
+		if duration - self.duration() <= tolerance {
+			let mut profile = self.clone();
+			profile.phases[3].0 += duration - profile.duration();
+
+			return Some(profile);
+		}
+
+		// The fastest profile peaks at its cruise velocity between its third
+		// and fifth phases.
+		let fastest_cruise = self.state_at(total(&self.phases[..3])).velocity;
+
+		// Cruising at `velocity` for as long as it takes to reach the target.
+		let cruising = |velocity: f64| -> Option<Self> {
+			let end = Self {
+				start: *current,
+				phases: phases(current, target, limits, velocity, 0.),
+			}
+			.end();
+			let cruise = (target.position - end.position) / velocity;
+
+			if !(cruise >= 0. && cruise.is_finite()) {
+				return None;
+			}
+
+			Some(Self {
+				start: *current,
+				phases: phases(current, target, limits, velocity, cruise),
+			})
+		};
+
+		// Slower cruising takes longer, so search the fractions of the fastest
+		// cruise velocity for the one that takes `duration`, keeping `hi` at a
+		// profile that is no longer than asked.  A cruise too slow to reach the
+		// target at all counts as too long.
+		let overrun = |fraction: f64| {
+			cruising(fraction * fastest_cruise).map_or(f64::INFINITY, |p| p.duration() - duration)
+		};
+		let (_, (fraction, overrun)) = narrow(
+			overrun,
+			(0., f64::INFINITY),
+			(1., self.duration() - duration),
+			|overrun| (-tolerance..=0.).contains(&overrun),
+		);
+
+		if !(-tolerance..=0.).contains(&overrun) {
+			return None;
+		}
+
+		let mut best = cruising(fraction * fastest_cruise)?;
+
+		// Take up the last of the difference in the cruise.
+		best.phases[3].0 += duration - best.duration();
+
+		Some(best)
+	}
+
+	/// The total duration of the profile.
+	pub fn duration(&self) -> f64 {
+		total(&self.phases)
+	}
+
+	/// The state at the end of the profile.
+	pub fn end(&self) -> Pose3<f64> {
+		self.state_at(self.duration())
+	}
+
+	/// Computes the state `t` seconds after the start of the profile.
+	///
+	/// Times before the start return the initial state, and times after the
+	/// end continue with the final acceleration.
+	pub fn state_at(&self, t: f64) -> Pose3<f64> {
+		let mut state = self.start;
+		let mut remaining = t.max(0.);
+
+		for &(duration, jerk) in &self.phases {
+			if remaining <= duration {
+				return integrate(&state, remaining, jerk);
+			}
+
+			state = integrate(&state, duration, jerk);
+			remaining -= duration;
+		}
+
+		integrate(&state, remaining, 0.)
+	}
+}
+
+/// Computes the state one control cycle of `dt` seconds from `current`, on
+/// the way to `target`.
+///
+/// Each axis is planned with its own limits, and then every axis but the
+/// slowest is replanned with [`Profile::with_duration`] to arrive with it.
+/// An axis that can't be slowed to match, because it reaches its target
+/// without cruising, keeps its fastest profile.  Returns `None` under the
+/// same conditions as [`Profile::new`] for any axis.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::otg::{step, Limits};
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose3;
+///
+/// let limits = Limits {
+///     velocity: Vec3d(1., 1., 1.),
+///     acceleration: Vec3d(2., 2., 2.),
+///     jerk: Vec3d(10., 10., 10.),
+/// };
+/// let target = Pose3 {
+///     position: Vec3d(1., 2., 0.),
+///     velocity: Vec3d(0., 0., 0.),
+///     acceleration: Vec3d(0., 0., 0.),
+/// };
+///
+/// let mut state = Pose3 {
+///     position: Vec3d(0., 0., 0.),
+///     velocity: Vec3d(0., 0., 0.),
+///     acceleration: Vec3d(0., 0., 0.),
+/// };
+///
+/// for _ in 0..1000 {
+///     state = step(&state, &target, &limits, 0.01).unwrap();
+/// }
+///
+/// assert!((state.position.1 - 2.).abs() < 1e-6);
+/// ```
+pub fn step(
+	current: &Pose3<Vec3d<f64>>,
+	target: &Pose3<Vec3d<f64>>,
+	limits: &Limits<Vec3d<f64>>,
+	dt: f64,
+) -> Option<Pose3<Vec3d<f64>>> {
+	let axis = |select: fn(&Vec3d<f64>) -> f64| {
+		let component = |pose: &Pose3<Vec3d<f64>>| Pose3 {
+			position: select(&pose.position),
+			velocity: select(&pose.velocity),
+			acceleration: select(&pose.acceleration),
+		};
+
+		let limits = Limits {
+			velocity: select(&limits.velocity),
+			acceleration: select(&limits.acceleration),
+			jerk: select(&limits.jerk),
+		};
+
+		(component(current), component(target), limits)
+	};
+
+	let axes = [axis(|v| v.0), axis(|v| v.1), axis(|v| v.2)];
+	let mut profiles = Vec::with_capacity(3);
+
+	for (current, target, limits) in &axes {
+		profiles.push(Profile::new(current, target, limits)?);
+	}
+
+	let duration = profiles.iter().map(Profile::duration).fold(0., f64::max);
+
+	let [x, y, z] = [0, 1, 2].map(|i| {
+		let (_, target, limits) = &axes[i];
+
+		profiles[i]
+			.stretch(target, limits, duration)
+			.as_ref()
+			.unwrap_or(&profiles[i])
+			.state_at(dt)
+	});
+
+	Some(Pose3 {
+		position: Vec3d(x.position, y.position, z.position),
+		velocity: Vec3d(x.velocity, y.velocity, z.velocity),
+		acceleration: Vec3d(x.acceleration, y.acceleration, z.acceleration),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::*;
+
+	fn state(position: f64, velocity: f64, acceleration: f64) -> Pose3<f64> {
+		Pose3 {
+			position,
+			velocity,
+			acceleration,
+		}
+	}
+
+	const LIMITS: Limits<f64> = Limits {
+		velocity: 2.,
+		acceleration: 1.5,
+		jerk: 3.,
+	};
+
+	fn assert_reaches(current: Pose3<f64>, target: Pose3<f64>) -> Profile {
+		let profile = Profile::new(&current, &target, &LIMITS).unwrap();
+		let end = profile.end();
+
+		assert_f64_roughly_eq!(end.position, target.position, 1e-9);
+		assert_f64_roughly_eq!(end.velocity, target.velocity, 1e-9);
+		assert_f64_roughly_eq!(end.acceleration, target.acceleration, 1e-9);
+
+		profile
+	}
+
+	fn assert_within_limits(profile: &Profile) {
+		let steps = 1000;
+		for i in 0..=steps {
+			let t = profile.duration() * f64::from(i) / f64::from(steps);
+			let s = profile.state_at(t);
+
+			assert!(s.velocity.abs() <= LIMITS.velocity + 1e-9);
+			assert!(s.acceleration.abs() <= LIMITS.acceleration + 1e-9);
+		}
+	}
+
+	#[test]
+	fn rest_to_rest_with_cruise() {
+		let profile = assert_reaches(state(0., 0., 0.), state(10., 0., 0.));
+
+		assert_within_limits(&profile);
+		assert_f64_roughly_eq!(profile.state_at(profile.duration() / 2.).velocity, 2., 1e-9);
+	}
+
+	#[test]
+	fn rest_to_rest_short_move() {
+		let profile = assert_reaches(state(0., 0., 0.), state(0.1, 0., 0.));
+
+		assert_within_limits(&profile);
+	}
+
+	#[test]
+	fn backwards_move() {
+		let profile = assert_reaches(state(3., 0., 0.), state(-4., 0., 0.));
+
+		assert_within_limits(&profile);
+		assert!(profile.state_at(profile.duration() / 2.).velocity < 0.);
+	}
+
+	#[test]
+	fn from_moving_state_past_target() {
+		// Moving quickly and accelerating away from a target just behind us.
+		let profile = assert_reaches(state(0., 1.5, 1.), state(-0.5, 0., 0.));
+
+		assert_within_limits(&profile);
+	}
+
+	#[test]
+	fn to_nonzero_target_velocity() {
+		let profile = assert_reaches(state(0., -1., 0.5), state(5., 1., 0.));
+
+		assert_within_limits(&profile);
+	}
+
+	#[test]
+	fn from_above_acceleration_limit() {
+		assert_reaches(state(0., 0., 2.), state(4., 0., 0.));
+	}
+
+	#[test]
+	fn from_above_velocity_limit() {
+		assert_reaches(state(0., 3., 0.), state(20., 0., 0.));
+	}
+
+	#[test]
+	fn already_at_target() {
+		let profile = assert_reaches(state(1., 0., 0.), state(1., 0., 0.));
+
+		// Displacement grows with the cube of the duration near zero, so the
+		// search only pins the duration down to about the cube root of the
+		// position precision.
+		assert_f64_roughly_eq!(profile.duration(), 0., 1e-4);
+	}
+
+	#[test]
+	fn state_at_clamps_before_start_and_coasts_after_end() {
+		let profile = assert_reaches(state(0., 0., 0.), state(2., 1., 0.));
+
+		assert_eq!(profile.state_at(-1.), state(0., 0., 0.));
+
+		let after = profile.state_at(profile.duration() + 1.);
+		assert_f64_roughly_eq!(after.position, 3., 1e-9);
+		assert_f64_roughly_eq!(after.velocity, 1., 1e-9);
+	}
+
+	#[test]
+	fn peak_velocity_monotonic_in_target_position() {
+		// The search relies on the peak velocity mapping monotonically to
+		// the displacement of the profile.
+		let start = state(0., 0.7, -1.2);
+		let mut previous = f64::NEG_INFINITY;
+
+		for i in -100..=100 {
+			let target = f64::from(i) / 10.;
+			let profile = Profile::new(&start, &state(target, 0., 0.), &LIMITS).unwrap();
+			let peak = (0..=200)
+				.map(|k| {
+					profile
+						.state_at(profile.duration() * f64::from(k) / 200.)
+						.velocity
+				})
+				.fold(f64::NEG_INFINITY, f64::max);
+
+			assert!(peak >= previous - 1e-6);
+			previous = peak;
+		}
+	}
+
+	#[test]
+	fn rejects_invalid_inputs() {
+		let bad_limits = Limits { jerk: 0., ..LIMITS };
+
+		assert_eq!(
+			Profile::new(&state(0., 0., 0.), &state(1., 0., 0.), &bad_limits),
+			None
+		);
+		assert_eq!(
+			Profile::new(&state(0., 0., 0.), &state(1., 3., 0.), &LIMITS),
+			None
+		);
+		assert_eq!(
+			Profile::new(&state(0., 0., 0.), &state(1., 0., 2.), &LIMITS),
+			None
+		);
+
+		// Slowing into the target at full speed means having been too fast.
+		assert_eq!(
+			Profile::new(&state(0., 0., 0.), &state(5., 2., -1.), &LIMITS),
+			None
+		);
+	}
+
+	#[test]
+	fn to_nonzero_target_acceleration() {
+		for target in [
+			state(5., 1., 1.),
+			state(5., 1., -1.),
+			state(-3., -0.5, 1.2),
+			state(0.2, 0., -1.5),
+		] {
+			let profile = assert_reaches(state(0., 0.5, -0.5), target);
+
+			assert_within_limits(&profile);
+		}
+	}
+
+	#[test]
+	fn stretched_to_duration() {
+		for (current, target) in [
+			(state(0., 0., 0.), state(10., 0., 0.)),
+			(state(0., 1.5, 1.), state(-4., 0., 0.)),
+			(state(2., -1., 0.5), state(6., 1., -0.5)),
+		] {
+			let fastest = assert_reaches(current, target);
+
+			for extra in [0., 0.5, 3.] {
+				let duration = fastest.duration() + extra;
+				let profile = Profile::with_duration(&current, &target, &LIMITS, duration).unwrap();
+				let end = profile.end();
+
+				assert_f64_roughly_eq!(profile.duration(), duration, 1e-12);
+				assert_f64_roughly_eq!(end.position, target.position, 1e-9);
+				assert_f64_roughly_eq!(end.velocity, target.velocity, 1e-9);
+				assert_f64_roughly_eq!(end.acceleration, target.acceleration, 1e-9);
+				assert_within_limits(&profile);
+			}
+
+			let sooner = fastest.duration() - 0.1;
+			assert_eq!(
+				Profile::with_duration(&current, &target, &LIMITS, sooner),
+				None
+			);
+		}
+	}
+
+	#[test]
+	fn step_advances_each_axis() {
+		let limits = Limits {
+			velocity: Vec3d(LIMITS.velocity, LIMITS.velocity, LIMITS.velocity),
+			acceleration: Vec3d(
+				LIMITS.acceleration,
+				LIMITS.acceleration,
+				LIMITS.acceleration,
+			),
+			jerk: Vec3d(LIMITS.jerk, LIMITS.jerk, LIMITS.jerk),
+		};
+		let current = Pose3 {
+			position: Vec3d(0., 0., 0.),
+			velocity: Vec3d(0., 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		};
+		let target = Pose3 {
+			position: Vec3d(1., -1., 0.),
+			..current
+		};
+
+		let next = step(&current, &target, &limits, 0.1).unwrap();
+
+		assert!(next.velocity.0 > 0.);
+		assert!(next.velocity.1 < 0.);
+		assert_f64_roughly_eq!(next.velocity.2, 0.);
+		assert_f64_roughly_eq!(next.acceleration.0, LIMITS.jerk * 0.1, 1e-12);
+
+		let unreachable = Pose3 {
+			velocity: Vec3d(0., 0., 5.),
+			..target
+		};
+		assert_eq!(step(&current, &unreachable, &limits, 0.1), None);
+	}
+
+	#[test]
+	fn step_synchronizes_axes() {
+		let limits = Limits {
+			velocity: Vec3d(2., 1., 3.),
+			acceleration: Vec3d(1.5, 2., 1.),
+			jerk: Vec3d(3., 5., 2.),
+		};
+		let target = Pose3 {
+			position: Vec3d(1., -6., 2.),
+			velocity: Vec3d(0., 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		};
+		let mut state = Pose3 {
+			position: Vec3d(0., 0., 0.),
+			velocity: Vec3d(0.5, 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		};
+
+		// The cycle on which each axis first settles at its target.
+		let mut arrivals = [None; 3];
+
+		for cycle in 0..2000 {
+			state = step(&state, &target, &limits, 0.01).unwrap();
+
+			let error = state.position + target.position * -1.;
+			for (arrival, e) in arrivals.iter_mut().zip([error.0, error.1, error.2]) {
+				if arrival.is_none() && e.abs() < 1e-6 {
+					*arrival = Some(cycle);
+				}
+			}
+		}
+
+		let [x, y, z] = arrivals.map(Option::unwrap);
+		assert!(x.max(y).max(z) - x.min(y).min(z) <= 2, "{:?}", arrivals);
+	}
+}
@@ -0,0 +1,258 @@
+//! Poses in the plane: a position and a heading.
+//!
+//! An [`Se2`] holds `x`, `y` and `heading` side by side, so a [`Pose3`] of
+//! them plans all three with the same smoothness, and the heading is free to
+//! differ from the direction of travel as a holonomic drive needs.  Its
+//! velocity and acceleration hold the heading's rate and acceleration in
+//! the same way.
+//!
+//! Headings are angles, so a turn from just below π to just above -π is a
+//! short one.  A [`PlanarTrajectory`] takes that into account, turning the
+//! shorter way between waypoints and giving headings back in `(-π, π]`.
+
+use core::f64::consts::{PI, TAU};
+use core::ops::{Add, Mul};
+
+use super::timed::{timed, Timed};
+use super::{Pose3, Segment3, Trajectory3};
+
+/// A position in the plane with a heading, in radians anticlockwise from
+/// the x axis; or the rate of change of one.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Se2 {
+	pub x: f64,
+	pub y: f64,
+	pub heading: f64,
+}
+
+impl Add<Se2> for Se2 {
+	type Output = Self;
+
+	fn add(self, other: Se2) -> Self::Output {
+		Se2 {
+			x: self.x + other.x,
+			y: self.y + other.y,
+			heading: self.heading + other.heading,
+		}
+	}
+}
+
+impl Mul<f64> for Se2 {
+	type Output = Self;
+
+	fn mul(self, scalar: f64) -> Self::Output {
+		Se2 {
+			x: self.x * scalar,
+			y: self.y * scalar,
+			heading: self.heading * scalar,
+		}
+	}
+}
+
+/// Wraps `angle` into `(-π, π]`.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::planar::wrap;
+///
+/// assert!((wrap(3. * std::f64::consts::PI / 2.) + std::f64::consts::PI / 2.).abs() < 1e-15);
+/// assert_eq!(wrap(-std::f64::consts::PI), std::f64::consts::PI);
+/// ```
+pub fn wrap(angle: f64) -> f64 {
+	let wrapped = angle.rem_euclid(TAU);
+
+	if wrapped > PI {
+		wrapped - TAU
+	} else {
+		wrapped
+	}
+}
+
+/// A trajectory through planar waypoints, turning the shorter way between
+/// each pair of headings.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::planar::{PlanarTrajectory, Se2};
+/// use motion_planning::{Pose3, Trajectory3};
+///
+/// let waypoint = |x, heading| Pose3 {
+///     position: Se2 { x, y: 0., heading },
+///     velocity: Se2 { x: 1., y: 0., heading: 0. },
+///     acceleration: Se2::default(),
+/// };
+///
+/// // Drive along x while turning from 170° to -170°, through 180°.
+/// let path = PlanarTrajectory::untimed(&[waypoint(0., 3.0), waypoint(1., -3.0)]);
+/// let halfway = path.position_at(0.5).unwrap();
+///
+/// assert!((halfway.x - 0.5).abs() < 1e-15);
+/// assert!((halfway.heading.abs() - std::f64::consts::PI).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlanarTrajectory {
+	/// The waypoints, with each heading moved by whole turns to within half
+	/// a turn of the heading before it.
+	waypoints: Vec<Timed<Pose3<Se2>>>,
+}
+
+impl PlanarTrajectory {
+	/// Builds a trajectory through `waypoints`, which need not have their
+	/// headings wrapped in any particular way.
+	pub fn new(waypoints: &[Timed<Pose3<Se2>>]) -> Self {
+		let mut unwrapped = waypoints.to_vec();
+
+		for i in 1..unwrapped.len() {
+			let previous = unwrapped[i - 1].pose.position.heading;
+			let heading = &mut unwrapped[i].pose.position.heading;
+
+			*heading = previous + wrap(*heading - previous);
+		}
+
+		PlanarTrajectory {
+			waypoints: unwrapped,
+		}
+	}
+
+	/// Builds a trajectory through `waypoints` timed by their indices, like a
+	/// plain waypoint vector.
+	pub fn untimed(waypoints: &[Pose3<Se2>]) -> Self {
+		PlanarTrajectory::new(&timed(waypoints))
+	}
+
+	/// The waypoints, with each heading moved by whole turns to within half a
+	/// turn of the one before it.
+	pub fn waypoints(&self) -> &[Timed<Pose3<Se2>>] {
+		&self.waypoints
+	}
+}
+
+/// Velocities and accelerations come out as from any other trajectory, with
+/// the heading's rate and acceleration in their `heading`.  Positions come
+/// out with the heading wrapped into `(-π, π]`.
+impl Trajectory3<Se2> for PlanarTrajectory {
+	fn get_segment(&self, t: f64) -> Option<Segment3<'_, Se2>> {
+		self.waypoints.get_segment(t)
+	}
+
+	fn position_at(&self, t: f64) -> Option<Se2> {
+		let position = self.waypoints.position_at(t)?;
+
+		Some(Se2 {
+			heading: wrap(position.heading),
+			..position
+		})
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<Se2> {
+		self.waypoints.velocity_at(t)
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<Se2> {
+		self.waypoints.acceleration_at(t)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::*;
+
+	fn rest(x: f64, y: f64, heading: f64) -> Pose3<Se2> {
+		Pose3 {
+			position: Se2 { x, y, heading },
+			velocity: Se2::default(),
+			acceleration: Se2::default(),
+		}
+	}
+
+	#[test]
+	fn wrapping() {
+		assert_f64_roughly_eq!(wrap(0.5), 0.5);
+		assert_f64_roughly_eq!(wrap(-0.5 - 4. * PI), -0.5, 1e-14);
+		assert_f64_roughly_eq!(wrap(PI), PI);
+		assert_f64_roughly_eq!(wrap(7. * PI), PI, 1e-14);
+	}
+
+	#[test]
+	fn turns_shorter_way() {
+		let path = PlanarTrajectory::untimed(&[
+			rest(0., 0., -3.),
+			rest(0., 0., 3.),
+			rest(0., 0., 3. + 4. * TAU),
+		]);
+
+		// Each heading is unwrapped to near the one before.
+		let headings: Vec<f64> = path
+			.waypoints()
+			.iter()
+			.map(|w| w.pose.position.heading)
+			.collect();
+		assert_f64_roughly_eq!(headings[1], 3. - TAU, 1e-14);
+		assert_f64_roughly_eq!(headings[2], 3. - TAU, 1e-12);
+
+		// The heading never passes through zero on the way.
+		for i in 0..=100 {
+			let heading = path.position_at(f64::from(i) / 100.).unwrap().heading;
+
+			assert!(heading.abs() >= 3. - 1e-12, "{}", heading);
+			assert!((-PI..=PI).contains(&heading));
+		}
+
+		assert!(path.velocity_at(0.5).unwrap().heading < 0.);
+		assert_eq!(path.velocity_at(1.5), Some(Se2::default()));
+	}
+
+	#[test]
+	fn heading_independent_of_travel() {
+		// Strafe sideways along y while spinning a full turn.
+		let path = PlanarTrajectory::new(&[
+			Timed {
+				time: 0.,
+				pose: rest(0., 0., 0.),
+			},
+			Timed {
+				time: 2.,
+				pose: Pose3 {
+					position: Se2 {
+						x: 0.,
+						y: 1.,
+						heading: PI,
+					},
+					velocity: Se2 {
+						x: 0.,
+						y: 0.5,
+						heading: 2.,
+					},
+					acceleration: Se2::default(),
+				},
+			},
+			Timed {
+				time: 4.,
+				pose: rest(0., 2., 0.),
+			},
+		]);
+
+		for t in [0.5, 1., 2., 3.] {
+			let position = path.position_at(t).unwrap();
+
+			assert_eq!(position.x, 0.);
+			assert!(path.velocity_at(t).unwrap().heading > 0.);
+		}
+
+		assert_f64_roughly_eq!(path.position_at(4.).unwrap().heading, 0., 1e-12);
+		assert_f64_roughly_eq!(path.velocity_at(2.).unwrap().heading, 2.);
+
+		let dt = 1e-6;
+		let t = 2.7;
+		let turn =
+			path.position_at(t + dt).unwrap().heading - path.position_at(t - dt).unwrap().heading;
+		let difference = wrap(turn) / (2. * dt);
+
+		assert_f64_roughly_eq!(path.velocity_at(t).unwrap().heading, difference, 1e-6);
+	}
+}
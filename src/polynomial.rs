@@ -0,0 +1,445 @@
+//! Real polynomials in one variable, and their real roots.
+//!
+//! Every segment of a trajectory is a polynomial in time, so questions such
+//! as "when is the speed greatest?" or "when does the path cross this plane?"
+//! come down to finding the roots of a polynomial on an interval.  Roots are
+//! isolated with a Sturm sequence, which counts the distinct real roots in
+//! any interval, and then narrowed down by bisecting on that count.
+
+use core::ops::{Add, Mul};
+
+use super::{Pose3, Pose4};
+
+/// The number of times an interval may be halved while isolating a root,
+/// which is more than enough to reach the precision of an `f64`.
+const BISECTION_DEPTH: usize = 128;
+
+/// Remainders whose coefficients are all this small, relative to the
+/// polynomial they were divided from, are taken to be zero.
+const REMAINDER_THRESHOLD: f64 = 1e-10;
+
+/// Values this small, relative to the sum of the magnitudes of the terms
+/// making them up, are taken to be zero.
+const ROOT_THRESHOLD: f64 = 1e-12;
+
+/// A polynomial, as its coefficients in order of increasing power.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::polynomial::Polynomial;
+///
+/// // x² - 2
+/// let p = Polynomial(vec![-2., 0., 1.]);
+///
+/// assert_eq!(p.eval(3.), 7.);
+/// assert_eq!(p.derivative(), Polynomial(vec![0., 2.]));
+///
+/// let roots = p.roots(0., 10.);
+/// assert_eq!(roots.len(), 1);
+/// assert!((roots[0] - 2_f64.sqrt()).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial(pub Vec<f64>);
+
+impl Polynomial {
+	/// The degree of the polynomial, or `None` if it is zero everywhere.
+	pub fn degree(&self) -> Option<usize> {
+		self.0.iter().rposition(|&c| c != 0.)
+	}
+
+	/// Evaluates the polynomial at `x`, using Horner's method.
+	pub fn eval(&self, x: f64) -> f64 {
+		self.0.iter().rev().fold(0., |sum, &c| sum * x + c)
+	}
+
+	pub fn derivative(&self) -> Polynomial {
+		Polynomial(
+			self
+				.0
+				.iter()
+				.enumerate()
+				.skip(1)
+				.map(|(i, &c)| c * i as f64)
+				.collect(),
+		)
+	}
+
+	/// Finds the distinct real roots of the polynomial between `a` and `b`
+	/// inclusive, in increasing order.
+	///
+	/// A polynomial that is zero everywhere is reported as having no roots.
+	pub fn roots(&self, a: f64, b: f64) -> Vec<f64> {
+		let degree = match self.degree() {
+			Some(degree) if degree > 0 => degree,
+			_ => return Vec::new(),
+		};
+
+		let sequence = sturm_sequence(&self.0[..=degree]);
+		let changes = |x: f64| sign_changes(&sequence, x, false);
+
+		let mut roots = Vec::new();
+
+		// The Sturm count covers `(a, b]`, so `a` itself is checked directly.
+		// Counting at a root must skip the polynomial itself, whose sign there
+		// is only rounding error.
+		let at_a = vanishes(self, a);
+		let at_b = vanishes(self, b);
+
+		if at_a {
+			roots.push(a);
+		}
+
+		if a < b {
+			isolate(
+				&changes,
+				(a, sign_changes(&sequence, a, at_a)),
+				(b, sign_changes(&sequence, b, at_b)),
+				0,
+				&mut roots,
+			);
+		}
+
+		roots
+	}
+}
+
+impl Add for Polynomial {
+	type Output = Polynomial;
+
+	fn add(self, other: Polynomial) -> Polynomial {
+		let (mut long, short) = if self.0.len() >= other.0.len() {
+			(self, other)
+		} else {
+			(other, self)
+		};
+
+		for (c, s) in long.0.iter_mut().zip(short.0) {
+			*c += s;
+		}
+
+		long
+	}
+}
+
+impl Mul for Polynomial {
+	type Output = Polynomial;
+
+	fn mul(self, other: Polynomial) -> Polynomial {
+		if self.0.is_empty() || other.0.is_empty() {
+			return Polynomial(Vec::new());
+		}
+
+		let mut product = vec![0.; self.0.len() + other.0.len() - 1];
+
+		for (i, &a) in self.0.iter().enumerate() {
+			for (j, &b) in other.0.iter().enumerate() {
+				product[i + j] += a * b;
+			}
+		}
+
+		Polynomial(product)
+	}
+}
+
+impl Mul<f64> for Polynomial {
+	type Output = Polynomial;
+
+	fn mul(self, scalar: f64) -> Polynomial {
+		Polynomial(self.0.into_iter().map(|c| c * scalar).collect())
+	}
+}
+
+/// Rewrites the quintic Hermite segment from `prec` to `succ`, lasting
+/// `duration`, as a polynomial in the fraction of the way through it.
+pub(crate) fn quintic_segment(prec: &Pose3<f64>, succ: &Pose3<f64>, duration: f64) -> Polynomial {
+	let h = duration;
+
+	let (p0, v0, a0) = (prec.position, prec.velocity * h, prec.acceleration * h * h);
+	let (p1, v1, a1) = (succ.position, succ.velocity * h, succ.acceleration * h * h);
+	let d = p1 - p0;
+
+	Polynomial(vec![
+		p0,
+		v0,
+		a0 / 2.,
+		10. * d - 6. * v0 - 4. * v1 - 1.5 * a0 + 0.5 * a1,
+		-15. * d + 8. * v0 + 7. * v1 + 1.5 * a0 - a1,
+		6. * d - 3. * v0 - 3. * v1 - 0.5 * a0 + 0.5 * a1,
+	])
+}
+
+/// Rewrites the septic Hermite segment from `prec` to `succ`, lasting
+/// `duration`, as a polynomial in the fraction of the way through it.
+pub(crate) fn septic_segment(prec: &Pose4<f64>, succ: &Pose4<f64>, duration: f64) -> Polynomial {
+	let h = duration;
+
+	let (p0, v0) = (prec.position, prec.velocity * h);
+	let (a0, j0) = (prec.acceleration * h * h, prec.jerk * h * h * h);
+	let (p1, v1) = (succ.position, succ.velocity * h);
+	let (a1, j1) = (succ.acceleration * h * h, succ.jerk * h * h * h);
+	let d = p1 - p0;
+
+	Polynomial(vec![
+		p0,
+		v0,
+		a0 / 2.,
+		j0 / 6.,
+		35. * d - 20. * v0 - 15. * v1 - 5. * a0 + 2.5 * a1 - 2. / 3. * j0 - j1 / 6.,
+		-84. * d + 45. * v0 + 39. * v1 + 10. * a0 - 7. * a1 + j0 + 0.5 * j1,
+		70. * d - 36. * v0 - 34. * v1 - 7.5 * a0 + 6.5 * a1 - 2. / 3. * j0 - 0.5 * j1,
+		-20. * d + 10. * v0 + 10. * v1 + 2. * a0 - 2. * a1 + j0 / 6. + j1 / 6.,
+	])
+}
+
+/// Scales `coefficients` so that the largest is one in magnitude, which keeps
+/// the signs of its values and so the Sturm count.
+fn normalized(coefficients: Vec<f64>) -> Vec<f64> {
+	let largest = coefficients.iter().fold(0_f64, |m, c| m.max(c.abs()));
+
+	coefficients.into_iter().map(|c| c / largest).collect()
+}
+
+/// Divides `dividend` by `divisor`, whose last coefficient must be nonzero,
+/// giving the quotient and remainder.
+fn divide(dividend: &[f64], divisor: &[f64]) -> (Vec<f64>, Vec<f64>) {
+	let mut r = dividend.to_vec();
+	let degree = divisor.len() - 1;
+	let lead = divisor[degree];
+
+	if r.len() <= degree {
+		return (Vec::new(), r);
+	}
+
+	let mut quotient = vec![0.; r.len() - degree];
+
+	for i in (degree..r.len()).rev() {
+		let q = r[i] / lead;
+		quotient[i - degree] = q;
+
+		for (j, &d) in divisor.iter().enumerate() {
+			r[i - degree + j] -= q * d;
+		}
+	}
+
+	r.truncate(degree);
+	(quotient, r)
+}
+
+/// Builds the Sturm sequence of the polynomial with the given coefficients,
+/// whose last must be nonzero.
+///
+/// The sequence is divided through by its last element, the greatest common
+/// divisor of the polynomial and its derivative, so that it doesn't vanish
+/// all at once at a repeated root.
+fn sturm_sequence(coefficients: &[f64]) -> Vec<Vec<f64>> {
+	let p = normalized(coefficients.to_vec());
+	let dp = normalized(Polynomial(p.clone()).derivative().0);
+
+	let mut sequence = vec![p, dp];
+
+	loop {
+		let n = sequence.len();
+		let mut r: Vec<f64> = divide(&sequence[n - 2], &sequence[n - 1])
+			.1
+			.into_iter()
+			.map(|c| -c)
+			.collect();
+
+		// `sequence[n - 2]` is normalized, so this threshold is relative.
+		while r.last().is_some_and(|c| c.abs() <= REMAINDER_THRESHOLD) {
+			r.pop();
+		}
+
+		if r.is_empty() {
+			break;
+		}
+
+		sequence.push(normalized(r));
+	}
+
+	let gcd = sequence[sequence.len() - 1].clone();
+
+	if gcd.len() == 1 {
+		return sequence;
+	}
+
+	sequence
+		.iter()
+		.map(|s| normalized(divide(s, &gcd).0))
+		.collect()
+}
+
+/// Whether `p` is zero at `x` to within rounding.
+fn vanishes(p: &Polynomial, x: f64) -> bool {
+	let scale = p.0.iter().rev().fold(0., |sum, &c| sum * x.abs() + c.abs());
+
+	p.eval(x).abs() <= ROOT_THRESHOLD * scale
+}
+
+/// Counts the changes of sign along the Sturm sequence at `x`, ignoring
+/// zeros, and the first element if `x` is known to be a root.
+fn sign_changes(sequence: &[Vec<f64>], x: f64, at_root: bool) -> usize {
+	let signs = sequence
+		.iter()
+		.skip(usize::from(at_root))
+		.map(|s| s.iter().rev().fold(0., |sum, &c| sum * x + c))
+		.filter(|&v| v != 0.)
+		.map(f64::is_sign_negative);
+
+	let mut changes = 0;
+	let mut previous = None;
+
+	for sign in signs {
+		if previous.is_some_and(|p| p != sign) {
+			changes += 1;
+		}
+		previous = Some(sign);
+	}
+
+	changes
+}
+
+/// Bisects `(lo, hi]`, given the Sturm counts at either end, until each root
+/// in it is pinned down, and pushes them onto `roots` in increasing order.
+fn isolate(
+	changes: &dyn Fn(f64) -> usize,
+	(lo, at_lo): (f64, usize),
+	(hi, at_hi): (f64, usize),
+	depth: usize,
+	roots: &mut Vec<f64>,
+) {
+	// Rounding can make the count rise slightly; there's nothing to find then.
+	if at_lo <= at_hi {
+		return;
+	}
+
+	let mid = lo + (hi - lo) / 2.;
+
+	if depth == BISECTION_DEPTH || mid <= lo || mid >= hi {
+		roots.push(hi);
+		return;
+	}
+
+	let at_mid = changes(mid);
+
+	isolate(changes, (lo, at_lo), (mid, at_mid), depth + 1, roots);
+	isolate(changes, (mid, at_mid), (hi, at_hi), depth + 1, roots);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::*;
+
+	/// Builds the polynomial with the given roots.
+	fn from_roots(roots: &[f64]) -> Polynomial {
+		roots
+			.iter()
+			.fold(Polynomial(vec![1.]), |p, &r| p * Polynomial(vec![-r, 1.]))
+	}
+
+	#[test]
+	fn quintic_segment_matches_hermite() {
+		use super::super::eval3;
+		use super::super::hermite::quintic::h_5;
+
+		let prec = Pose3 {
+			position: 1.,
+			velocity: -2.,
+			acceleration: 3.,
+		};
+		let succ = Pose3 {
+			position: 4.,
+			velocity: 0.5,
+			acceleration: -1.,
+		};
+		let p = quintic_segment(&prec, &succ, 1.5);
+
+		for i in 0..=10 {
+			let u = f64::from(i) / 10.;
+
+			assert_f64_roughly_eq!(p.eval(u), eval3(u, &prec, &succ, 1.5, h_5, 0), 1e-12);
+		}
+	}
+
+	#[test]
+	fn septic_segment_matches_hermite() {
+		use super::super::eval4;
+		use super::super::hermite::septic::h_7;
+
+		let prec = Pose4 {
+			position: 1.,
+			velocity: -2.,
+			acceleration: 3.,
+			jerk: 0.5,
+		};
+		let succ = Pose4 {
+			position: 4.,
+			velocity: 0.5,
+			acceleration: -1.,
+			jerk: -2.,
+		};
+		let p = septic_segment(&prec, &succ, 1.5);
+
+		for i in 0..=10 {
+			let u = f64::from(i) / 10.;
+
+			assert_f64_roughly_eq!(p.eval(u), eval4(u, &prec, &succ, 1.5, h_7, 0), 1e-12);
+		}
+	}
+
+	#[test]
+	fn arithmetic() {
+		let a = Polynomial(vec![1., 2.]);
+		let b = Polynomial(vec![0., 1., 3.]);
+
+		assert_eq!(a.clone() + b.clone(), Polynomial(vec![1., 3., 3.]));
+		assert_eq!(a.clone() * b, Polynomial(vec![0., 1., 5., 6.]));
+		assert_eq!(a * 2., Polynomial(vec![2., 4.]));
+		assert_eq!(Polynomial(vec![1., 0., 0.]).degree(), Some(0));
+		assert_eq!(Polynomial(vec![0.]).degree(), None);
+	}
+
+	#[test]
+	fn finds_simple_roots() {
+		let expected = [-0.9, -0.2, 0.1, 0.35, 0.6, 0.61, 0.95];
+		let roots = from_roots(&expected).roots(-1., 1.);
+
+		assert_eq!(roots.len(), expected.len());
+		for (root, expected) in roots.iter().zip(&expected) {
+			assert_f64_roughly_eq!(*root, *expected, 1e-9);
+		}
+	}
+
+	#[test]
+	fn finds_repeated_roots_once() {
+		let roots = from_roots(&[0.25, 0.25, 0.5, 0.75, 0.75, 0.75]).roots(0., 1.);
+
+		assert_eq!(roots.len(), 3);
+		assert_f64_roughly_eq!(roots[0], 0.25, 1e-9);
+		assert_f64_roughly_eq!(roots[1], 0.5, 1e-9);
+		assert_f64_roughly_eq!(roots[2], 0.75, 1e-9);
+	}
+
+	#[test]
+	fn interval_ends_are_inclusive() {
+		let p = from_roots(&[0., 0.5, 1.]);
+
+		let roots = p.roots(0., 1.);
+
+		assert_eq!(roots.len(), 3);
+		assert_eq!(roots[0], 0.);
+		assert_f64_roughly_eq!(roots[1], 0.5, 1e-12);
+		assert_eq!(roots[2], 1.);
+		assert_eq!(p.roots(0.6, 0.9), Vec::<f64>::new());
+	}
+
+	#[test]
+	fn no_real_roots() {
+		// x² + 1
+		assert!(Polynomial(vec![1., 0., 1.]).roots(-10., 10.).is_empty());
+		assert!(Polynomial(vec![3.]).roots(-10., 10.).is_empty());
+		assert!(Polynomial(vec![]).roots(-10., 10.).is_empty());
+	}
+}
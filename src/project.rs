@@ -0,0 +1,288 @@
+//! Finding the point on a trajectory nearest to a given point.
+//!
+//! The nearest point on a segment is either one of its ends or a place where
+//! the direction of travel is perpendicular to the offset from the query, so
+//! where `(p(u) - q) · p'(u)` is zero.  That is itself a polynomial, so all
+//! its roots on the segment are found by Sturm sequences, polished by a few
+//! steps of Newton's method, and compared with the ends.
+
+use super::extrema::{pieces, Derivative, Piece};
+use super::polynomial::Polynomial;
+use super::timed::Timed;
+use super::vec::Vec3d;
+use super::Pose3;
+
+/// How many Newton steps polish each root.
+const NEWTON_ITERATIONS: usize = 4;
+
+/// The point on a trajectory nearest to a query point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+	pub time: f64,
+	pub position: Vec3d<f64>,
+	pub distance: f64,
+}
+
+/// Finds the point on `waypoints` nearest to `query`.
+///
+/// Returns `None` if there are fewer than two waypoints.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::project::closest_point;
+/// use motion_planning::timed::timed;
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose3;
+///
+/// let cruise = |x| Pose3 {
+///     position: Vec3d(x, 0., 0.),
+///     velocity: Vec3d(1., 0., 0.),
+///     acceleration: Vec3d(0., 0., 0.),
+/// };
+/// let path = timed(&[cruise(0.), cruise(1.), cruise(2.)]);
+///
+/// let nearest = closest_point(&path, Vec3d(1.25, 3., 4.)).unwrap();
+///
+/// assert!((nearest.time - 1.25).abs() < 1e-9);
+/// assert!((nearest.distance - 5.).abs() < 1e-9);
+/// ```
+pub fn closest_point(
+	waypoints: &[Timed<Pose3<Vec3d<f64>>>],
+	query: Vec3d<f64>,
+) -> Option<Projection> {
+	let first = waypoints.first()?.time;
+	let last = waypoints.last()?.time;
+
+	search(waypoints, query, first, last)
+}
+
+/// Finds the point on `waypoints` nearest to `query`, looking only within
+/// `window` of the time `hint`.
+///
+/// When tracking a moving point, passing the previous answer as `hint` keeps
+/// each search short, and stops the answer jumping to a distant part of the
+/// trajectory that happens to pass close by.
+///
+/// Returns `None` if there are fewer than two waypoints or the window misses
+/// the trajectory entirely.
+pub fn closest_point_near(
+	waypoints: &[Timed<Pose3<Vec3d<f64>>>],
+	query: Vec3d<f64>,
+	hint: f64,
+	window: f64,
+) -> Option<Projection> {
+	search(waypoints, query, hint - window, hint + window)
+}
+
+/// Finds the nearest point to `query` between the times `from` and `to`.
+fn search(
+	waypoints: &[Timed<Pose3<Vec3d<f64>>>],
+	query: Vec3d<f64>,
+	from: f64,
+	to: f64,
+) -> Option<Projection> {
+	pieces(waypoints, Derivative::Position)
+		.iter()
+		.filter(|piece| piece.end > piece.start && piece.start <= to && from <= piece.end)
+		.filter_map(|piece| {
+			let duration = piece.end - piece.start;
+			let a = ((from - piece.start) / duration).max(0.);
+			let b = ((to - piece.start) / duration).min(1.);
+
+			nearest_on_piece(piece, query, a, b)
+		})
+		.reduce(|best, p| if p.distance < best.distance { p } else { best })
+}
+
+/// Finds the nearest point to `query` on `piece` between the fractions `a`
+/// and `b` of the way through it.
+fn nearest_on_piece(piece: &Piece, query: Vec3d<f64>, a: f64, b: f64) -> Option<Projection> {
+	if a > b {
+		return None;
+	}
+
+	let q = [query.0, query.1, query.2];
+	let [x, y, z] = &piece.axes;
+
+	// The offset from the query.
+	let offset = [
+		x.clone() + Polynomial(vec![-q[0]]),
+		y.clone() + Polynomial(vec![-q[1]]),
+		z.clone() + Polynomial(vec![-q[2]]),
+	];
+
+	let at = |polynomials: &[Polynomial; 3], u: f64| {
+		Vec3d(
+			polynomials[0].eval(u),
+			polynomials[1].eval(u),
+			polynomials[2].eval(u),
+		)
+	};
+
+	// Half the derivative of the squared distance, and its own derivative.
+	let f = offset[0].clone() * x.derivative()
+		+ offset[1].clone() * y.derivative()
+		+ offset[2].clone() * z.derivative();
+	let df = f.derivative();
+
+	let mut candidates = vec![a, b];
+	candidates.extend(f.roots(a, b).into_iter().map(|u| polish(&f, &df, u, a, b)));
+
+	candidates
+		.into_iter()
+		.map(|u| {
+			let offset = at(&offset, u);
+
+			Projection {
+				time: piece.time(u),
+				position: offset + query,
+				distance: offset.norm(),
+			}
+		})
+		.reduce(|best, p| if p.distance < best.distance { p } else { best })
+}
+
+/// Refines the root `u` of `f` by Newton's method, keeping each step only
+/// while it stays within `[a, b]` and brings `f` closer to zero.
+fn polish(f: &Polynomial, df: &Polynomial, mut u: f64, a: f64, b: f64) -> f64 {
+	for _ in 0..NEWTON_ITERATIONS {
+		let value = f.eval(u);
+		let step = u - value / df.eval(u);
+
+		if !(a..=b).contains(&step) || f.eval(step).abs() >= value.abs() {
+			break;
+		}
+
+		u = step;
+	}
+
+	u
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::timed::timed;
+	use super::super::Trajectory3;
+	use super::*;
+
+	/// Out along a curve and back again along a parallel one.
+	fn out_and_back() -> Vec<Timed<Pose3<Vec3d<f64>>>> {
+		timed(&[
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(1., 0., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 1., 0.),
+				velocity: Vec3d(1., 1., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(4., 0.5, 0.),
+				velocity: Vec3d(0., -1., 0.),
+				acceleration: Vec3d(-1., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 1.2, 0.),
+				velocity: Vec3d(-1., 0., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(0., 0.2, 0.),
+				velocity: Vec3d(-1., 0., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+		])
+	}
+
+	fn brute_force(
+		waypoints: &Vec<Timed<Pose3<Vec3d<f64>>>>,
+		query: Vec3d<f64>,
+		from: f64,
+		to: f64,
+	) -> f64 {
+		(0..=40000)
+			.map(|i| from + (to - from) * f64::from(i) / 40000.)
+			.map(|t| (waypoints.position_at(t).unwrap() + query * -1.).norm())
+			.fold(f64::INFINITY, f64::min)
+	}
+
+	#[test]
+	fn matches_brute_force() {
+		let path = out_and_back();
+
+		for query in [
+			Vec3d(1., 0.5, 0.),
+			Vec3d(3., -1., 2.),
+			Vec3d(5., 0.7, 0.),
+			Vec3d(-1., -1., 0.),
+			Vec3d(2., 1.1, 0.),
+		] {
+			let nearest = closest_point(&path, query).unwrap();
+			let expected = brute_force(&path, query, 0., 4.);
+
+			assert!(nearest.distance <= expected + 1e-12);
+			assert_f64_roughly_eq!(nearest.distance, expected, 1e-6);
+			let on_path = path.position_at(nearest.time).unwrap();
+			assert_f64_roughly_eq!((nearest.position + on_path * -1.).norm(), 0., 1e-12);
+		}
+	}
+
+	#[test]
+	fn minimum_between_close_crossings() {
+		// A sharp hook whose nearest point to the query is a narrow dip in
+		// the distance, easily stepped over by sampling.
+		let path = timed(&[
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(4.49224, -6.39648, 0.),
+				acceleration: Vec3d(-281.2656, -30.83, 0.),
+			},
+			Pose3 {
+				position: Vec3d(0.648458, 0.712855, 0.),
+				velocity: Vec3d(-1.58908, -0.37224, 0.),
+				acceleration: Vec3d(-362.6128, -12.1776, 0.),
+			},
+		]);
+		let query = Vec3d(0.393378, 0.9087, 0.);
+		let nearest = closest_point(&path, query).unwrap();
+		let expected = brute_force(&path, query, 0., 1.);
+
+		assert!(nearest.distance <= expected + 1e-12);
+		assert_f64_roughly_eq!(nearest.distance, expected, 1e-6);
+		assert_f64_roughly_eq!(nearest.distance, 0.1939, 1e-4);
+	}
+
+	#[test]
+	fn perpendicular_at_interior_minimum() {
+		let path = out_and_back();
+		let query = Vec3d(1., -0.5, 0.3);
+		let nearest = closest_point(&path, query).unwrap();
+
+		assert!(nearest.time > 0. && nearest.time < 4.);
+
+		let offset = nearest.position + query * -1.;
+		let velocity = path.velocity_at(nearest.time).unwrap();
+
+		assert_f64_roughly_eq!(offset.dot(&velocity), 0., 1e-12);
+	}
+
+	#[test]
+	fn window_picks_nearby_pass() {
+		let path = out_and_back();
+		let query = Vec3d(2., 1.18, 0.);
+
+		// The return pass is closer, but the hint says we are outbound.
+		assert!(closest_point(&path, query).unwrap().time > 2.);
+
+		let near = closest_point_near(&path, query, 1., 0.5).unwrap();
+		assert!((0.5..=1.5).contains(&near.time));
+		assert_f64_roughly_eq!(near.distance, brute_force(&path, query, 0.5, 1.5), 1e-6);
+
+		assert_eq!(closest_point_near(&path, query, 10., 1.), None);
+		assert_eq!(closest_point(&path[..1], query), None);
+	}
+}
@@ -0,0 +1,56 @@
+//! Gauss–Legendre quadrature, for integrating smooth functions such as speed.
+
+/// Gauss–Legendre nodes on `[-1, 1]`.
+const NODES: [f64; 5] = [
+	-0.906_179_845_938_664,
+	-0.538_469_310_105_683_1,
+	0.,
+	0.538_469_310_105_683_1,
+	0.906_179_845_938_664,
+];
+
+/// Gauss–Legendre weights matching [`NODES`].
+const WEIGHTS: [f64; 5] = [
+	0.236_926_885_056_189_1,
+	0.478_628_670_499_366_5,
+	0.568_888_888_888_888_9,
+	0.478_628_670_499_366_5,
+	0.236_926_885_056_189_1,
+];
+
+/// The points at which to evaluate a function, and the weights to give each
+/// value, to integrate it from `from` to `to` in `panels` equal parts.
+pub(crate) fn points(from: f64, to: f64, panels: usize) -> impl Iterator<Item = (f64, f64)> {
+	let width = (to - from) / panels as f64;
+
+	(0..panels).flat_map(move |i| {
+		let centre = from + width * (i as f64 + 0.5);
+
+		NODES
+			.iter()
+			.zip(&WEIGHTS)
+			.map(move |(node, weight)| (centre + node * width / 2., weight * width / 2.))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::*;
+
+	#[test]
+	fn exact_for_degree_nine() {
+		let integral: f64 = points(-1., 2., 1).map(|(t, w)| w * t.powi(9)).sum();
+
+		assert_f64_roughly_eq!(integral, (2_f64.powi(10) - 1.) / 10., 1e-12);
+
+		let panels: f64 = points(0., 3., 4).map(|(t, w)| w * t.sin()).sum();
+
+		assert_f64_roughly_eq!(panels, 1. - 3_f64.cos(), 1e-12);
+	}
+
+	#[test]
+	fn weights_sum_to_interval() {
+		assert_eq!(WEIGHTS.iter().sum::<f64>(), 2.);
+	}
+}
@@ -0,0 +1,484 @@
+//! Quadrotor thrust and attitude from trajectories, by differential flatness.
+//!
+//! A quadrotor can only push along its own `z` axis, so to follow a path it
+//! must tilt that axis along the acceleration it needs plus gravity.  Given
+//! also which way it should face, its yaw, this fixes its attitude, and the
+//! derivatives of the path fix how fast that attitude turns: body rates
+//! from the jerk, and angular accelerations from the snap.
+//!
+//! Trajectories are of [`FlatOutput`]s, a position and a yaw side by side,
+//! planned as quintic or septic Hermite segments.  Positions are in a fixed
+//! frame with `z` up, and the yaw is anticlockwise from its `x` axis.  The
+//! body frame has `x` forwards, `z` along the thrust, and body rates and
+//! angular accelerations are given in it, as a flight controller expects.
+
+use core::ops::{Add, Mul};
+
+use super::polynomial::{quintic_segment, septic_segment, Polynomial};
+use super::rotation::Quaternion;
+use super::timed::{find_segment, find_untimed_segment, Timed};
+use super::vec::Vec3d;
+use super::{Pose3, Pose4};
+
+/// A position with a yaw, in radians anticlockwise from the `x` axis; or the
+/// rate of change of one.
+///
+/// Yaws are not wrapped, so that a trajectory turning past π keeps turning
+/// the same way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlatOutput {
+	pub position: Vec3d<f64>,
+	pub yaw: f64,
+}
+
+impl Add<FlatOutput> for FlatOutput {
+	type Output = Self;
+
+	fn add(self, other: FlatOutput) -> Self::Output {
+		FlatOutput {
+			position: self.position + other.position,
+			yaw: self.yaw + other.yaw,
+		}
+	}
+}
+
+impl Mul<f64> for FlatOutput {
+	type Output = Self;
+
+	fn mul(self, scalar: f64) -> Self::Output {
+		FlatOutput {
+			position: self.position * scalar,
+			yaw: self.yaw * scalar,
+		}
+	}
+}
+
+/// The position and yaw of a trajectory at some time, with as many of their
+/// derivatives as a quadrotor's inputs depend on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlatState {
+	pub position: Vec3d<f64>,
+	pub velocity: Vec3d<f64>,
+	pub acceleration: Vec3d<f64>,
+	pub jerk: Vec3d<f64>,
+	pub snap: Vec3d<f64>,
+	pub yaw: f64,
+	pub yaw_rate: f64,
+	pub yaw_acceleration: f64,
+}
+
+/// Trajectories of [`FlatOutput`]s.
+///
+/// At a waypoint's time, the segment beginning there is used, since jerk
+/// and snap may jump at a waypoint.
+pub trait FlatTrajectory {
+	fn flat_state_at(&self, t: f64) -> Option<FlatState>;
+}
+
+/// The `i`th of a flat output's `x`, `y`, `z` and yaw.
+fn component(output: &FlatOutput, i: usize) -> f64 {
+	match i {
+		0 => output.position.0,
+		1 => output.position.1,
+		2 => output.position.2,
+		_ => output.yaw,
+	}
+}
+
+/// Evaluates a segment, given as polynomials for `x`, `y`, `z` and yaw in the
+/// fraction `u` of the way through it.
+fn flat_state(polynomials: [Polynomial; 4], u: f64, duration: f64) -> FlatState {
+	let derivatives = polynomials.map(|mut p| {
+		let mut values = [0.; 5];
+
+		for (n, value) in values.iter_mut().enumerate() {
+			*value = p.eval(u) * duration.powi(-(n as i32));
+			p = p.derivative();
+		}
+
+		values
+	});
+	let vector = |n: usize| Vec3d(derivatives[0][n], derivatives[1][n], derivatives[2][n]);
+
+	FlatState {
+		position: vector(0),
+		velocity: vector(1),
+		acceleration: vector(2),
+		jerk: vector(3),
+		snap: vector(4),
+		yaw: derivatives[3][0],
+		yaw_rate: derivatives[3][1],
+		yaw_acceleration: derivatives[3][2],
+	}
+}
+
+fn quintic(prec: &Pose3<FlatOutput>, succ: &Pose3<FlatOutput>, u: f64, duration: f64) -> FlatState {
+	let along = |pose: &Pose3<FlatOutput>, i| Pose3 {
+		position: component(&pose.position, i),
+		velocity: component(&pose.velocity, i),
+		acceleration: component(&pose.acceleration, i),
+	};
+
+	flat_state(
+		[0, 1, 2, 3].map(|i| quintic_segment(&along(prec, i), &along(succ, i), duration)),
+		u,
+		duration,
+	)
+}
+
+fn septic(prec: &Pose4<FlatOutput>, succ: &Pose4<FlatOutput>, u: f64, duration: f64) -> FlatState {
+	let along = |pose: &Pose4<FlatOutput>, i| Pose4 {
+		position: component(&pose.position, i),
+		velocity: component(&pose.velocity, i),
+		acceleration: component(&pose.acceleration, i),
+		jerk: component(&pose.jerk, i),
+	};
+
+	flat_state(
+		[0, 1, 2, 3].map(|i| septic_segment(&along(prec, i), &along(succ, i), duration)),
+		u,
+		duration,
+	)
+}
+
+impl FlatTrajectory for Vec<Pose3<FlatOutput>> {
+	fn flat_state_at(&self, t: f64) -> Option<FlatState> {
+		let (prec, succ, u, duration) = find_untimed_segment(self.len(), t)?;
+
+		Some(quintic(&self[prec], &self[succ], u, duration))
+	}
+}
+
+impl FlatTrajectory for Vec<Timed<Pose3<FlatOutput>>> {
+	fn flat_state_at(&self, t: f64) -> Option<FlatState> {
+		let (prec, succ, u, duration) = find_segment(self, t)?;
+
+		Some(quintic(&self[prec].pose, &self[succ].pose, u, duration))
+	}
+}
+
+impl FlatTrajectory for Vec<Pose4<FlatOutput>> {
+	fn flat_state_at(&self, t: f64) -> Option<FlatState> {
+		let (prec, succ, u, duration) = find_untimed_segment(self.len(), t)?;
+
+		Some(septic(&self[prec], &self[succ], u, duration))
+	}
+}
+
+impl FlatTrajectory for Vec<Timed<Pose4<FlatOutput>>> {
+	fn flat_state_at(&self, t: f64) -> Option<FlatState> {
+		let (prec, succ, u, duration) = find_segment(self, t)?;
+
+		Some(septic(&self[prec].pose, &self[succ].pose, u, duration))
+	}
+}
+
+/// What a quadrotor must do at `time`: push with `thrust` along its `z`
+/// axis while at `attitude`, turning at `body_rates` and accelerating its
+/// turn at `angular_acceleration`, which takes `torque`.
+///
+/// The attitude turns the fixed frame onto the body frame; the rates,
+/// accelerations and torque are all in the body frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuadrotorCommand {
+	pub time: f64,
+	pub thrust: f64,
+	pub attitude: Quaternion,
+	pub body_rates: Vec3d<f64>,
+	pub angular_acceleration: Vec3d<f64>,
+	pub torque: Vec3d<f64>,
+}
+
+/// A quadrotor of `mass`, with principal moments of `inertia` about its
+/// body axes, flying in a field of `gravity` pulling along `-z`.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::quadrotor::{FlatOutput, Quadrotor};
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose3;
+///
+/// let quadrotor = Quadrotor { mass: 1.5, inertia: Vec3d(0.02, 0.02, 0.04), gravity: 9.81 };
+///
+/// // Hover in place, turning to face along y.
+/// let hover = |yaw| Pose3 {
+///     position: FlatOutput { position: Vec3d(0., 0., 2.), yaw },
+///     velocity: FlatOutput { position: Vec3d(0., 0., 0.), yaw: 0. },
+///     acceleration: FlatOutput { position: Vec3d(0., 0., 0.), yaw: 0. },
+/// };
+/// let path = vec![hover(0.), hover(std::f64::consts::FRAC_PI_2)];
+///
+/// let start = quadrotor.command_at(&path, 0.).unwrap();
+/// assert!((start.thrust - 1.5 * 9.81).abs() < 1e-12);
+///
+/// // Halfway through the turn, it is yawing at its fastest, level.
+/// let halfway = quadrotor.command_at(&path, 0.5).unwrap();
+/// assert!((halfway.body_rates.2 - 15. / 16. * std::f64::consts::PI).abs() < 1e-12);
+/// assert!(halfway.body_rates.0.abs() < 1e-12 && halfway.body_rates.1.abs() < 1e-12);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quadrotor {
+	pub mass: f64,
+	pub inertia: Vec3d<f64>,
+	pub gravity: f64,
+}
+
+impl Quadrotor {
+	/// Finds what the quadrotor must do to follow `trajectory` at time `t`.
+	///
+	/// Returns `None` if `t` is outside the trajectory, if the quadrotor
+	/// would be falling freely, when its attitude is undefined, or if its
+	/// thrust axis lies along its yaw direction, when its heading is.
+	pub fn command_at<T: FlatTrajectory>(&self, trajectory: &T, t: f64) -> Option<QuadrotorCommand> {
+		let flat = trajectory.flat_state_at(t)?;
+
+		let push = flat.acceleration + Vec3d(0., 0., self.gravity);
+		let c = push.norm();
+
+		if c == 0. {
+			return None;
+		}
+
+		// The body axes: `z` along the thrust, and `x` as near the yaw
+		// direction as that allows.
+		let (sin, cos) = flat.yaw.sin_cos();
+		let heading = Vec3d(cos, sin, 0.);
+		let sideways = Vec3d(-sin, cos, 0.);
+
+		let z = push * c.recip();
+		let across = z.cross(&heading);
+		let n = across.norm();
+
+		if n == 0. {
+			return None;
+		}
+
+		let y = across * n.recip();
+		let x = y.cross(&z);
+
+		// The rate of change of the thrust axis, and so the roll and pitch
+		// rates; the yaw rate keeps `y` at right angles to the heading.
+		let c_dot = z.dot(&flat.jerk);
+		let z_dot = (flat.jerk + z * -c_dot) * c.recip();
+		let heading_dot = sideways * flat.yaw_rate;
+
+		let p = -z_dot.dot(&y);
+		let q = z_dot.dot(&x);
+		let r = (p * z.dot(&heading) + y.dot(&heading_dot)) / n;
+
+		// The same again, differentiated once more.
+		let c_ddot = z_dot.dot(&flat.jerk) + z.dot(&flat.snap);
+		let z_ddot = (flat.snap + z * -c_ddot + z_dot * (-2. * c_dot)) * c.recip();
+		let heading_ddot = sideways * flat.yaw_acceleration + heading * -flat.yaw_rate.powi(2);
+
+		let x_dot = y * r + z * -q;
+		let y_dot = x * -r + z * p;
+
+		let p_dot = -z_ddot.dot(&y) - z_dot.dot(&y_dot);
+		let q_dot = z_ddot.dot(&x) + z_dot.dot(&x_dot);
+
+		// The part of `y`'s second derivative due to turning at a steady
+		// rate, ω × (ω × y), in the body frame and then the fixed one.
+		let centripetal = Vec3d(p, q, r).cross(&Vec3d(-r, 0., p));
+		let centripetal = x * centripetal.0 + y * centripetal.1 + z * centripetal.2;
+
+		let r_dot = (p_dot * z.dot(&heading)
+			+ centripetal.dot(&heading)
+			+ 2. * y_dot.dot(&heading_dot)
+			+ y.dot(&heading_ddot))
+			/ n;
+
+		let rates = Vec3d(p, q, r);
+		let angular_acceleration = Vec3d(p_dot, q_dot, r_dot);
+		let inertia = |v: Vec3d<f64>| {
+			Vec3d(
+				self.inertia.0 * v.0,
+				self.inertia.1 * v.1,
+				self.inertia.2 * v.2,
+			)
+		};
+
+		Some(QuadrotorCommand {
+			time: t,
+			thrust: self.mass * c,
+			attitude: Quaternion::from_axes(x, y, z),
+			body_rates: rates,
+			angular_acceleration,
+			torque: inertia(angular_acceleration) + rates.cross(&inertia(rates)),
+		})
+	}
+
+	/// Finds what the quadrotor must do to follow `trajectory` at each of
+	/// `times`.
+	///
+	/// Returns `None` if any time is outside the trajectory, or the
+	/// quadrotor's attitude is undefined at any of them.
+	pub fn commands<T: FlatTrajectory>(
+		&self,
+		trajectory: &T,
+		times: &[f64],
+	) -> Option<Vec<QuadrotorCommand>> {
+		times
+			.iter()
+			.map(|&t| self.command_at(trajectory, t))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::timed::timed;
+	use super::*;
+
+	const QUADROTOR: Quadrotor = Quadrotor {
+		mass: 2.,
+		inertia: Vec3d(0.03, 0.04, 0.05),
+		gravity: 9.81,
+	};
+
+	fn flat(x: f64, y: f64, z: f64, yaw: f64) -> FlatOutput {
+		FlatOutput {
+			position: Vec3d(x, y, z),
+			yaw,
+		}
+	}
+
+	fn assert_vec_roughly_eq(a: Vec3d<f64>, b: Vec3d<f64>, tolerance: f64) {
+		assert_f64_roughly_eq!(a.0, b.0, tolerance);
+		assert_f64_roughly_eq!(a.1, b.1, tolerance);
+		assert_f64_roughly_eq!(a.2, b.2, tolerance);
+	}
+
+	/// A swooping, yawing septic trajectory.
+	fn swoop() -> Vec<Timed<Pose4<FlatOutput>>> {
+		timed(&[
+			Pose4 {
+				position: flat(0., 0., 1., 0.),
+				velocity: flat(1., 0., 0., 0.5),
+				acceleration: flat(0., 2., 1., 0.),
+				jerk: flat(1., 0., -1., 0.),
+			},
+			Pose4 {
+				position: flat(2., 1., 2., 1.),
+				velocity: flat(1., 1., 0.5, 1.),
+				acceleration: flat(-2., 0., 0., -1.),
+				jerk: flat(0., 1., 0., 0.),
+			},
+			Pose4 {
+				position: flat(3., 3., 1.5, 3.),
+				velocity: flat(0., 1., -1., 0.),
+				acceleration: flat(0., 0., 0., 0.),
+				jerk: flat(0., 0., 0., 0.),
+			},
+		])
+	}
+
+	#[test]
+	fn hovering_and_tilting() {
+		let still = |x, yaw| Pose3 {
+			position: flat(x, 0., 1., yaw),
+			velocity: flat(0., 0., 0., 0.),
+			acceleration: flat(0., 0., 0., 0.),
+		};
+		let hover = vec![still(0., 0.4), still(0., 0.4)];
+		let command = QUADROTOR.command_at(&hover, 0.5).unwrap();
+
+		assert_f64_roughly_eq!(command.thrust, 2. * 9.81, 1e-12);
+		assert_f64_roughly_eq!(
+			command
+				.attitude
+				.dot(&Quaternion::from_axis_angle(Vec3d(0., 0., 1.), 0.4).unwrap())
+				.abs(),
+			1.,
+			1e-12
+		);
+		assert_vec_roughly_eq(command.body_rates, Vec3d(0., 0., 0.), 1e-12);
+		assert_vec_roughly_eq(command.torque, Vec3d(0., 0., 0.), 1e-12);
+
+		// Accelerating steadily along x tilts the thrust forwards.
+		let accelerating = |x| Pose3 {
+			position: flat(x, 0., 1., 0.),
+			velocity: flat(2. * x, 0., 0., 0.),
+			acceleration: flat(2., 0., 0., 0.),
+		};
+		let path = vec![accelerating(0.), accelerating(1.)];
+		let command = QUADROTOR.command_at(&path, 1.).unwrap();
+
+		let thrust_axis = command.attitude.rotate(Vec3d(0., 0., 1.));
+		assert_f64_roughly_eq!(command.thrust, 2. * 2_f64.hypot(9.81), 1e-12);
+		assert_f64_roughly_eq!(thrust_axis.0.atan2(thrust_axis.2), 2_f64.atan2(9.81), 1e-12);
+		assert_f64_roughly_eq!(command.attitude.rotate(Vec3d(0., 1., 0.)).1, 1., 1e-12);
+	}
+
+	#[test]
+	fn rates_match_differences() {
+		let path = swoop();
+		let dt = 1e-5;
+
+		for t in [0.2, 0.7, 1.3, 1.9] {
+			let command = QUADROTOR.command_at(&path, t).unwrap();
+			let before = QUADROTOR.command_at(&path, t - dt).unwrap();
+			let after = QUADROTOR.command_at(&path, t + dt).unwrap();
+
+			// The turn from one attitude to the next, in the body frame.
+			let turn = (before.attitude.conjugate() * after.attitude).rotation_vector();
+			assert_vec_roughly_eq(command.body_rates, turn * (2. * dt).recip(), 1e-6);
+
+			let change = after.body_rates + before.body_rates * -1.;
+			assert_vec_roughly_eq(
+				command.angular_acceleration,
+				change * (2. * dt).recip(),
+				1e-5,
+			);
+
+			let thrust = (after.thrust - before.thrust) / (2. * dt);
+			let flat = path.flat_state_at(t).unwrap();
+			let z = command.attitude.rotate(Vec3d(0., 0., 1.));
+			assert_f64_roughly_eq!(thrust, QUADROTOR.mass * z.dot(&flat.jerk), 1e-5);
+		}
+	}
+
+	#[test]
+	fn quintic_and_septic_agree_on_waypoints() {
+		let septic = swoop();
+		let quintic: Vec<Timed<Pose3<FlatOutput>>> = septic
+			.iter()
+			.map(|waypoint| Timed {
+				time: waypoint.time,
+				pose: Pose3 {
+					position: waypoint.pose.position,
+					velocity: waypoint.pose.velocity,
+					acceleration: waypoint.pose.acceleration,
+				},
+			})
+			.collect();
+
+		for t in [0., 1., 2.] {
+			let a = QUADROTOR.command_at(&septic, t).unwrap();
+			let b = QUADROTOR.command_at(&quintic, t).unwrap();
+
+			assert_f64_roughly_eq!(a.thrust, b.thrust, 1e-12);
+			assert_f64_roughly_eq!(a.attitude.dot(&b.attitude).abs(), 1., 1e-12);
+		}
+
+		assert_eq!(QUADROTOR.command_at(&septic, 2.5), None);
+		assert_eq!(QUADROTOR.commands(&quintic, &[0., 3.]), None);
+	}
+
+	#[test]
+	fn free_fall_is_undefined() {
+		let falling = |z| Pose3 {
+			position: flat(0., 0., z, 0.),
+			velocity: flat(0., 0., 0., 0.),
+			acceleration: flat(0., 0., -9.81, 0.),
+		};
+
+		assert_eq!(
+			QUADROTOR.command_at(&vec![falling(0.), falling(0.)], 0.),
+			None
+		);
+	}
+}
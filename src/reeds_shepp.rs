@@ -0,0 +1,697 @@
+//! Reeds–Shepp paths: the shortest way between two poses for a vehicle that
+//! turns no tighter than a minimum radius, but can reverse.
+//!
+//! Reeds and Shepp showed that such a shortest path has at most five
+//! pieces, each a turn at the minimum radius or a straight line, driven
+//! forwards or backwards, in one of 48 families.  Those families come from
+//! nine closed-form solutions by the symmetries of driving the path in
+//! reverse, mirroring it, and following it from the far end, and all of
+//! them are tried.
+//!
+//! Each [`Segment`] of a [`ReedsSheppPath`] says which [`Gear`] it is driven
+//! in, so that the sign of the velocity can be applied when timing it, and
+//! the [`Cusp`]s where the gear changes, and the vehicle must stop, are
+//! found along with it.
+
+use core::f64::consts::{FRAC_PI_2, PI};
+
+use super::dubins::{advance, distances, Steer};
+use super::planar::{wrap, Se2};
+
+/// Pieces shorter than this, in turning radii, are rounding error and are
+/// left out, so that they can't make spurious cusps.
+const NEGLIGIBLE: f64 = 1e-9;
+
+/// Which way a segment is driven.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gear {
+	Forward,
+	Reverse,
+}
+
+impl Gear {
+	/// The sign of the velocity along the heading in this gear.
+	pub fn sign(self) -> f64 {
+		match self {
+			Gear::Forward => 1.,
+			Gear::Reverse => -1.,
+		}
+	}
+}
+
+/// One piece of a Reeds–Shepp path: `length` of a turn or straight, driven
+/// in `gear`.
+///
+/// Steering left in reverse turns the vehicle clockwise, as it would a car.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+	pub steer: Steer,
+	pub gear: Gear,
+	pub length: f64,
+}
+
+/// A point where a path changes gear, `distance` along it, at `pose`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cusp {
+	pub distance: f64,
+	pub pose: Se2,
+}
+
+/// A path from `start` of `segments`, turning at `radius`.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::planar::Se2;
+/// use motion_planning::reeds_shepp::{shortest, Gear};
+///
+/// // Straight back 3 m.
+/// let start = Se2 { x: 0., y: 0., heading: 0. };
+/// let path = shortest(start, Se2 { x: -3., y: 0., heading: 0. }, 1.).unwrap();
+///
+/// assert_eq!(path.segments.len(), 1);
+/// assert_eq!(path.segments[0].gear, Gear::Reverse);
+/// assert!((path.length() - 3.).abs() < 1e-12);
+///
+/// // Shuffling sideways takes some back and forth.
+/// let path = shortest(start, Se2 { x: 0., y: 1., heading: 0. }, 1.).unwrap();
+/// let cusps = path.cusps();
+///
+/// assert!(!cusps.is_empty());
+/// assert!(cusps.iter().all(|cusp| path.gear_at(cusp.distance).is_some()));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReedsSheppPath {
+	pub start: Se2,
+	pub radius: f64,
+	pub segments: Vec<Segment>,
+}
+
+/// The distance and direction of `(x, y)` from the origin.
+fn polar(x: f64, y: f64) -> (f64, f64) {
+	(x.hypot(y), y.atan2(x))
+}
+
+/// Finds the first and last turns of the four-turn families, given the
+/// middle turns `u` and `v`.
+fn tau_omega(u: f64, v: f64, xi: f64, eta: f64, phi: f64) -> (f64, f64) {
+	let delta = wrap(u - v);
+	let a = u.sin() - delta.sin();
+	let b = u.cos() - delta.cos() - 1.;
+	let t1 = (eta * a - xi * b).atan2(xi * a + eta * b);
+	let t2 = 2. * (delta.cos() - v.cos() - u.cos()) + 3.;
+	let tau = if t2 < 0. { wrap(t1 + PI) } else { wrap(t1) };
+
+	(tau, wrap(tau - u + v - phi))
+}
+
+// The nine solutions, each from the origin facing along `x` to `(x, y)`
+// facing `phi`, on a unit radius.  They are named for their pieces, with
+// `p` for forwards and `m` for backwards, and give the lengths of the
+// pieces that vary, with turns of at most half a turn either way.
+
+/// Left forwards, straight forwards, left forwards.
+fn lp_sp_lp(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (u, t) = polar(x - phi.sin(), y - 1. + phi.cos());
+	let v = wrap(phi - t);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && v >= -NEGLIGIBLE)
+}
+
+/// Left forwards, straight forwards, right forwards.
+fn lp_sp_rp(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (u1, t1) = polar(x + phi.sin(), y - 1. - phi.cos());
+	let u1 = u1 * u1;
+
+	if u1 < 4. {
+		return None;
+	}
+
+	let u = (u1 - 4.).sqrt();
+	let t = wrap(t1 + 2_f64.atan2(u));
+	let v = wrap(t - phi);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && v >= -NEGLIGIBLE)
+}
+
+/// Left forwards, right backwards, left.
+fn lp_rm_l(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (u1, theta) = polar(x - phi.sin(), y - 1. + phi.cos());
+
+	if u1 > 4. {
+		return None;
+	}
+
+	let u = -2. * (u1 / 4.).asin();
+	let t = wrap(theta + u / 2. + PI);
+	let v = wrap(phi - t + u);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && u <= NEGLIGIBLE)
+}
+
+/// Left forwards, right forwards, left backwards and right backwards, the
+/// middle two of the same length.
+fn lp_rup_lum_rm(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (xi, eta) = (x + phi.sin(), y - 1. - phi.cos());
+	let rho = (2. + xi.hypot(eta)) / 4.;
+
+	if rho > 1. {
+		return None;
+	}
+
+	let u = rho.acos();
+	let (t, v) = tau_omega(u, -u, xi, eta, phi);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && v <= NEGLIGIBLE)
+}
+
+/// Left forwards, right backwards, left backwards and right forwards, the
+/// middle two of the same length.
+fn lp_rum_lum_rp(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (xi, eta) = (x + phi.sin(), y - 1. - phi.cos());
+	let rho = (20. - xi * xi - eta * eta) / 16.;
+
+	if !(0. ..=1.).contains(&rho) {
+		return None;
+	}
+
+	let u = -rho.acos();
+
+	if u < -FRAC_PI_2 {
+		return None;
+	}
+
+	let (t, v) = tau_omega(u, u, xi, eta, phi);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && v >= -NEGLIGIBLE)
+}
+
+/// Left forwards, a quarter turn right backwards, straight backwards, left
+/// backwards.
+fn lp_rm_sm_lm(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (rho, theta) = polar(x - phi.sin(), y - 1. + phi.cos());
+
+	if rho < 2. {
+		return None;
+	}
+
+	let r = (rho * rho - 4.).sqrt();
+	let u = 2. - r;
+	let t = wrap(theta + r.atan2(-2.));
+	let v = wrap(phi - FRAC_PI_2 - t);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && u <= NEGLIGIBLE && v <= NEGLIGIBLE)
+}
+
+/// Left forwards, a quarter turn right backwards, straight backwards, right
+/// backwards.
+fn lp_rm_sm_rm(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (xi, eta) = (x + phi.sin(), y - 1. - phi.cos());
+	let (rho, theta) = polar(-eta, xi);
+
+	if rho < 2. {
+		return None;
+	}
+
+	let t = theta;
+	let u = 2. - rho;
+	let v = wrap(t + FRAC_PI_2 - phi);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && u <= NEGLIGIBLE && v <= NEGLIGIBLE)
+}
+
+/// Left forwards, a quarter turn right backwards, straight backwards, a
+/// quarter turn left backwards, right forwards.
+fn lp_rm_s_lm_rp(x: f64, y: f64, phi: f64) -> Option<[f64; 3]> {
+	let (xi, eta) = (x + phi.sin(), y - 1. - phi.cos());
+	let (rho, _) = polar(xi, eta);
+
+	if rho < 2. {
+		return None;
+	}
+
+	let u = 4. - (rho * rho - 4.).sqrt();
+
+	if u > NEGLIGIBLE {
+		return None;
+	}
+
+	let t = wrap(((4. - u) * xi - 2. * eta).atan2(-2. * xi + (u - 4.) * eta));
+	let v = wrap(t - phi);
+
+	Some([t, u, v]).filter(|_| t >= -NEGLIGIBLE && v >= -NEGLIGIBLE)
+}
+
+/// A candidate path, as the steering of each piece and its length in radii,
+/// negative backwards.
+type Pieces = Vec<(Steer, f64)>;
+
+fn reflected(steer: Steer) -> Steer {
+	match steer {
+		Steer::Left => Steer::Right,
+		Steer::Straight => Steer::Straight,
+		Steer::Right => Steer::Left,
+	}
+}
+
+/// Tries `solution` for reaching `(x, y)` facing `phi`, and its variations
+/// driven in reverse, mirrored, and both, adding each that exists to
+/// `found` as laid out by `pieces`.
+///
+/// If `backwards`, the solution is tried from the far end, with the pieces
+/// in the opposite order.
+fn family(
+	(x, y, phi): (f64, f64, f64),
+	backwards: bool,
+	solution: fn(f64, f64, f64) -> Option<[f64; 3]>,
+	pieces: fn([f64; 3]) -> Pieces,
+	found: &mut Vec<Pieces>,
+) {
+	let (x, y) = if backwards {
+		(x * phi.cos() + y * phi.sin(), x * phi.sin() - y * phi.cos())
+	} else {
+		(x, y)
+	};
+
+	for &(flip, reflect) in &[(false, false), (true, false), (false, true), (true, true)] {
+		let sx = if flip { -1. } else { 1. };
+		let sy = if reflect { -1. } else { 1. };
+
+		if let Some(lengths) = solution(sx * x, sy * y, sx * sy * phi) {
+			let mut candidate: Pieces = pieces(lengths)
+				.into_iter()
+				.map(|(steer, length)| (if reflect { reflected(steer) } else { steer }, sx * length))
+				.collect();
+
+			if backwards {
+				candidate.reverse();
+			}
+
+			found.push(candidate);
+		}
+	}
+}
+
+/// Every candidate path from the origin facing along `x` to `(x, y)` facing
+/// `phi`, on a unit radius.
+fn candidates(x: f64, y: f64, phi: f64) -> Vec<Pieces> {
+	use Steer::{Left as L, Right as R, Straight as S};
+
+	let goal = (x, y, phi);
+	let mut found = Vec::new();
+
+	// A turn, a straight and a turn.
+	family(
+		goal,
+		false,
+		lp_sp_lp,
+		|[t, u, v]| vec![(L, t), (S, u), (L, v)],
+		&mut found,
+	);
+	family(
+		goal,
+		false,
+		lp_sp_rp,
+		|[t, u, v]| vec![(L, t), (S, u), (R, v)],
+		&mut found,
+	);
+
+	// Three turns.
+	for &backwards in &[false, true] {
+		family(
+			goal,
+			backwards,
+			lp_rm_l,
+			|[t, u, v]| vec![(L, t), (R, u), (L, v)],
+			&mut found,
+		);
+	}
+
+	// Four turns.
+	family(
+		goal,
+		false,
+		lp_rup_lum_rm,
+		|[t, u, v]| vec![(L, t), (R, u), (L, -u), (R, v)],
+		&mut found,
+	);
+	family(
+		goal,
+		false,
+		lp_rum_lum_rp,
+		|[t, u, v]| vec![(L, t), (R, u), (L, u), (R, v)],
+		&mut found,
+	);
+
+	// Two turns, a straight and a turn, or the other way round.
+	for &backwards in &[false, true] {
+		family(
+			goal,
+			backwards,
+			lp_rm_sm_lm,
+			|[t, u, v]| vec![(L, t), (R, -FRAC_PI_2), (S, u), (L, v)],
+			&mut found,
+		);
+		family(
+			goal,
+			backwards,
+			lp_rm_sm_rm,
+			|[t, u, v]| vec![(L, t), (R, -FRAC_PI_2), (S, u), (R, v)],
+			&mut found,
+		);
+	}
+
+	// Two turns, a straight and two turns.
+	family(
+		goal,
+		false,
+		lp_rm_s_lm_rp,
+		|[t, u, v]| vec![(L, t), (R, -FRAC_PI_2), (S, u), (L, -FRAC_PI_2), (R, v)],
+		&mut found,
+	);
+
+	found
+}
+
+/// Finds every candidate path from `start` to `end` turning at `radius`, one
+/// for each of the 48 families that can join them.  The shortest path is
+/// among them, but others may be wanted if it is blocked.
+///
+/// Returns an empty list if `radius` is not positive and finite.
+pub fn paths(start: Se2, end: Se2, radius: f64) -> Vec<ReedsSheppPath> {
+	if !(radius > 0. && radius.is_finite()) {
+		return Vec::new();
+	}
+
+	// Work in units of the radius, in the frame of `start`.
+	let (sin, cos) = start.heading.sin_cos();
+	let (dx, dy) = ((end.x - start.x) / radius, (end.y - start.y) / radius);
+
+	candidates(
+		cos * dx + sin * dy,
+		cos * dy - sin * dx,
+		end.heading - start.heading,
+	)
+	.into_iter()
+	.map(|pieces| ReedsSheppPath {
+		start,
+		radius,
+		segments: pieces
+			.into_iter()
+			.filter(|(_, length)| length.abs() > NEGLIGIBLE)
+			.map(|(steer, length)| Segment {
+				steer,
+				gear: if length < 0. {
+					Gear::Reverse
+				} else {
+					Gear::Forward
+				},
+				length: length.abs() * radius,
+			})
+			.collect(),
+	})
+	.collect()
+}
+
+/// Finds the shortest path from `start` to `end` for a vehicle turning no
+/// tighter than `radius`, forwards or backwards.
+///
+/// Returns `None` if `radius` is not positive and finite.
+pub fn shortest(start: Se2, end: Se2, radius: f64) -> Option<ReedsSheppPath> {
+	paths(start, end, radius)
+		.into_iter()
+		.fold(None, |best: Option<ReedsSheppPath>, candidate| match best {
+			Some(best) if best.length() <= candidate.length() => Some(best),
+			_ => Some(candidate),
+		})
+}
+
+impl ReedsSheppPath {
+	pub fn length(&self) -> f64 {
+		self.segments.iter().map(|segment| segment.length).sum()
+	}
+
+	/// The index of the segment `s` along the path, that beginning there at
+	/// a join, and how far into it `s` is.
+	fn locate(&self, s: f64) -> Option<(usize, f64)> {
+		if !(0. ..=self.length()).contains(&s) || self.segments.is_empty() {
+			return None;
+		}
+
+		let mut start = 0.;
+
+		for (i, segment) in self.segments.iter().enumerate() {
+			if s < start + segment.length || i == self.segments.len() - 1 {
+				return Some((i, s - start));
+			}
+
+			start += segment.length;
+		}
+
+		unreachable!()
+	}
+
+	/// The pose `s` along the path, with its heading unwrapped.
+	fn follow(&self, s: f64) -> Se2 {
+		let mut pose = self.start;
+		let mut remaining = s;
+
+		for segment in &self.segments {
+			let along = remaining.min(segment.length);
+			let curvature = segment.steer.curvature() / self.radius;
+
+			pose = advance(pose, curvature, along * segment.gear.sign());
+			remaining -= along;
+
+			if remaining <= 0. {
+				break;
+			}
+		}
+
+		pose
+	}
+
+	/// The pose `s` along the path, with its heading in `(-π, π]`, or `None`
+	/// if `s` is not on the path.
+	pub fn pose_at(&self, s: f64) -> Option<Se2> {
+		if !(0. ..=self.length()).contains(&s) {
+			return None;
+		}
+
+		let pose = self.follow(s);
+
+		Some(Se2 {
+			heading: wrap(pose.heading),
+			..pose
+		})
+	}
+
+	/// The gear the path is driven in `s` along it, or `None` if `s` is not on
+	/// the path or it has no length.  At a cusp, this is the gear driven away
+	/// from it.
+	pub fn gear_at(&self, s: f64) -> Option<Gear> {
+		let (i, _) = self.locate(s)?;
+
+		Some(self.segments[i].gear)
+	}
+
+	/// The pose the path ends at.
+	pub fn end(&self) -> Se2 {
+		let pose = self.follow(self.length());
+
+		Se2 {
+			heading: wrap(pose.heading),
+			..pose
+		}
+	}
+
+	/// The points where the path changes gear, in order along it.
+	pub fn cusps(&self) -> Vec<Cusp> {
+		let mut cusps = Vec::new();
+		let mut distance = 0.;
+
+		for pair in self.segments.windows(2) {
+			distance += pair[0].length;
+
+			if pair[0].gear != pair[1].gear {
+				cusps.push(Cusp {
+					distance,
+					pose: self.follow(distance),
+				});
+			}
+		}
+
+		for cusp in &mut cusps {
+			cusp.pose.heading = wrap(cusp.pose.heading);
+		}
+
+		cusps
+	}
+
+	/// Samples the path at points no more than `spacing` apart, including
+	/// both ends and the joins between segments, with the gear each is driven
+	/// away in, or at the end, arrived in.
+	///
+	/// Returns `None` if `spacing` is not positive.
+	pub fn sample(&self, spacing: f64) -> Option<Vec<(Se2, Gear)>> {
+		if spacing <= 0. || spacing.is_nan() {
+			return None;
+		}
+
+		let lengths: Vec<f64> = self.segments.iter().map(|segment| segment.length).collect();
+
+		Some(
+			distances(&lengths, spacing)
+				.into_iter()
+				.filter_map(|s| {
+					let s = s.min(self.length());
+
+					Some((self.pose_at(s)?, self.gear_at(s)?))
+				})
+				.collect(),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use core::f64::consts::TAU;
+
+	use super::super::assert_f64_roughly_eq;
+	use super::super::dubins;
+	use super::*;
+
+	fn pose(x: f64, y: f64, heading: f64) -> Se2 {
+		Se2 { x, y, heading }
+	}
+
+	fn assert_same_pose(a: Se2, b: Se2) {
+		assert_f64_roughly_eq!(a.x, b.x, 1e-8);
+		assert_f64_roughly_eq!(a.y, b.y, 1e-8);
+		assert_f64_roughly_eq!(wrap(a.heading - b.heading), 0., 1e-8);
+	}
+
+	/// Goals all round a start, near and far, facing every way.
+	fn goals() -> Vec<Se2> {
+		let mut goals = Vec::new();
+
+		for i in 0..12 {
+			for &distance in &[0.3, 1.1, 2.5, 6.] {
+				for j in 0..8 {
+					let direction = f64::from(i) * TAU / 12. + 0.1;
+
+					goals.push(pose(
+						1. + distance * direction.cos(),
+						-2. + distance * direction.sin(),
+						f64::from(j) * TAU / 8. - 0.2,
+					));
+				}
+			}
+		}
+
+		goals
+	}
+
+	#[test]
+	fn every_candidate_reaches_the_goal() {
+		let start = pose(1., -2., 0.4);
+		let mut families: Vec<Vec<(Steer, Gear)>> = Vec::new();
+
+		for goal in goals() {
+			let candidates = paths(start, goal, 1.3);
+			assert!(!candidates.is_empty());
+
+			for path in &candidates {
+				assert_same_pose(path.end(), goal);
+				assert!(path.segments.iter().all(|segment| segment.length > 0.));
+				assert!(path.segments.len() <= 5);
+
+				let family: Vec<(Steer, Gear)> = path
+					.segments
+					.iter()
+					.map(|segment| (segment.steer, segment.gear))
+					.collect();
+
+				if !families.contains(&family) {
+					families.push(family);
+				}
+			}
+		}
+
+		// Every family turns up for some goal.
+		assert_eq!(families.len(), 48);
+	}
+
+	#[test]
+	fn no_longer_than_dubins() {
+		let start = pose(1., -2., 0.4);
+
+		for goal in goals() {
+			let reeds_shepp = shortest(start, goal, 1.3).unwrap();
+			let dubins = dubins::shortest(start, goal, 1.3).unwrap();
+
+			assert!(reeds_shepp.length() <= dubins.length() + 1e-9);
+		}
+	}
+
+	#[test]
+	fn straight_back_and_mirror_images() {
+		let path = shortest(pose(0., 0., FRAC_PI_2), pose(0., -2., FRAC_PI_2), 1.).unwrap();
+
+		assert_eq!(
+			path.segments,
+			vec![Segment {
+				steer: Steer::Straight,
+				gear: Gear::Reverse,
+				length: 2.,
+			}]
+		);
+		assert!(path.cusps().is_empty());
+
+		// Mirroring the goal mirrors the path.
+		let left = shortest(pose(0., 0., 0.), pose(1., 2., 1.), 1.).unwrap();
+		let right = shortest(pose(0., 0., 0.), pose(1., -2., -1.), 1.).unwrap();
+
+		assert_f64_roughly_eq!(left.length(), right.length(), 1e-12);
+
+		for (l, r) in left.segments.iter().zip(&right.segments) {
+			assert_eq!(l.steer, reflected(r.steer));
+			assert_eq!(l.gear, r.gear);
+		}
+
+		assert!(shortest(pose(0., 0., 0.), pose(1., 0., 0.), -1.).is_none());
+	}
+
+	#[test]
+	fn cusps_and_gears() {
+		let path = shortest(pose(0., 0., 0.), pose(0., 1., 0.), 1.).unwrap();
+		let cusps = path.cusps();
+
+		assert!(!cusps.is_empty());
+
+		for cusp in &cusps {
+			assert_same_pose(cusp.pose, path.pose_at(cusp.distance).unwrap());
+
+			// The gear changes across every cusp.
+			let before = path.gear_at(cusp.distance - 1e-6).unwrap();
+			let after = path.gear_at(cusp.distance).unwrap();
+			assert_ne!(before, after);
+		}
+
+		let samples = path.sample(0.1).unwrap();
+		assert_same_pose(samples.last().unwrap().0, path.end());
+
+		for cusp in &cusps {
+			assert!(samples
+				.iter()
+				.any(|(pose, _)| (pose.x - cusp.pose.x).hypot(pose.y - cusp.pose.y) < 1e-12));
+		}
+
+		assert_eq!(path.gear_at(path.length() + 1.), None);
+		assert_eq!(path.sample(-1.), None);
+	}
+}
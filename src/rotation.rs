@@ -0,0 +1,864 @@
+//! Orientations, and trajectories that turn smoothly between them.
+//!
+//! Orientations are unit [`Quaternion`]s.  Between two orientations,
+//! [`slerp`] turns at a constant rate about a fixed axis, and [`squad`]
+//! strings several such turns together without a sudden change of rate at
+//! each keyframe.
+//!
+//! For planning, an [`Orientation`] waypoint also gives the angular velocity
+//! there, and a sequence of them is followed by a cubic Hermite spline in the
+//! rotation vector relative to the start of each segment.  Angular velocity
+//! is then continuous through every waypoint, and both it and angular
+//! acceleration are computed exactly.  All angular velocities are in the
+//! fixed frame, in radians per unit time.
+//!
+//! A [`RigidPose`] pairs an orientation with a translational [`Pose3`], so
+//! that a trajectory of them gives both.
+
+use core::ops::Mul;
+
+use super::hermite::cubic::{h_3, h_3p, h_3pp};
+use super::hermite::quintic::{h_5, h_5p, h_5pp};
+use super::timed::{find_segment, find_untimed_segment, locate, Timed};
+use super::vec::Vec3d;
+use super::{eval3, Pose3, Segment3, Trajectory3};
+
+/// Below this angle, in radians, functions of the angle are evaluated from
+/// their Taylor series instead of directly, which would lose precision.
+const SMALL_ANGLE: f64 = 1e-2;
+
+/// A quaternion `w + xi + yj + zk`.  Those of unit length represent
+/// orientations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quaternion {
+	pub w: f64,
+	pub x: f64,
+	pub y: f64,
+	pub z: f64,
+}
+
+impl Quaternion {
+	/// The orientation that leaves everything as it is.
+	pub fn identity() -> Self {
+		Quaternion {
+			w: 1.,
+			x: 0.,
+			y: 0.,
+			z: 0.,
+		}
+	}
+
+	/// Builds a quaternion from its scalar and vector parts.
+	fn from_parts(w: f64, v: Vec3d<f64>) -> Self {
+		Quaternion {
+			w,
+			x: v.0,
+			y: v.1,
+			z: v.2,
+		}
+	}
+
+	/// The vector part.
+	fn vector(&self) -> Vec3d<f64> {
+		Vec3d(self.x, self.y, self.z)
+	}
+
+	/// The orientation reached by turning `angle` radians about `axis`,
+	/// anticlockwise when looking back along the axis.
+	///
+	/// Returns `None` if `axis` has no length.
+	pub fn from_axis_angle(axis: Vec3d<f64>, angle: f64) -> Option<Self> {
+		let length = axis.norm();
+
+		if length == 0. || !length.is_finite() {
+			return None;
+		}
+
+		Some(Quaternion::from_rotation_vector(axis * (angle / length)))
+	}
+
+	/// The orientation that turns the fixed frame's axes onto `x`, `y` and
+	/// `z`, which must be of unit length, at right angles and right-handed.
+	///
+	/// These are the columns of the rotation matrix.
+	pub fn from_axes(x: Vec3d<f64>, y: Vec3d<f64>, z: Vec3d<f64>) -> Self {
+		let trace = x.0 + y.1 + z.2;
+
+		// Divide by whichever component is largest, which keeps precision.
+		if trace > 0. {
+			let s = (trace + 1.).sqrt() * 2.;
+
+			Quaternion {
+				w: s / 4.,
+				x: (y.2 - z.1) / s,
+				y: (z.0 - x.2) / s,
+				z: (x.1 - y.0) / s,
+			}
+		} else if x.0 >= y.1 && x.0 >= z.2 {
+			let s = (1. + x.0 - y.1 - z.2).sqrt() * 2.;
+
+			Quaternion {
+				w: (y.2 - z.1) / s,
+				x: s / 4.,
+				y: (y.0 + x.1) / s,
+				z: (z.0 + x.2) / s,
+			}
+		} else if y.1 >= z.2 {
+			let s = (1. + y.1 - x.0 - z.2).sqrt() * 2.;
+
+			Quaternion {
+				w: (z.0 - x.2) / s,
+				x: (y.0 + x.1) / s,
+				y: s / 4.,
+				z: (z.1 + y.2) / s,
+			}
+		} else {
+			let s = (1. + z.2 - x.0 - y.1).sqrt() * 2.;
+
+			Quaternion {
+				w: (x.1 - y.0) / s,
+				x: (z.0 + x.2) / s,
+				y: (z.1 + y.2) / s,
+				z: s / 4.,
+			}
+		}
+	}
+
+	/// The orientation reached by turning about `r` by its length in radians.
+	///
+	/// This is the exponential map from rotation vectors to orientations.
+	pub fn from_rotation_vector(r: Vec3d<f64>) -> Self {
+		let angle = r.norm();
+		let half = angle / 2.;
+
+		// sin(angle / 2) / angle
+		let k = if angle < SMALL_ANGLE {
+			0.5 - angle * angle / 48.
+		} else {
+			half.sin() / angle
+		};
+
+		Quaternion::from_parts(half.cos(), r * k)
+	}
+
+	/// The shortest turn that reaches this orientation, as an axis scaled by
+	/// the angle in radians, which is at most π.
+	///
+	/// This is the logarithm map, the inverse of
+	/// [`from_rotation_vector`](Quaternion::from_rotation_vector).
+	pub fn rotation_vector(&self) -> Vec3d<f64> {
+		// `q` and `-q` are the same orientation; take the one turning least.
+		let q = if self.w < 0. { -*self } else { *self };
+		let v = q.vector();
+		let s = v.norm();
+
+		if s == 0. {
+			return v;
+		}
+
+		v * (2. * s.atan2(q.w) / s)
+	}
+
+	pub fn conjugate(&self) -> Self {
+		Quaternion::from_parts(self.w, self.vector() * -1.)
+	}
+
+	pub fn dot(&self, other: &Quaternion) -> f64 {
+		self.w * other.w + self.vector().dot(&other.vector())
+	}
+
+	pub fn norm(&self) -> f64 {
+		self.dot(self).sqrt()
+	}
+
+	/// Scales the quaternion to unit length.
+	///
+	/// Returns `None` if it has no length.
+	pub fn normalized(&self) -> Option<Self> {
+		let length = self.norm();
+
+		if length == 0. || !length.is_finite() {
+			return None;
+		}
+
+		Some(Quaternion::from_parts(
+			self.w / length,
+			self.vector() * length.recip(),
+		))
+	}
+
+	/// Rotates `v` by this orientation, which must be of unit length.
+	pub fn rotate(&self, v: Vec3d<f64>) -> Vec3d<f64> {
+		let u = self.vector();
+		let t = u.cross(&v) * 2.;
+
+		v + t * self.w + u.cross(&t)
+	}
+}
+
+impl core::ops::Neg for Quaternion {
+	type Output = Self;
+
+	fn neg(self) -> Self::Output {
+		Quaternion::from_parts(-self.w, self.vector() * -1.)
+	}
+}
+
+/// Composes two orientations: `a * b` applies `b` and then `a`.
+impl Mul<Quaternion> for Quaternion {
+	type Output = Self;
+
+	fn mul(self, other: Quaternion) -> Self::Output {
+		let (u, v) = (self.vector(), other.vector());
+
+		Quaternion::from_parts(
+			self.w * other.w - u.dot(&v),
+			v * self.w + u * other.w + u.cross(&v),
+		)
+	}
+}
+
+/// Turns from `a` towards `b` at a constant rate, the shorter way round,
+/// reaching `b` when `s` is `1`.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::rotation::{slerp, Quaternion};
+/// use motion_planning::vec::Vec3d;
+///
+/// let up = Vec3d(0., 0., 1.);
+/// let a = Quaternion::identity();
+/// let b = Quaternion::from_axis_angle(up, 1.).unwrap();
+///
+/// let halfway = slerp(a, b, 0.5).rotation_vector();
+///
+/// assert!((halfway.2 - 0.5).abs() < 1e-12);
+/// ```
+pub fn slerp(a: Quaternion, b: Quaternion, s: f64) -> Quaternion {
+	let turn = (a.conjugate() * b).rotation_vector();
+
+	a * Quaternion::from_rotation_vector(turn * s)
+}
+
+/// Finds the inner control quaternion of [`squad`] at the keyframe `q`,
+/// between `prev` and `next`.
+///
+/// The control makes the turn rate continuous through `q`.  At the first
+/// and last keyframes, where there is no `prev` or `next`, pass `q` itself.
+pub fn squad_control(prev: Quaternion, q: Quaternion, next: Quaternion) -> Quaternion {
+	let inverse = q.conjugate();
+	let to_next = (inverse * next).rotation_vector();
+	let to_prev = (inverse * prev).rotation_vector();
+
+	q * Quaternion::from_rotation_vector((to_next + to_prev) * -0.25)
+}
+
+/// Turns from `q0` to `q1` along a curve shaped by the controls `a0` and
+/// `a1`, reaching `q1` when `s` is `1`.
+///
+/// With controls from [`squad_control`], consecutive segments join without a
+/// sudden change of turn rate.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::rotation::{squad, squad_control, Quaternion};
+/// use motion_planning::vec::Vec3d;
+///
+/// let keys: Vec<Quaternion> = [0., 0.4, 1.1, 1.5]
+///     .iter()
+///     .map(|&angle| Quaternion::from_axis_angle(Vec3d(1., 1., 0.), angle).unwrap())
+///     .collect();
+/// let a1 = squad_control(keys[0], keys[1], keys[2]);
+/// let a2 = squad_control(keys[1], keys[2], keys[3]);
+///
+/// let start = squad(keys[1], a1, a2, keys[2], 0.);
+/// let end = squad(keys[1], a1, a2, keys[2], 1.);
+///
+/// assert!((start.dot(&keys[1]).abs() - 1.).abs() < 1e-12);
+/// assert!((end.dot(&keys[2]).abs() - 1.).abs() < 1e-12);
+/// ```
+pub fn squad(q0: Quaternion, a0: Quaternion, a1: Quaternion, q1: Quaternion, s: f64) -> Quaternion {
+	slerp(slerp(q0, q1, s), slerp(a0, a1, s), 2. * s * (1. - s))
+}
+
+/// The coefficients of the left Jacobian of the exponential map at `r`,
+/// `J = I + a [r]× + b [r]×²`, and of their derivatives with respect to the
+/// angle, divided by the angle.
+fn jacobian_coefficients(r: Vec3d<f64>) -> (f64, f64, f64, f64) {
+	let angle = r.norm();
+	let a2 = angle * angle;
+
+	if angle < SMALL_ANGLE {
+		return (
+			0.5 - a2 / 24. + a2 * a2 / 720.,
+			1. / 6. - a2 / 120. + a2 * a2 / 5040.,
+			-1. / 12. + a2 / 180.,
+			-1. / 60. + a2 / 1260.,
+		);
+	}
+
+	let (s, c) = angle.sin_cos();
+	let a3 = a2 * angle;
+
+	(
+		(1. - c) / a2,
+		(angle - s) / a3,
+		(angle * s - 2. * (1. - c)) / (a3 * angle),
+		((1. - c) * angle - 3. * (angle - s)) / (a3 * a2),
+	)
+}
+
+/// Applies the left Jacobian of the exponential map at `r` to `v`.
+///
+/// A rotation vector `r` changing at `ṙ` turns the orientation it gives with
+/// angular velocity `J ṙ`, in the frame `r` is measured in.
+pub(crate) fn jacobian(r: Vec3d<f64>, v: Vec3d<f64>) -> Vec3d<f64> {
+	let (a, b, _, _) = jacobian_coefficients(r);
+	let rv = r.cross(&v);
+
+	v + rv * a + r.cross(&rv) * b
+}
+
+/// Applies the inverse of the left Jacobian of the exponential map at `r`
+/// to `v`.
+pub(crate) fn inverse_jacobian(r: Vec3d<f64>, v: Vec3d<f64>) -> Vec3d<f64> {
+	let angle = r.norm();
+	let half = angle / 2.;
+
+	let e = if angle < SMALL_ANGLE {
+		let a2 = angle * angle;
+
+		1. / 12. + a2 / 720. + a2 * a2 / 30240.
+	} else {
+		(1. - half * half.cos() / half.sin()) / (angle * angle)
+	};
+
+	let rv = r.cross(&v);
+
+	v + rv * -0.5 + r.cross(&rv) * e
+}
+
+/// An orientation waypoint: a unit quaternion `attitude`, turning with
+/// `angular_velocity` in the fixed frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orientation {
+	pub attitude: Quaternion,
+	pub angular_velocity: Vec3d<f64>,
+}
+
+/// The orientation, angular velocity and angular acceleration a fraction `u`
+/// of the way through a segment from `prec` to `succ` lasting `duration`.
+fn evaluate(
+	u: f64,
+	prec: &Orientation,
+	succ: &Orientation,
+	duration: f64,
+) -> (Quaternion, Vec3d<f64>, Vec3d<f64>) {
+	let q0 = prec.attitude;
+	let inverse = q0.conjugate();
+
+	// The Hermite data for the rotation vector, relative to `q0`, with
+	// derivatives taken in `u`.
+	let r1 = (inverse * succ.attitude).rotation_vector();
+	let v0 = inverse.rotate(prec.angular_velocity) * duration;
+	let v1 = inverse_jacobian(r1, inverse.rotate(succ.angular_velocity)) * duration;
+
+	let hermite = |h: fn(f64, usize) -> f64| v0 * h(u, 1) + v1 * h(u, 2) + r1 * h(u, 3);
+
+	let r = hermite(h_3);
+	let dr = hermite(h_3p) * duration.recip();
+	let ddr = hermite(h_3pp) * duration.powi(-2);
+
+	let (_, b, da, db) = jacobian_coefficients(r);
+	let rdr = r.cross(&dr);
+	let rate = r.dot(&dr);
+
+	// The derivative of the Jacobian, applied to `dr`.
+	let djdr = rdr * (da * rate) + r.cross(&rdr) * (db * rate) + dr.cross(&rdr) * b;
+
+	(
+		q0 * Quaternion::from_rotation_vector(r),
+		q0.rotate(jacobian(r, dr)),
+		q0.rotate(jacobian(r, ddr) + djdr),
+	)
+}
+
+/// Waypoints that include an orientation.
+pub trait Oriented {
+	fn orientation(&self) -> &Orientation;
+}
+
+impl Oriented for Orientation {
+	fn orientation(&self) -> &Orientation {
+		self
+	}
+}
+
+/// A trajectory of orientations.
+pub trait RotationTrajectory {
+	fn orientation_at(&self, t: f64) -> Option<Quaternion>;
+	fn angular_velocity_at(&self, t: f64) -> Option<Vec3d<f64>>;
+	fn angular_acceleration_at(&self, t: f64) -> Option<Vec3d<f64>>;
+}
+
+impl<W: Oriented> RotationTrajectory for Vec<W> {
+	fn orientation_at(&self, t: f64) -> Option<Quaternion> {
+		let (prec, succ, u, duration) = find_untimed_segment(self.len(), t)?;
+
+		Some(
+			evaluate(
+				u,
+				self[prec].orientation(),
+				self[succ].orientation(),
+				duration,
+			)
+			.0,
+		)
+	}
+
+	fn angular_velocity_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let (prec, succ, u, duration) = find_untimed_segment(self.len(), t)?;
+
+		Some(
+			evaluate(
+				u,
+				self[prec].orientation(),
+				self[succ].orientation(),
+				duration,
+			)
+			.1,
+		)
+	}
+
+	fn angular_acceleration_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let (prec, succ, u, duration) = find_untimed_segment(self.len(), t)?;
+
+		Some(
+			evaluate(
+				u,
+				self[prec].orientation(),
+				self[succ].orientation(),
+				duration,
+			)
+			.2,
+		)
+	}
+}
+
+impl<W: Oriented> RotationTrajectory for Vec<Timed<W>> {
+	fn orientation_at(&self, t: f64) -> Option<Quaternion> {
+		let (prec, succ, u, duration) = find_segment(self, t)?;
+
+		Some(
+			evaluate(
+				u,
+				self[prec].pose.orientation(),
+				self[succ].pose.orientation(),
+				duration,
+			)
+			.0,
+		)
+	}
+
+	fn angular_velocity_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let (prec, succ, u, duration) = find_segment(self, t)?;
+
+		Some(
+			evaluate(
+				u,
+				self[prec].pose.orientation(),
+				self[succ].pose.orientation(),
+				duration,
+			)
+			.1,
+		)
+	}
+
+	fn angular_acceleration_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let (prec, succ, u, duration) = find_segment(self, t)?;
+
+		Some(
+			evaluate(
+				u,
+				self[prec].pose.orientation(),
+				self[succ].pose.orientation(),
+				duration,
+			)
+			.2,
+		)
+	}
+}
+
+/// A waypoint for a rigid body: where it is and how it is moving, with
+/// `translation`, and which way it faces and how it is turning, with
+/// `rotation`.
+///
+/// A trajectory of rigid poses is both a [`Trajectory3`] and a
+/// [`RotationTrajectory`].
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::rotation::{Orientation, Quaternion, RigidPose, RotationTrajectory};
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::{Pose3, Trajectory3};
+///
+/// let camera = |x: f64, yaw: f64| RigidPose {
+///     translation: Pose3 {
+///         position: Vec3d(x, 0., 0.),
+///         velocity: Vec3d(1., 0., 0.),
+///         acceleration: Vec3d(0., 0., 0.),
+///     },
+///     rotation: Orientation {
+///         attitude: Quaternion::from_axis_angle(Vec3d(0., 0., 1.), yaw).unwrap(),
+///         angular_velocity: Vec3d(0., 0., 0.5),
+///     },
+/// };
+/// let pan = vec![camera(0., 0.), camera(1., 0.5)];
+///
+/// assert_eq!(pan.position_at(0.5), Some(Vec3d(0.5, 0., 0.)));
+///
+/// let yaw = pan.orientation_at(0.5).unwrap().rotation_vector().2;
+/// assert!((yaw - 0.25).abs() < 1e-12);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RigidPose {
+	pub translation: Pose3<Vec3d<f64>>,
+	pub rotation: Orientation,
+}
+
+impl Oriented for RigidPose {
+	fn orientation(&self) -> &Orientation {
+		&self.rotation
+	}
+}
+
+impl Trajectory3<Vec3d<f64>> for Vec<RigidPose> {
+	fn get_segment(&self, t: f64) -> Option<Segment3<'_, Vec3d<f64>>> {
+		if !(0. ..=self.len() as f64 - 1.).contains(&t) {
+			return None;
+		}
+
+		let prec = &self[t.floor() as usize].translation;
+		let succ = &self[t.ceil() as usize].translation;
+
+		Some(Segment3(t.fract(), prec, succ))
+	}
+
+	fn position_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let Segment3(u, prec, succ) = self.get_segment(t)?;
+
+		Some(eval3(u, prec, succ, 1., h_5, 0))
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let Segment3(u, prec, succ) = self.get_segment(t)?;
+
+		Some(eval3(u, prec, succ, 1., h_5p, 1))
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let Segment3(u, prec, succ) = self.get_segment(t)?;
+
+		Some(eval3(u, prec, succ, 1., h_5pp, 2))
+	}
+}
+
+impl Trajectory3<Vec3d<f64>> for Vec<Timed<RigidPose>> {
+	fn get_segment(&self, t: f64) -> Option<Segment3<'_, Vec3d<f64>>> {
+		let (prec, succ, u, _) = locate(self, t)?;
+
+		Some(Segment3(
+			u,
+			&self[prec].pose.translation,
+			&self[succ].pose.translation,
+		))
+	}
+
+	fn position_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval3(
+			u,
+			&self[prec].pose.translation,
+			&self[succ].pose.translation,
+			duration,
+			h_5,
+			0,
+		))
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval3(
+			u,
+			&self[prec].pose.translation,
+			&self[succ].pose.translation,
+			duration,
+			h_5p,
+			1,
+		))
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<Vec3d<f64>> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval3(
+			u,
+			&self[prec].pose.translation,
+			&self[succ].pose.translation,
+			duration,
+			h_5pp,
+			2,
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::*;
+
+	fn assert_vec_roughly_eq(a: Vec3d<f64>, b: Vec3d<f64>, tolerance: f64) {
+		assert!(
+			(a + b * -1.).norm() < tolerance,
+			"{} and {} differ by more than {}",
+			a,
+			b,
+			tolerance
+		);
+	}
+
+	fn assert_same_orientation(a: Quaternion, b: Quaternion, tolerance: f64) {
+		assert_f64_roughly_eq!(a.dot(&b).abs(), 1., tolerance);
+	}
+
+	/// Some turning waypoints, unevenly timed.
+	fn waypoints() -> Vec<Timed<Orientation>> {
+		let at = |time, axis, angle, angular_velocity| Timed {
+			time,
+			pose: Orientation {
+				attitude: Quaternion::from_axis_angle(axis, angle).unwrap(),
+				angular_velocity,
+			},
+		};
+
+		vec![
+			at(0., Vec3d(1., 0., 0.), 0., Vec3d(0., 0., 0.)),
+			at(1.5, Vec3d(0., 1., 1.), 1.2, Vec3d(0.3, -0.2, 0.9)),
+			at(2., Vec3d(1., -1., 0.5), 2.5, Vec3d(-1., 0.4, 0.2)),
+			at(4., Vec3d(0., 0., 1.), -3., Vec3d(0., 0., 0.)),
+		]
+	}
+
+	#[test]
+	fn quaternion_rotation() {
+		let quarter =
+			Quaternion::from_axis_angle(Vec3d(0., 0., 2.), core::f64::consts::FRAC_PI_2).unwrap();
+
+		assert_vec_roughly_eq(quarter.rotate(Vec3d(1., 0., 0.)), Vec3d(0., 1., 0.), 1e-15);
+
+		let tilt = Quaternion::from_axis_angle(Vec3d(1., 0., 0.), 0.7).unwrap();
+		let v = Vec3d(0.3, -1., 2.);
+
+		assert_vec_roughly_eq(
+			(tilt * quarter).rotate(v),
+			tilt.rotate(quarter.rotate(v)),
+			1e-15,
+		);
+		assert_eq!(Quaternion::from_axis_angle(Vec3d(0., 0., 0.), 1.), None);
+		assert_eq!(
+			Quaternion {
+				w: 0.,
+				x: 0.,
+				y: 0.,
+				z: 0.
+			}
+			.normalized(),
+			None
+		);
+	}
+
+	#[test]
+	fn from_axes_round_trip() {
+		for r in [
+			Vec3d(0.3, -0.2, 0.5),
+			Vec3d(3., 0.1, 0.),
+			Vec3d(0., -3., 0.2),
+			Vec3d(0.1, 0.2, 3.1),
+		] {
+			let q = Quaternion::from_rotation_vector(r);
+			let axes = Quaternion::from_axes(
+				q.rotate(Vec3d(1., 0., 0.)),
+				q.rotate(Vec3d(0., 1., 0.)),
+				q.rotate(Vec3d(0., 0., 1.)),
+			);
+
+			assert_same_orientation(axes, q, 1e-12);
+		}
+	}
+
+	#[test]
+	fn rotation_vector_round_trip() {
+		for r in [
+			Vec3d(0., 0., 0.),
+			Vec3d(1e-9, -2e-9, 0.),
+			Vec3d(0.004, 0.003, 0.),
+			Vec3d(0.5, -1., 2.),
+			Vec3d(0., 3.1, 0.),
+		] {
+			let q = Quaternion::from_rotation_vector(r);
+
+			assert_f64_roughly_eq!(q.norm(), 1., 1e-15);
+			assert_vec_roughly_eq(q.rotation_vector(), r, 1e-14);
+			assert_vec_roughly_eq((-q).rotation_vector(), r, 1e-14);
+		}
+	}
+
+	#[test]
+	fn slerp_takes_shorter_way() {
+		let a = Quaternion::from_axis_angle(Vec3d(0., 1., 0.), 0.2).unwrap();
+		let b = Quaternion::from_axis_angle(Vec3d(0., 1., 0.), 1.).unwrap();
+
+		assert_same_orientation(slerp(a, b, 0.), a, 1e-15);
+		assert_same_orientation(slerp(a, b, 1.), b, 1e-15);
+		assert_vec_roughly_eq(
+			slerp(a, -b, 0.25).rotation_vector(),
+			Vec3d(0., 0.4, 0.),
+			1e-14,
+		);
+	}
+
+	#[test]
+	fn squad_about_one_axis_is_uniform() {
+		// Evenly spaced turns about one axis need no easing at the keyframes.
+		let axis = Vec3d(1., 2., 2.);
+		let keys: Vec<Quaternion> = (0..4)
+			.map(|i| Quaternion::from_axis_angle(axis, 0.5 * f64::from(i)).unwrap())
+			.collect();
+		let a1 = squad_control(keys[0], keys[1], keys[2]);
+		let a2 = squad_control(keys[1], keys[2], keys[3]);
+
+		for s in [0., 0.3, 0.5, 0.8, 1.] {
+			assert_same_orientation(
+				squad(keys[1], a1, a2, keys[2], s),
+				slerp(keys[1], keys[2], s),
+				1e-14,
+			);
+		}
+	}
+
+	#[test]
+	fn spline_interpolates_waypoints() {
+		let path = waypoints();
+
+		for waypoint in &path {
+			let t = waypoint.time;
+
+			assert_same_orientation(
+				path.orientation_at(t).unwrap(),
+				waypoint.pose.attitude,
+				1e-14,
+			);
+			assert_vec_roughly_eq(
+				path.angular_velocity_at(t).unwrap(),
+				waypoint.pose.angular_velocity,
+				1e-12,
+			);
+		}
+
+		assert_eq!(path.orientation_at(-0.1), None);
+		assert_eq!(path.angular_velocity_at(4.1), None);
+	}
+
+	#[test]
+	fn spline_derivatives_match_differences() {
+		let path = waypoints();
+		let dt = 1e-5;
+
+		for t in [0.2, 1.4, 1.6, 1.99, 3.] {
+			let before = path.orientation_at(t - dt).unwrap();
+			let after = path.orientation_at(t + dt).unwrap();
+			let q = path.orientation_at(t).unwrap();
+
+			// The turn over `2 dt`, in the fixed frame.
+			let turn = (after * before.conjugate()).rotation_vector();
+			let omega = path.angular_velocity_at(t).unwrap();
+
+			assert_vec_roughly_eq(turn * (0.5 / dt), omega, 1e-8);
+			assert_f64_roughly_eq!(q.norm(), 1., 1e-14);
+
+			let difference = (path.angular_velocity_at(t + dt).unwrap()
+				+ path.angular_velocity_at(t - dt).unwrap() * -1.)
+				* (0.5 / dt);
+
+			assert_vec_roughly_eq(path.angular_acceleration_at(t).unwrap(), difference, 1e-6);
+		}
+	}
+
+	#[test]
+	fn constant_turn() {
+		let spin = |angle| Orientation {
+			attitude: Quaternion::from_axis_angle(Vec3d(0., 0., 1.), angle).unwrap(),
+			angular_velocity: Vec3d(0., 0., 0.75),
+		};
+		let path = vec![spin(0.), spin(0.75), spin(1.5)];
+
+		for t in [0.25, 1., 1.9] {
+			assert_vec_roughly_eq(
+				path.orientation_at(t).unwrap().rotation_vector(),
+				Vec3d(0., 0., 0.75 * t),
+				1e-14,
+			);
+			assert_vec_roughly_eq(
+				path.angular_acceleration_at(t).unwrap(),
+				Vec3d(0., 0., 0.),
+				1e-14,
+			);
+		}
+	}
+
+	#[test]
+	fn rigid_pose_trajectory() {
+		let translations = vec![
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(1., 0., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 1., 0.),
+				velocity: Vec3d(1., 1., 0.),
+				acceleration: Vec3d(0., 0., 1.),
+			},
+		];
+		let rotations = vec![waypoints()[0].pose, waypoints()[1].pose];
+		let poses: Vec<RigidPose> = translations
+			.iter()
+			.zip(&rotations)
+			.map(|(&translation, &rotation)| RigidPose {
+				translation,
+				rotation,
+			})
+			.collect();
+		let timed = super::super::timed::timed(&poses);
+
+		for t in [0., 0.4, 1.] {
+			assert_eq!(poses.position_at(t), translations.position_at(t));
+			assert_eq!(timed.acceleration_at(t), translations.acceleration_at(t));
+			assert_eq!(poses.orientation_at(t), rotations.orientation_at(t));
+			assert_eq!(
+				timed.angular_velocity_at(t),
+				rotations.angular_velocity_at(t)
+			);
+		}
+
+		assert_eq!(poses.position_at(1.5), None);
+	}
+}
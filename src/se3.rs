@@ -0,0 +1,536 @@
+//! Splines on rigid-body motions, moving and turning together.
+//!
+//! An [`Se3`] is a rigid-body transform, and a [`Twist`] is how one is
+//! changing: a linear and an angular velocity, both in the body's own frame.
+//! Each segment of an [`Se3Trajectory`] starts from the pose at its first
+//! waypoint and follows `exp(ξ(t))` from there, where `ξ` is a quintic
+//! Hermite curve through twists.  The boundary conditions on `ξ` are chosen
+//! so that the body twist and its rate of change match those of each
+//! waypoint, just as a [`Pose3`](crate::Pose3) gives velocity and
+//! acceleration.  Translation and rotation therefore interpolate as one
+//! screw motion, with the twist continuous, and its rate of change too.
+//!
+//! Relating `ξ`'s derivatives to body twists needs the Jacobian of the
+//! exponential map, which is computed from its power series in the adjoint
+//! of `ξ`.  Since the rotation in a segment is at most half a turn, the
+//! series converges to rounding error within a few dozen terms.
+
+use core::ops::{Add, Mul};
+
+use super::hermite::quintic::{h_5, h_5p, h_5pp};
+use super::linalg::solve;
+use super::rotation::{inverse_jacobian, jacobian, Quaternion};
+use super::timed::{locate, Timed};
+use super::vec::Vec3d;
+use super::{eval3, Pose3};
+
+/// How many terms of the series for the Jacobian of the exponential map are
+/// summed.
+const SERIES_TERMS: usize = 40;
+
+/// A velocity of a rigid body: `linear` for how fast its origin moves and
+/// `angular` for how fast it turns.  Twists in this module are in the
+/// body's own frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Twist {
+	pub linear: Vec3d<f64>,
+	pub angular: Vec3d<f64>,
+}
+
+impl Twist {
+	/// Neither moving nor turning.
+	pub fn zero() -> Self {
+		Twist {
+			linear: Vec3d(0., 0., 0.),
+			angular: Vec3d(0., 0., 0.),
+		}
+	}
+
+	/// The Lie bracket `[self, other]`, which is how `other` changes under
+	/// the adjoint action of `self`.
+	fn bracket(&self, other: &Twist) -> Twist {
+		Twist {
+			linear: self.angular.cross(&other.linear) + self.linear.cross(&other.angular),
+			angular: self.angular.cross(&other.angular),
+		}
+	}
+
+	fn components(&self) -> [f64; 6] {
+		[
+			self.linear.0,
+			self.linear.1,
+			self.linear.2,
+			self.angular.0,
+			self.angular.1,
+			self.angular.2,
+		]
+	}
+
+	fn from_components(c: &[f64]) -> Self {
+		Twist {
+			linear: Vec3d(c[0], c[1], c[2]),
+			angular: Vec3d(c[3], c[4], c[5]),
+		}
+	}
+}
+
+impl Add<Twist> for Twist {
+	type Output = Self;
+
+	fn add(self, other: Twist) -> Self::Output {
+		Twist {
+			linear: self.linear + other.linear,
+			angular: self.angular + other.angular,
+		}
+	}
+}
+
+impl Mul<f64> for Twist {
+	type Output = Self;
+
+	fn mul(self, scalar: f64) -> Self::Output {
+		Twist {
+			linear: self.linear * scalar,
+			angular: self.angular * scalar,
+		}
+	}
+}
+
+/// A rigid-body transform: a `rotation` followed by a `translation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Se3 {
+	pub rotation: Quaternion,
+	pub translation: Vec3d<f64>,
+}
+
+impl Se3 {
+	/// The transform that leaves everything as it is.
+	pub fn identity() -> Self {
+		Se3 {
+			rotation: Quaternion::identity(),
+			translation: Vec3d(0., 0., 0.),
+		}
+	}
+
+	/// The transform reached by following `twist` for unit time.
+	///
+	/// This is the exponential map from twists to transforms.
+	pub fn exp(twist: Twist) -> Self {
+		Se3 {
+			rotation: Quaternion::from_rotation_vector(twist.angular),
+			translation: jacobian(twist.angular, twist.linear),
+		}
+	}
+
+	/// The twist that reaches this transform in unit time, turning by at
+	/// most half a turn.
+	///
+	/// This is the logarithm map, the inverse of [`exp`](Se3::exp).
+	pub fn log(&self) -> Twist {
+		let angular = self.rotation.rotation_vector();
+
+		Twist {
+			linear: inverse_jacobian(angular, self.translation),
+			angular,
+		}
+	}
+
+	/// The transform that undoes this one.
+	pub fn inverse(&self) -> Self {
+		let rotation = self.rotation.conjugate();
+
+		Se3 {
+			rotation,
+			translation: rotation.rotate(self.translation) * -1.,
+		}
+	}
+
+	/// Applies the transform to a point.
+	pub fn point(&self, p: Vec3d<f64>) -> Vec3d<f64> {
+		self.rotation.rotate(p) + self.translation
+	}
+}
+
+/// Composes two transforms: `a * b` applies `b` and then `a`.
+impl Mul<Se3> for Se3 {
+	type Output = Self;
+
+	fn mul(self, other: Se3) -> Self::Output {
+		Se3 {
+			rotation: self.rotation * other.rotation,
+			translation: self.point(other.translation),
+		}
+	}
+}
+
+/// A waypoint for a rigid body: its pose `position`, its body twist
+/// `velocity`, and the rate of change of that twist, `acceleration`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Se3Pose {
+	pub position: Se3,
+	pub velocity: Twist,
+	pub acceleration: Twist,
+}
+
+/// The coefficient of the `k`th power of the adjoint in the series for the
+/// right Jacobian, `(-1)^k / (k + 1)!`.
+fn coefficients() -> [f64; SERIES_TERMS] {
+	let mut c = [0.; SERIES_TERMS];
+	let mut factorial = 1.;
+
+	for (k, coefficient) in c.iter_mut().enumerate() {
+		factorial *= (k + 1) as f64;
+		*coefficient = if k % 2 == 0 { 1. } else { -1. } / factorial;
+	}
+
+	c
+}
+
+/// Applies the right Jacobian of the exponential map at `xi` to `v`.
+///
+/// When `xi` changes at `v`, `exp(xi)` moves with body twist `J v`.
+fn right_jacobian(xi: &Twist, v: &Twist) -> Twist {
+	let c = coefficients();
+
+	c.iter()
+		.rev()
+		.fold(Twist::zero(), |sum, &ck| *v * ck + xi.bracket(&sum))
+}
+
+/// Applies the rate of change of the right Jacobian at `xi`, as `xi` changes
+/// at `dxi`, to `dxi` itself.
+fn right_jacobian_rate(xi: &Twist, dxi: &Twist) -> Twist {
+	let c = coefficients();
+
+	// The `m`th power of the adjoint of `xi`, applied to `dxi`, then
+	// bracketed with `dxi`.
+	let mut power = *dxi;
+	let mut bracketed = Vec::with_capacity(SERIES_TERMS);
+
+	for _ in 0..SERIES_TERMS {
+		bracketed.push(dxi.bracket(&power));
+		power = xi.bracket(&power);
+	}
+
+	// The derivative of the `k`th power is the sum over `j` of
+	// `ad^j ad_dxi ad^(k-1-j)`; group the terms by `j`.
+	(0..SERIES_TERMS).rev().fold(Twist::zero(), |sum, j| {
+		let group = (0..SERIES_TERMS - j - 1)
+			.map(|m| bracketed[m] * c[j + m + 1])
+			.fold(Twist::zero(), |a, b| a + b);
+
+		group + xi.bracket(&sum)
+	})
+}
+
+/// Solves `J(xi) x = v` for `x`, where `J` is the right Jacobian.
+fn solve_right_jacobian(xi: &Twist, v: &Twist) -> Option<Twist> {
+	let columns: Vec<[f64; 6]> = (0..6)
+		.map(|i| {
+			let mut e = [0.; 6];
+			e[i] = 1.;
+
+			right_jacobian(xi, &Twist::from_components(&e)).components()
+		})
+		.collect();
+	let matrix = (0..6)
+		.map(|row| columns.iter().map(|column| column[row]).collect())
+		.collect();
+
+	solve(matrix, v.components().to_vec()).map(|x| Twist::from_components(&x))
+}
+
+/// The pose, body twist and its rate of change a fraction `u` of the way
+/// through a segment from `prec` to `succ` lasting `duration`.
+fn evaluate(u: f64, prec: &Se3Pose, succ: &Se3Pose, duration: f64) -> Option<(Se3, Twist, Twist)> {
+	let start = prec.position;
+	let xi1 = (start.inverse() * succ.position).log();
+
+	let dxi1 = solve_right_jacobian(&xi1, &succ.velocity)?;
+	let ddxi1 = solve_right_jacobian(
+		&xi1,
+		&(succ.acceleration + right_jacobian_rate(&xi1, &dxi1) * -1.),
+	)?;
+
+	// At the start `xi` is zero, where the Jacobian is the identity and
+	// its rate is zero.
+	let from = Pose3 {
+		position: Twist::zero(),
+		velocity: prec.velocity,
+		acceleration: prec.acceleration,
+	};
+	let to = Pose3 {
+		position: xi1,
+		velocity: dxi1,
+		acceleration: ddxi1,
+	};
+
+	let xi = eval3(u, &from, &to, duration, h_5, 0);
+	let dxi = eval3(u, &from, &to, duration, h_5p, 1);
+	let ddxi = eval3(u, &from, &to, duration, h_5pp, 2);
+
+	Some((
+		start * Se3::exp(xi),
+		right_jacobian(&xi, &dxi),
+		right_jacobian(&xi, &ddxi) + right_jacobian_rate(&xi, &dxi),
+	))
+}
+
+/// A trajectory of rigid-body poses.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::se3::{Se3, Se3Pose, Se3Trajectory, Twist};
+/// use motion_planning::vec::Vec3d;
+///
+/// // Screw along and about z, as a drill would.
+/// let screw = Twist {
+///     linear: Vec3d(0., 0., 0.1),
+///     angular: Vec3d(0., 0., 2.),
+/// };
+/// let waypoint = |t: f64| Se3Pose {
+///     position: Se3::exp(screw * t),
+///     velocity: screw,
+///     acceleration: Twist::zero(),
+/// };
+/// let drill = vec![waypoint(0.), waypoint(1.), waypoint(2.)];
+///
+/// let halfway = drill.position_at(1.5).unwrap();
+///
+/// assert!((halfway.translation.2 - 0.15).abs() < 1e-12);
+/// assert!((drill.velocity_at(0.3).unwrap().angular.2 - 2.).abs() < 1e-12);
+/// ```
+pub trait Se3Trajectory {
+	fn position_at(&self, t: f64) -> Option<Se3>;
+	fn velocity_at(&self, t: f64) -> Option<Twist>;
+	fn acceleration_at(&self, t: f64) -> Option<Twist>;
+}
+
+impl Se3Trajectory for Vec<Se3Pose> {
+	fn position_at(&self, t: f64) -> Option<Se3> {
+		let (u, prec, succ) = unit_segment(self, t)?;
+
+		Some(evaluate(u, prec, succ, 1.)?.0)
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<Twist> {
+		let (u, prec, succ) = unit_segment(self, t)?;
+
+		Some(evaluate(u, prec, succ, 1.)?.1)
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<Twist> {
+		let (u, prec, succ) = unit_segment(self, t)?;
+
+		Some(evaluate(u, prec, succ, 1.)?.2)
+	}
+}
+
+/// Finds the segment containing `t` in waypoints timed by their indices.
+fn unit_segment(waypoints: &[Se3Pose], t: f64) -> Option<(f64, &Se3Pose, &Se3Pose)> {
+	if !(0. ..=waypoints.len() as f64 - 1.).contains(&t) {
+		return None;
+	}
+
+	Some((
+		t.fract(),
+		&waypoints[t.floor() as usize],
+		&waypoints[t.ceil() as usize],
+	))
+}
+
+impl Se3Trajectory for Vec<Timed<Se3Pose>> {
+	fn position_at(&self, t: f64) -> Option<Se3> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(evaluate(u, &self[prec].pose, &self[succ].pose, duration)?.0)
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<Twist> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(evaluate(u, &self[prec].pose, &self[succ].pose, duration)?.1)
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<Twist> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(evaluate(u, &self[prec].pose, &self[succ].pose, duration)?.2)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_twist_roughly_eq(a: Twist, b: Twist, tolerance: f64) {
+		let difference = a + b * -1.;
+		let error = difference.linear.norm() + difference.angular.norm();
+
+		assert!(
+			error < tolerance,
+			"{:?} and {:?} differ by more than {}",
+			a,
+			b,
+			tolerance
+		);
+	}
+
+	fn twist(linear: (f64, f64, f64), angular: (f64, f64, f64)) -> Twist {
+		Twist {
+			linear: Vec3d(linear.0, linear.1, linear.2),
+			angular: Vec3d(angular.0, angular.1, angular.2),
+		}
+	}
+
+	/// Some waypoints turning and moving in all directions, unevenly timed.
+	fn waypoints() -> Vec<Timed<Se3Pose>> {
+		let at = |time, pose, velocity, acceleration| Timed {
+			time,
+			pose: Se3Pose {
+				position: Se3::exp(pose),
+				velocity,
+				acceleration,
+			},
+		};
+
+		vec![
+			at(
+				0.,
+				twist((0., 0., 0.), (0., 0., 0.)),
+				twist((0.5, 0., 0.), (0., 0., 0.3)),
+				twist((0., 0., 0.), (0., 0., 0.)),
+			),
+			at(
+				1.5,
+				twist((1., 0.4, -0.2), (0.3, -0.5, 1.)),
+				twist((0.4, 0.1, 0.), (0.2, 0.1, -0.4)),
+				twist((0.1, 0., 0.3), (-0.2, 0., 0.1)),
+			),
+			at(
+				2.5,
+				twist((2., -1., 0.5), (1.2, 0.7, -1.4)),
+				twist((0., 0.3, 0.2), (0.5, -0.6, 0.2)),
+				twist((0., -0.4, 0.), (0.3, 0.2, 0.)),
+			),
+		]
+	}
+
+	#[test]
+	fn exp_and_log() {
+		for xi in [
+			twist((1., 2., 3.), (0., 0., 0.)),
+			twist((0.3, -1., 0.2), (1e-9, 0., -2e-9)),
+			twist((0.3, -1., 0.2), (0.5, 2., -1.)),
+			twist((0., 0., 1.), (0., 3.1, 0.)),
+		] {
+			let pose = Se3::exp(xi);
+
+			assert_twist_roughly_eq(pose.log(), xi, 1e-13);
+			assert_twist_roughly_eq((pose.inverse() * pose).log(), Twist::zero(), 1e-15);
+		}
+
+		// A quarter turn about z while moving along y sweeps an arc.
+		let quarter = Se3::exp(twist(
+			(0., core::f64::consts::FRAC_PI_2, 0.),
+			(0., 0., core::f64::consts::FRAC_PI_2),
+		));
+		let origin = quarter.point(Vec3d(0., 0., 0.));
+
+		assert!((origin + Vec3d(-1., 1., 0.) * -1.).norm() < 1e-15);
+	}
+
+	#[test]
+	fn jacobian_series() {
+		// The series agrees with the closed form for pure rotations.
+		let xi = twist((0., 0., 0.), (0.4, -1.3, 2.));
+		let v = twist((0., 0., 0.), (1., 0.5, -0.2));
+
+		let expected = jacobian(xi.angular * -1., v.angular);
+
+		assert_twist_roughly_eq(
+			right_jacobian(&xi, &v),
+			twist((0., 0., 0.), (expected.0, expected.1, expected.2)),
+			1e-15,
+		);
+
+		let v = twist((0.3, 0.1, -1.), (1., 0.5, -0.2));
+		let back = solve_right_jacobian(&xi, &right_jacobian(&xi, &v)).unwrap();
+
+		assert_twist_roughly_eq(back, v, 1e-14);
+	}
+
+	#[test]
+	fn interpolates_waypoints() {
+		let path = waypoints();
+
+		for waypoint in &path {
+			let t = waypoint.time;
+			let pose = path.position_at(t).unwrap();
+
+			assert_twist_roughly_eq(
+				(waypoint.pose.position.inverse() * pose).log(),
+				Twist::zero(),
+				1e-14,
+			);
+			assert_twist_roughly_eq(path.velocity_at(t).unwrap(), waypoint.pose.velocity, 1e-12);
+			assert_twist_roughly_eq(
+				path.acceleration_at(t).unwrap(),
+				waypoint.pose.acceleration,
+				1e-12,
+			);
+		}
+
+		assert_eq!(path.position_at(-0.5), None);
+		assert_eq!(path.velocity_at(3.), None);
+	}
+
+	#[test]
+	fn derivatives_match_differences() {
+		let path = waypoints();
+		let dt = 1e-5;
+
+		// Either side of the middle waypoint, and close to it.
+		for t in [0.3, 1.2, 1.49, 1.51, 2.2] {
+			let before = path.position_at(t - dt).unwrap();
+			let after = path.position_at(t + dt).unwrap();
+
+			let twist = (before.inverse() * after).log() * (0.5 / dt);
+
+			assert_twist_roughly_eq(path.velocity_at(t).unwrap(), twist, 1e-8);
+
+			let rate =
+				(path.velocity_at(t + dt).unwrap() + path.velocity_at(t - dt).unwrap() * -1.) * (0.5 / dt);
+
+			assert_twist_roughly_eq(path.acceleration_at(t).unwrap(), rate, 1e-6);
+		}
+	}
+
+	#[test]
+	fn constant_screw_is_exact() {
+		let screw = twist((0.3, -0.2, 1.), (0.4, 0.9, -0.5));
+		let path: Vec<Se3Pose> = (0..3)
+			.map(|i| Se3Pose {
+				position: Se3::exp(screw * f64::from(i)),
+				velocity: screw,
+				acceleration: Twist::zero(),
+			})
+			.collect();
+
+		for t in [0.2, 0.9, 1.7] {
+			let pose = path.position_at(t).unwrap();
+
+			assert_twist_roughly_eq(
+				(Se3::exp(screw * t).inverse() * pose).log(),
+				Twist::zero(),
+				1e-14,
+			);
+			assert_twist_roughly_eq(path.velocity_at(t).unwrap(), screw, 1e-14);
+			assert_twist_roughly_eq(path.acceleration_at(t).unwrap(), Twist::zero(), 1e-14);
+		}
+	}
+}
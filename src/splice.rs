@@ -0,0 +1,286 @@
+//! Cutting trajectories apart and joining them back together.
+//!
+//! These work on timed waypoints, because a cut inside a segment leaves two
+//! pieces of different durations that a plain waypoint vector cannot
+//! represent.  Convert a `Vec<Pose3<V>>` with [`timed`](super::timed::timed)
+//! first; cutting is then exact, in that the trajectory passes through the
+//! same positions at the same times before and after.
+
+use core::ops::{Add, Mul};
+
+use super::eval3;
+use super::hermite::quintic::{h_5, h_5p, h_5pp};
+use super::timed::{locate, Timed};
+use super::vec::Vec3d;
+use super::Pose3;
+
+/// The first derivative found not to match across a joint, along with the
+/// size of the mismatch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Discontinuity {
+	Position(f64),
+	Velocity(f64),
+	Acceleration(f64),
+}
+
+/// Evaluates the full pose of `waypoints` at `t`.
+fn pose_at<V>(waypoints: &[Timed<Pose3<V>>], t: f64) -> Option<Pose3<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let (prec, succ, u, duration) = locate(waypoints, t)?;
+	let prec = &waypoints[prec].pose;
+	let succ = &waypoints[succ].pose;
+
+	Some(Pose3 {
+		position: eval3(u, prec, succ, duration, h_5, 0),
+		velocity: eval3(u, prec, succ, duration, h_5p, 1),
+		acceleration: eval3(u, prec, succ, duration, h_5pp, 2),
+	})
+}
+
+/// Cuts `waypoints` at `t` by inserting a waypoint there, with the position,
+/// velocity and acceleration the trajectory already has at `t`.
+///
+/// If `t` is already a waypoint's time, the waypoints are returned unchanged.
+/// Returns `None` if `t` lies outside the trajectory.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::splice::split;
+/// use motion_planning::timed::timed;
+/// use motion_planning::{Pose3, Trajectory3};
+///
+/// let waypoints = timed(&[
+///     Pose3 { position: 0., velocity: 0., acceleration: 0. },
+///     Pose3 { position: 1., velocity: 0., acceleration: 0. },
+/// ]);
+///
+/// let cut = split(&waypoints, 0.25).unwrap();
+///
+/// assert_eq!(cut.len(), 3);
+/// assert_eq!(cut[1].time, 0.25);
+/// let difference = cut.position_at(0.6).unwrap() - waypoints.position_at(0.6).unwrap();
+/// assert!(difference.abs() < 1e-12);
+/// ```
+pub fn split<V>(waypoints: &[Timed<Pose3<V>>], t: f64) -> Option<Vec<Timed<Pose3<V>>>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let (prec, succ, _, _) = locate(waypoints, t)?;
+	let mut result = waypoints.to_vec();
+
+	if prec != succ {
+		let pose = pose_at(waypoints, t)?;
+
+		result.insert(succ, Timed { time: t, pose });
+	}
+
+	Some(result)
+}
+
+/// Extracts the part of `waypoints` between the times `from` and `to`,
+/// cutting the segments that contain them.
+///
+/// The waypoints keep their original times, so the result starts at `from`.
+/// Returns `None` if `from` is after `to` or either lies outside the
+/// trajectory.
+pub fn trim<V>(waypoints: &[Timed<Pose3<V>>], from: f64, to: f64) -> Option<Vec<Timed<Pose3<V>>>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	if from > to {
+		return None;
+	}
+
+	let start = Timed {
+		time: from,
+		pose: pose_at(waypoints, from)?,
+	};
+	let end = Timed {
+		time: to,
+		pose: pose_at(waypoints, to)?,
+	};
+
+	let mut result = vec![start];
+	result.extend(
+		waypoints
+			.iter()
+			.filter(|waypoint| from < waypoint.time && waypoint.time < to),
+	);
+
+	if to > from {
+		result.push(end);
+	}
+
+	Some(result)
+}
+
+/// Appends `second` to `first`, shifting `second` in time so that it begins
+/// where `first` ends.
+///
+/// The last waypoint of `first` and the first of `second` become a single
+/// waypoint, so they must agree in position, velocity and acceleration to
+/// within `tolerance`; if they don't, the first mismatch is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::splice::{concatenate, Discontinuity};
+/// use motion_planning::timed::timed;
+/// use motion_planning::vec::Vec3d;
+/// use motion_planning::Pose3;
+///
+/// let at = |x| Pose3 {
+///     position: Vec3d(x, 0., 0.),
+///     velocity: Vec3d(0., 0., 0.),
+///     acceleration: Vec3d(0., 0., 0.),
+/// };
+///
+/// let joined = concatenate(&timed(&[at(0.), at(1.)]), &timed(&[at(1.), at(2.)]), 1e-9);
+/// assert_eq!(joined.unwrap()[2].time, 2.);
+///
+/// let gap = concatenate(&timed(&[at(0.), at(1.)]), &timed(&[at(3.), at(4.)]), 1e-9);
+/// assert_eq!(gap, Err(Discontinuity::Position(2.)));
+/// ```
+pub fn concatenate(
+	first: &[Timed<Pose3<Vec3d<f64>>>],
+	second: &[Timed<Pose3<Vec3d<f64>>>],
+	tolerance: f64,
+) -> Result<Vec<Timed<Pose3<Vec3d<f64>>>>, Discontinuity> {
+	let (last, head) = match (first.last(), second.first()) {
+		(Some(last), Some(head)) => (last, head),
+		_ => return Ok(first.iter().chain(second).copied().collect()),
+	};
+
+	let gap = |a: Vec3d<f64>, b: Vec3d<f64>| (a + b * -1.).norm();
+
+	let position = gap(last.pose.position, head.pose.position);
+	if position > tolerance {
+		return Err(Discontinuity::Position(position));
+	}
+
+	let velocity = gap(last.pose.velocity, head.pose.velocity);
+	if velocity > tolerance {
+		return Err(Discontinuity::Velocity(velocity));
+	}
+
+	let acceleration = gap(last.pose.acceleration, head.pose.acceleration);
+	if acceleration > tolerance {
+		return Err(Discontinuity::Acceleration(acceleration));
+	}
+
+	let offset = last.time - head.time;
+
+	Ok(
+		first
+			.iter()
+			.copied()
+			.chain(second[1..].iter().map(|waypoint| Timed {
+				time: waypoint.time + offset,
+				pose: waypoint.pose,
+			}))
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::timed::timed;
+	use super::super::Trajectory3;
+	use super::*;
+
+	fn waypoints() -> Vec<Timed<Pose3<Vec3d<f64>>>> {
+		timed(&[
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(0., 1., 0.),
+				acceleration: Vec3d(1., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(1., 1., 0.),
+				velocity: Vec3d(1., 0., 0.),
+				acceleration: Vec3d(0., -1., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 0., 1.),
+				velocity: Vec3d(0., -1., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+		])
+	}
+
+	fn assert_same_at(a: &Vec<Timed<Pose3<Vec3d<f64>>>>, b: &Vec<Timed<Pose3<Vec3d<f64>>>>, t: f64) {
+		let (p, q) = (a.position_at(t).unwrap(), b.position_at(t).unwrap());
+		assert_f64_roughly_eq!((p + q * -1.).norm(), 0., 1e-9);
+
+		let (p, q) = (a.acceleration_at(t).unwrap(), b.acceleration_at(t).unwrap());
+		assert_f64_roughly_eq!((p + q * -1.).norm(), 0., 1e-9);
+	}
+
+	#[test]
+	fn split_is_exact() {
+		let original = waypoints();
+		let cut = split(&original, 1.3).unwrap();
+
+		assert_eq!(cut.len(), 4);
+		assert_eq!(cut[2].time, 1.3);
+
+		for i in 0..=20 {
+			assert_same_at(&original, &cut, f64::from(i) / 10.);
+		}
+
+		assert_eq!(split(&original, 1.).unwrap(), original);
+		assert_eq!(split(&original, 2.5), None);
+	}
+
+	#[test]
+	fn trim_keeps_times() {
+		let original = waypoints();
+		let part = trim(&original, 0.5, 1.75).unwrap();
+
+		assert_eq!(
+			part.iter().map(|w| w.time).collect::<Vec<_>>(),
+			vec![0.5, 1., 1.75]
+		);
+
+		for i in 0..=5 {
+			assert_same_at(&original, &part, 0.5 + f64::from(i) * 0.25);
+		}
+
+		assert_eq!(trim(&original, 1.2, 1.2).unwrap().len(), 1);
+		assert_eq!(trim(&original, 1.5, 0.5), None);
+		assert_eq!(trim(&original, -1., 0.5), None);
+	}
+
+	#[test]
+	fn trim_and_concatenate_round_trip() {
+		let original = waypoints();
+		let head = trim(&original, 0., 0.8).unwrap();
+		let tail = trim(&original, 0.8, 2.).unwrap();
+
+		let joined = concatenate(&head, &tail, 1e-9).unwrap();
+
+		for i in 0..=20 {
+			assert_same_at(&original, &joined, f64::from(i) / 10.);
+		}
+	}
+
+	#[test]
+	fn concatenate_detects_discontinuities() {
+		let original = waypoints();
+		let mut shifted = original.clone();
+		shifted[0].pose.position = original[2].pose.position;
+		shifted[0].pose.velocity = original[2].pose.velocity;
+
+		assert!(matches!(
+			concatenate(&original, &shifted, 1e-9),
+			Err(Discontinuity::Acceleration(_))
+		));
+
+		let joined = concatenate(&original, &[], 1e-9).unwrap();
+		assert_eq!(joined, original);
+	}
+}
@@ -0,0 +1,895 @@
+//! Global spline solvers that fill in waypoint derivatives from positions.
+//!
+//! Piecewise Hermite curves are only as smooth as the derivatives given at
+//! their waypoints.  The solvers here pick those derivatives so the whole
+//! curve is as smooth as its degree allows, which is what the classical
+//! interpolating splines do.  As with the rest of the crate, every segment has
+//! unit duration.
+
+use core::ops::{Add, Mul};
+
+use super::{Pose2, Pose3};
+
+/// The condition imposed at the ends of a cubic spline, where there are no
+/// neighbouring segments to be continuous with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EndCondition<V> {
+	/// Zero acceleration at both ends.
+	Natural,
+	/// The given velocities at the start and end.
+	Clamped(V, V),
+	/// Continuous jerk across the second and second-to-last waypoints, so the
+	/// first two and last two segments are each a single cubic.
+	NotAKnot,
+	/// A closed curve, continuous in acceleration where it wraps around.  The
+	/// last position should repeat the first.  It is not read: the last
+	/// waypoint is placed at the first position, so the curve always closes.
+	Periodic,
+}
+
+/// Solves a tridiagonal system with the Thomas algorithm.
+///
+/// `lower[i]`, `diagonal[i]` and `upper[i]` are the coefficients of row `i`;
+/// `lower[0]` and `upper[n - 1]` are ignored.  As nothing is pivoted, no
+/// pivot may become zero, which holds for the nonsingular systems built in
+/// this module, including the not-a-knot ones whose end rows are not
+/// diagonally dominant.
+fn solve_tridiagonal<V>(lower: &[f64], diagonal: &[f64], upper: &[f64], rhs: &[V]) -> Vec<V>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let n = rhs.len();
+
+	let mut upper_prime: Vec<f64> = Vec::with_capacity(n);
+	let mut rhs_prime: Vec<V> = Vec::with_capacity(n);
+
+	for i in 0..n {
+		let (denominator, rhs_i) = if i == 0 {
+			(diagonal[0], rhs[0])
+		} else {
+			(
+				lower[i].mul_add(-upper_prime[i - 1], diagonal[i]),
+				rhs[i] + rhs_prime[i - 1] * -lower[i],
+			)
+		};
+
+		upper_prime.push(upper[i] / denominator);
+		rhs_prime.push(rhs_i * (1. / denominator));
+	}
+
+	let mut solution = rhs_prime;
+
+	for i in (0..n.saturating_sub(1)).rev() {
+		solution[i] = solution[i] + solution[i + 1] * -upper_prime[i];
+	}
+
+	solution
+}
+
+/// Solves a cyclic tridiagonal system, in which row `0` also has `corner_upper`
+/// in its last column and row `n - 1` has `corner_lower` in its first column.
+///
+/// With `u = (γ, 0, …, 0, corner_lower)` and `v = (1, 0, …, 0, corner_upper / γ)`,
+/// the system is the tridiagonal one with its first and last diagonal entries
+/// reduced by `uᵢvᵢ`, plus `u vᵀ`.
+///
+/// This uses the Sherman–Morrison formula to reduce the problem to two plain
+/// tridiagonal solves, and requires at least three rows.
+fn solve_cyclic_tridiagonal<V>(
+	lower: &[f64],
+	diagonal: &[f64],
+	upper: &[f64],
+	corner_upper: f64,
+	corner_lower: f64,
+	rhs: &[V],
+) -> Vec<V>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let n = rhs.len();
+	let gamma = -diagonal[0];
+
+	let mut modified = diagonal.to_vec();
+	modified[0] -= gamma;
+	modified[n - 1] -= corner_upper * corner_lower / gamma;
+
+	let x = solve_tridiagonal(lower, &modified, upper, rhs);
+
+	let mut u = vec![0.; n];
+	u[0] = gamma;
+	u[n - 1] = corner_lower;
+	let z = solve_tridiagonal(lower, &modified, upper, &u);
+
+	let v_last = corner_upper / gamma;
+	let v_dot_x = x[0] + x[n - 1] * v_last;
+	let v_dot_z = z[n - 1].mul_add(v_last, z[0]);
+
+	let correction = v_dot_x * (-1. / (1. + v_dot_z));
+
+	x.into_iter()
+		.zip(z)
+		.map(|(x_i, z_i)| x_i + correction * z_i)
+		.collect()
+}
+
+/// Computes `scale * (a - b)`.
+fn scaled_difference<V>(a: V, b: V, scale: f64) -> V
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	(a * scale) + (b * -scale)
+}
+
+/// Computes the velocities of a cubic spline through `positions`.
+fn cubic_velocities<V>(positions: &[V], end: EndCondition<V>) -> Vec<V>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let p = positions;
+	let n = p.len();
+
+	match (n, end) {
+		(0, _) => return Vec::new(),
+		(1, EndCondition::Clamped(start, _)) => return vec![start],
+		(1, _) => return vec![p[0] * 0.],
+		(2, EndCondition::NotAKnot) => {
+			let slope = scaled_difference(p[1], p[0], 1.);
+			return vec![slope, slope];
+		}
+		(3, EndCondition::NotAKnot) => {
+			// With only one interior waypoint, the spline is the parabola
+			// through all three positions.
+			return vec![
+				(p[0] * -1.5) + (p[1] * 2.) + (p[2] * -0.5),
+				scaled_difference(p[2], p[0], 0.5),
+				(p[0] * 0.5) + (p[1] * -2.) + (p[2] * 1.5),
+			];
+		}
+		(_, EndCondition::Periodic) => {
+			let m = n - 1;
+
+			if m < 3 {
+				// Two or fewer distinct positions on a loop are at rest.
+				return vec![p[0] * 0.; n];
+			}
+
+			let rhs: Vec<V> = (0..m)
+				.map(|i| scaled_difference(p[(i + 1) % m], p[(i + m - 1) % m], 3.))
+				.collect();
+
+			let mut velocities =
+				solve_cyclic_tridiagonal(&vec![1.; m], &vec![4.; m], &vec![1.; m], 1., 1., &rhs);
+			velocities.push(velocities[0]);
+
+			return velocities;
+		}
+		_ => {}
+	}
+
+	let mut lower = vec![1.; n];
+	let mut diagonal = vec![4.; n];
+	let mut upper = vec![1.; n];
+
+	let mut rhs: Vec<V> = (0..n)
+		.map(|i| scaled_difference(p[(i + 1).min(n - 1)], p[i.saturating_sub(1)], 3.))
+		.collect();
+
+	match end {
+		EndCondition::Natural => {
+			diagonal[0] = 2.;
+			diagonal[n - 1] = 2.;
+		}
+		EndCondition::Clamped(start, end) => {
+			diagonal[0] = 1.;
+			upper[0] = 0.;
+			rhs[0] = start;
+
+			lower[n - 1] = 0.;
+			diagonal[n - 1] = 1.;
+			rhs[n - 1] = end;
+		}
+		EndCondition::NotAKnot => {
+			diagonal[0] = 1.;
+			upper[0] = 2.;
+			rhs[0] = (p[0] * -2.5) + (p[1] * 2.) + (p[2] * 0.5);
+
+			lower[n - 1] = 2.;
+			diagonal[n - 1] = 1.;
+			rhs[n - 1] = (p[n - 1] * 2.5) + (p[n - 2] * -2.) + (p[n - 3] * -0.5);
+		}
+		EndCondition::Periodic => unreachable!(),
+	}
+
+	solve_tridiagonal(&lower, &diagonal, &upper, &rhs)
+}
+
+/// Builds the waypoints of the cubic spline through `positions` that is
+/// continuous in acceleration, with the given end condition.
+///
+/// The resulting waypoints, used as a [`Trajectory2`](crate::Trajectory2),
+/// trace the classical interpolating cubic spline with uniform knots.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::spline::{cubic, EndCondition};
+///
+/// // A cubic is its own not-a-knot spline, so the velocities are exact.
+/// let positions: Vec<f64> = (0..5).map(|t| f64::from(t).powi(3)).collect();
+/// let waypoints = cubic(&positions, EndCondition::NotAKnot);
+///
+/// for (t, waypoint) in waypoints.iter().enumerate() {
+///     let expected = 3. * (t as f64).powi(2);
+///     assert!((waypoint.velocity - expected).abs() < 1e-9);
+/// }
+/// ```
+pub fn cubic<V>(positions: &[V], end: EndCondition<V>) -> Vec<Pose2<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let mut waypoints: Vec<Pose2<V>> = positions
+		.iter()
+		.zip(cubic_velocities(positions, end))
+		.map(|(&position, velocity)| Pose2 { position, velocity })
+		.collect();
+
+	if let (EndCondition::Periodic, Some(&first)) = (end, positions.first()) {
+		if let Some(last) = waypoints.last_mut() {
+			last.position = first;
+		}
+	}
+
+	waypoints
+}
+
+/// The derivatives held fixed at one end of a quintic spline.
+///
+/// Derivatives left as `None` are free, and take whatever value minimizes the
+/// jerk of the spline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Boundary<V> {
+	pub velocity: Option<V>,
+	pub acceleration: Option<V>,
+}
+
+impl<V> Default for Boundary<V> {
+	fn default() -> Self {
+		Self {
+			velocity: None,
+			acceleration: None,
+		}
+	}
+}
+
+/// A 2-by-2 block of coefficients, acting on a waypoint's velocity and
+/// acceleration.
+type Block = [[f64; 2]; 2];
+
+fn block_inverse(m: &Block) -> Block {
+	let determinant = m[0][0].mul_add(m[1][1], -m[0][1] * m[1][0]);
+
+	[
+		[m[1][1] / determinant, -m[0][1] / determinant],
+		[-m[1][0] / determinant, m[0][0] / determinant],
+	]
+}
+
+fn block_product(a: &Block, b: &Block) -> Block {
+	let mut product = [[0.; 2]; 2];
+
+	for (i, row) in product.iter_mut().enumerate() {
+		for (j, entry) in row.iter_mut().enumerate() {
+			*entry = a[i][0].mul_add(b[0][j], a[i][1] * b[1][j]);
+		}
+	}
+
+	product
+}
+
+fn block_apply<V>(m: &Block, x: &(V, V)) -> (V, V)
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	(
+		(x.0 * m[0][0]) + (x.1 * m[0][1]),
+		(x.0 * m[1][0]) + (x.1 * m[1][1]),
+	)
+}
+
+/// Solves a block-tridiagonal system of 2-by-2 blocks by block elimination.
+///
+/// The system must be symmetric positive-definite, which the quintic spline
+/// system is.
+fn solve_block_tridiagonal<V>(
+	lower: &[Block],
+	diagonal: &[Block],
+	upper: &[Block],
+	rhs: &[(V, V)],
+) -> Vec<(V, V)>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let n = rhs.len();
+
+	let mut inverses: Vec<Block> = Vec::with_capacity(n);
+	let mut rhs_prime: Vec<(V, V)> = Vec::with_capacity(n);
+
+	for i in 0..n {
+		let (pivot, rhs_i) = if i == 0 {
+			(diagonal[0], rhs[0])
+		} else {
+			let w = block_product(&lower[i], &inverses[i - 1]);
+			let wu = block_product(&w, &upper[i - 1]);
+			let wr = block_apply(&w, &rhs_prime[i - 1]);
+
+			(
+				[
+					[diagonal[i][0][0] - wu[0][0], diagonal[i][0][1] - wu[0][1]],
+					[diagonal[i][1][0] - wu[1][0], diagonal[i][1][1] - wu[1][1]],
+				],
+				(rhs[i].0 + wr.0 * -1., rhs[i].1 + wr.1 * -1.),
+			)
+		};
+
+		inverses.push(block_inverse(&pivot));
+		rhs_prime.push(rhs_i);
+	}
+
+	let mut solution: Vec<(V, V)> = Vec::with_capacity(n);
+
+	for i in (0..n).rev() {
+		let r = if i == n - 1 {
+			rhs_prime[i]
+		} else {
+			let ux = block_apply(&upper[i], &solution[n - 2 - i]);
+			(rhs_prime[i].0 + ux.0 * -1., rhs_prime[i].1 + ux.1 * -1.)
+		};
+
+		solution.push(block_apply(&inverses[i], &r));
+	}
+
+	solution.reverse();
+	solution
+}
+
+/// Selects the velocity (`0`) or acceleration (`1`) half of a pair.
+fn component<V>(pair: &mut (V, V), k: usize) -> &mut V {
+	if k == 0 {
+		&mut pair.0
+	} else {
+		&mut pair.1
+	}
+}
+
+/// Computes the velocities and accelerations of a quintic spline through
+/// `positions`.
+///
+/// Each row pair below is the gradient of the integrated squared jerk with
+/// respect to a waypoint's velocity and acceleration, scaled by a third.  At
+/// an interior waypoint these are the jumps in snap and jerk between its two
+/// segments; at the ends they are the snap and jerk themselves.
+fn quintic_derivatives<V>(positions: &[V], start: Boundary<V>, end: Boundary<V>) -> Vec<(V, V)>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let p = positions;
+	let n = p.len();
+
+	if n == 0 {
+		return Vec::new();
+	}
+
+	let zero = p[0] * 0.;
+
+	if n == 1 {
+		return vec![(
+			start.velocity.unwrap_or(zero),
+			start.acceleration.unwrap_or(zero),
+		)];
+	}
+
+	let free =
+		|boundary: &Boundary<V>| boundary.velocity.is_none() && boundary.acceleration.is_none();
+
+	if n == 2 && free(&start) && free(&end) {
+		// Every parabola through two points has zero jerk, so pick the line.
+		let slope = scaled_difference(p[1], p[0], 1.);
+		return vec![(slope, zero), (slope, zero)];
+	}
+
+	let mut lower: Vec<Block> = vec![[[0.; 2]; 2]; n];
+	let mut diagonal: Vec<Block> = vec![[[0.; 2]; 2]; n];
+	let mut upper: Vec<Block> = vec![[[0.; 2]; 2]; n];
+	let mut rhs: Vec<(V, V)> = vec![(zero, zero); n];
+
+	for i in 0..n {
+		let mut velocity_rhs = zero;
+		let mut acceleration_rhs = zero;
+
+		// The segment arriving at waypoint `i`.
+		if i > 0 {
+			lower[i] = [[56., 8.], [-8., -1.]];
+			diagonal[i][0][0] += 64.;
+			diagonal[i][0][1] += -12.;
+			diagonal[i][1][0] += -12.;
+			diagonal[i][1][1] += 3.;
+
+			velocity_rhs = velocity_rhs + scaled_difference(p[i], p[i - 1], 120.);
+			acceleration_rhs = acceleration_rhs + scaled_difference(p[i - 1], p[i], 20.);
+		}
+
+		// The segment leaving waypoint `i`.
+		if i < n - 1 {
+			upper[i] = [[56., -8.], [8., -1.]];
+			diagonal[i][0][0] += 64.;
+			diagonal[i][0][1] += 12.;
+			diagonal[i][1][0] += 12.;
+			diagonal[i][1][1] += 3.;
+
+			velocity_rhs = velocity_rhs + scaled_difference(p[i + 1], p[i], 120.);
+			acceleration_rhs = acceleration_rhs + scaled_difference(p[i + 1], p[i], 20.);
+		}
+
+		rhs[i] = (velocity_rhs, acceleration_rhs);
+	}
+
+	// Fixed derivatives replace their row with an identity, and are moved to
+	// the right-hand side of the rows of their neighbours to keep the system
+	// symmetric.
+	let fixed = [
+		(0, 0, start.velocity),
+		(0, 1, start.acceleration),
+		(n - 1, 0, end.velocity),
+		(n - 1, 1, end.acceleration),
+	];
+
+	for &(i, k, value) in &fixed {
+		if let Some(value) = value {
+			let other = 1 - k;
+
+			*component(&mut rhs[i], other) =
+				*component(&mut rhs[i], other) + value * -diagonal[i][other][k];
+			diagonal[i][other][k] = 0.;
+			diagonal[i][k] = [0.; 2];
+			diagonal[i][k][k] = 1.;
+			*component(&mut rhs[i], k) = value;
+
+			if i > 0 {
+				lower[i][k] = [0.; 2];
+
+				for (r, row) in upper[i - 1].iter_mut().enumerate() {
+					*component(&mut rhs[i - 1], r) = *component(&mut rhs[i - 1], r) + value * -row[k];
+					row[k] = 0.;
+				}
+			}
+
+			if i < n - 1 {
+				upper[i][k] = [0.; 2];
+
+				for (r, row) in lower[i + 1].iter_mut().enumerate() {
+					*component(&mut rhs[i + 1], r) = *component(&mut rhs[i + 1], r) + value * -row[k];
+					row[k] = 0.;
+				}
+			}
+		}
+	}
+
+	solve_block_tridiagonal(&lower, &diagonal, &upper, &rhs)
+}
+
+/// Builds the waypoints of the quintic spline through `positions` that has
+/// the least integrated squared jerk.
+///
+/// Any velocity or acceleration given in `start` or `end` is matched exactly.
+/// The result is also the quintic spline continuous in jerk and snap (and so
+/// C4 as a whole) at every interior waypoint: minimizing jerk and maximizing
+/// smoothness lead to the same curve.  A free end gets zero jerk if its
+/// velocity is free, and zero snap if its acceleration is free.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::spline::{quintic, Boundary};
+/// use motion_planning::Trajectory3;
+///
+/// let rest = Boundary {
+///     velocity: Some(0.),
+///     acceleration: Some(0.),
+/// };
+/// let waypoints = quintic(&[0., 1., 3., 2.], rest, rest);
+///
+/// assert_eq!(waypoints[0].velocity, 0.);
+/// assert_eq!(waypoints.position_at(2.), Some(3.));
+/// ```
+pub fn quintic<V>(positions: &[V], start: Boundary<V>, end: Boundary<V>) -> Vec<Pose3<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	positions
+		.iter()
+		.zip(quintic_derivatives(positions, start, end))
+		.map(|(&position, (velocity, acceleration))| Pose3 {
+			position,
+			velocity,
+			acceleration,
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::vec::Vec3d;
+	use super::super::{assert_f64_roughly_eq, Trajectory2, Trajectory3};
+	use super::*;
+
+	/// The accelerations at the start and end of each segment.
+	fn accelerations(waypoints: &[Pose2<f64>]) -> Vec<(f64, f64)> {
+		waypoints
+			.windows(2)
+			.map(|pair| {
+				let (p0, m0) = (pair[0].position, pair[0].velocity);
+				let (p1, m1) = (pair[1].position, pair[1].velocity);
+
+				(
+					6. * (p1 - p0) - 4. * m0 - 2. * m1,
+					-6. * (p1 - p0) + 2. * m0 + 4. * m1,
+				)
+			})
+			.collect()
+	}
+
+	fn assert_c2(waypoints: &[Pose2<f64>]) {
+		for pair in accelerations(waypoints).windows(2) {
+			assert_f64_roughly_eq!(pair[0].1, pair[1].0, 1e-9);
+		}
+	}
+
+	const POSITIONS: [f64; 6] = [0., 1., -1., 2., 2.5, 0.];
+
+	#[test]
+	fn tridiagonal_solution() {
+		let x = solve_tridiagonal(&[0., 1., 1.], &[2., 3., 2.], &[1., 1., 0.], &[4., 10., 8.]);
+
+		// [2 1 0; 1 3 1; 0 1 2] * [1 2 3] = [4 10 8]
+		assert_f64_roughly_eq!(x[0], 1., 1e-12);
+		assert_f64_roughly_eq!(x[1], 2., 1e-12);
+		assert_f64_roughly_eq!(x[2], 3., 1e-12);
+	}
+
+	#[test]
+	fn cyclic_tridiagonal_solution() {
+		let x = solve_cyclic_tridiagonal(&[1.; 4], &[4.; 4], &[1.; 4], 1., 1., &[9., 7., 13., 19.]);
+
+		// Each row is x[i - 1] + 4 x[i] + x[i + 1], wrapping around.
+		assert_f64_roughly_eq!(x[0], 1., 1e-12);
+		assert_f64_roughly_eq!(x[1], 1., 1e-12);
+		assert_f64_roughly_eq!(x[2], 2., 1e-12);
+		assert_f64_roughly_eq!(x[3], 4., 1e-12);
+
+		// With different corners:
+		// [4 1 0 2; 1 4 1 0; 0 1 4 1; 3 0 1 4] * [1 -1 2 0.5] = [4 -1 7.5 7]
+		let x = solve_cyclic_tridiagonal(&[1.; 4], &[4.; 4], &[1.; 4], 2., 3., &[4., -1., 7.5, 7.]);
+
+		assert_f64_roughly_eq!(x[0], 1., 1e-12);
+		assert_f64_roughly_eq!(x[1], -1., 1e-12);
+		assert_f64_roughly_eq!(x[2], 2., 1e-12);
+		assert_f64_roughly_eq!(x[3], 0.5, 1e-12);
+	}
+
+	#[test]
+	fn natural_is_c2_with_free_ends() {
+		let waypoints = cubic(&POSITIONS, EndCondition::Natural);
+		let accelerations = accelerations(&waypoints);
+
+		assert_c2(&waypoints);
+		assert_f64_roughly_eq!(accelerations[0].0, 0., 1e-9);
+		assert_f64_roughly_eq!(accelerations[accelerations.len() - 1].1, 0., 1e-9);
+	}
+
+	#[test]
+	fn natural_reproduces_lines() {
+		let waypoints = cubic(&[1., 3., 5., 7.], EndCondition::Natural);
+
+		for waypoint in &waypoints {
+			assert_f64_roughly_eq!(waypoint.velocity, 2., 1e-12);
+		}
+	}
+
+	#[test]
+	fn clamped_matches_end_velocities() {
+		let waypoints = cubic(&POSITIONS, EndCondition::Clamped(1., -2.));
+
+		assert_c2(&waypoints);
+		assert_f64_roughly_eq!(waypoints[0].velocity, 1.);
+		assert_f64_roughly_eq!(waypoints[5].velocity, -2.);
+	}
+
+	#[test]
+	fn clamped_reproduces_quadratics() {
+		let positions: Vec<f64> = (0..5).map(|t| f64::from(t).powi(2)).collect();
+		let waypoints = cubic(&positions, EndCondition::Clamped(0., 8.));
+
+		for (t, waypoint) in waypoints.iter().enumerate() {
+			assert_f64_roughly_eq!(waypoint.velocity, 2. * t as f64, 1e-12);
+		}
+	}
+
+	#[test]
+	fn not_a_knot_is_c2() {
+		assert_c2(&cubic(&POSITIONS, EndCondition::NotAKnot));
+	}
+
+	#[test]
+	fn not_a_knot_few_positions() {
+		let line = cubic(&[1., 2.], EndCondition::NotAKnot);
+		assert_eq!(line[0].velocity, 1.);
+		assert_eq!(line[1].velocity, 1.);
+
+		let parabola = cubic(&[0., 1., 4.], EndCondition::NotAKnot);
+		assert_f64_roughly_eq!(parabola[0].velocity, 0.);
+		assert_f64_roughly_eq!(parabola[1].velocity, 2.);
+		assert_f64_roughly_eq!(parabola[2].velocity, 4.);
+	}
+
+	#[test]
+	fn periodic_wraps_smoothly() {
+		let positions: Vec<Vec3d<f64>> = (0..=8)
+			.map(|i| {
+				let theta = f64::from(i) * core::f64::consts::PI / 4.;
+				Vec3d(theta.cos(), theta.sin(), 0.)
+			})
+			.collect();
+
+		let waypoints = cubic(&positions, EndCondition::Periodic);
+
+		assert_eq!(waypoints[0].velocity, waypoints[8].velocity);
+
+		// By symmetry every tangent is perpendicular to its position.
+		for waypoint in &waypoints {
+			assert_f64_roughly_eq!(waypoint.velocity.dot(&waypoint.position), 0., 1e-12);
+		}
+
+		let xs: Vec<Pose2<f64>> = waypoints
+			.iter()
+			.map(|w| Pose2 {
+				position: w.position.0,
+				velocity: w.velocity.0,
+			})
+			.collect();
+		let accelerations = accelerations(&xs);
+
+		assert_c2(&xs);
+		assert_f64_roughly_eq!(accelerations[0].0, accelerations[7].1, 1e-9);
+	}
+
+	#[test]
+	fn periodic_few_positions() {
+		assert_eq!(
+			cubic(&[0., 1., 0.], EndCondition::Periodic),
+			vec![
+				Pose2 {
+					position: 0.,
+					velocity: 0.
+				},
+				Pose2 {
+					position: 1.,
+					velocity: 0.
+				},
+				Pose2 {
+					position: 0.,
+					velocity: 0.
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn periodic_closes_on_first_position() {
+		let closed = cubic(&[0., 1., -1., 2., 0.], EndCondition::Periodic);
+		let open = cubic(&[0., 1., -1., 2., 7.], EndCondition::Periodic);
+
+		assert_eq!(open, closed);
+		assert_eq!(open[4].position, 0.);
+	}
+
+	#[test]
+	fn degenerate_lengths() {
+		assert_eq!(cubic::<f64>(&[], EndCondition::Natural), Vec::new());
+		assert_eq!(cubic(&[2.], EndCondition::Natural)[0].velocity, 0.);
+		assert_eq!(cubic(&[2.], EndCondition::Clamped(3., 4.))[0].velocity, 3.);
+	}
+
+	#[test]
+	fn passes_through_positions() {
+		let waypoints = cubic(&POSITIONS, EndCondition::Natural);
+
+		for (i, position) in POSITIONS.iter().enumerate() {
+			assert_eq!(waypoints.position_at(i as f64), Some(*position));
+		}
+	}
+
+	fn sampled(f: impl Fn(f64) -> f64, n: i32) -> Vec<f64> {
+		(0..n).map(|t| f(f64::from(t))).collect()
+	}
+
+	fn clamped(velocity: f64, acceleration: f64) -> Boundary<f64> {
+		Boundary {
+			velocity: Some(velocity),
+			acceleration: Some(acceleration),
+		}
+	}
+
+	/// Approximates the jerk just after `t` by a forward difference.
+	fn jerk_after(waypoints: &Vec<Pose3<f64>>, t: f64) -> f64 {
+		let h = 1e-6;
+		(waypoints.acceleration_at(t + h).unwrap() - waypoints.acceleration_at(t).unwrap()) / h
+	}
+
+	#[test]
+	fn block_tridiagonal_solution() {
+		let identity = [[1., 0.], [0., 1.]];
+		let zero = [[0.; 2]; 2];
+
+		let x = solve_block_tridiagonal(
+			&[zero, identity],
+			&[[[4., 1.], [1., 3.]], [[2., 0.], [0., 2.]]],
+			&[identity, zero],
+			&[(7., 8.), (5., 8.)],
+		);
+
+		let expected = [(7., 8.), (5., 8.)];
+		let residual = |i: usize, j: usize| match (i, j) {
+			(0, 0) => 4. * x[0].0 + x[0].1 + x[1].0,
+			(0, 1) => x[0].0 + 3. * x[0].1 + x[1].1,
+			(1, 0) => x[0].0 + 2. * x[1].0,
+			_ => x[0].1 + 2. * x[1].1,
+		};
+
+		assert_f64_roughly_eq!(residual(0, 0), expected[0].0, 1e-12);
+		assert_f64_roughly_eq!(residual(0, 1), expected[0].1, 1e-12);
+		assert_f64_roughly_eq!(residual(1, 0), expected[1].0, 1e-12);
+		assert_f64_roughly_eq!(residual(1, 1), expected[1].1, 1e-12);
+	}
+
+	#[test]
+	fn quintic_reproduces_quintics_when_clamped() {
+		let f = |t: f64| t.powi(5) - 3. * t.powi(3) + t;
+		let df = |t: f64| 5. * t.powi(4) - 9. * t.powi(2) + 1.;
+		let ddf = |t: f64| 20. * t.powi(3) - 18. * t;
+
+		let waypoints = quintic(
+			&sampled(f, 5),
+			clamped(df(0.), ddf(0.)),
+			clamped(df(4.), ddf(4.)),
+		);
+
+		for (t, waypoint) in waypoints.iter().enumerate() {
+			let t = t as f64;
+			assert_f64_roughly_eq!(waypoint.velocity, df(t), 1e-8);
+			assert_f64_roughly_eq!(waypoint.acceleration, ddf(t), 1e-8);
+		}
+
+		for i in 0..=40 {
+			let t = f64::from(i) / 10.;
+			assert_f64_roughly_eq!(waypoints.position_at(t).unwrap(), f(t), 1e-8);
+		}
+	}
+
+	#[test]
+	fn quintic_reproduces_quadratics_when_free() {
+		let waypoints = quintic(
+			&sampled(|t| 2. * t * t - t, 6),
+			Boundary::default(),
+			Boundary::default(),
+		);
+
+		for (t, waypoint) in waypoints.iter().enumerate() {
+			assert_f64_roughly_eq!(waypoint.velocity, 4. * t as f64 - 1., 1e-9);
+			assert_f64_roughly_eq!(waypoint.acceleration, 4., 1e-9);
+		}
+	}
+
+	#[test]
+	fn quintic_is_continuous_in_jerk() {
+		let waypoints = quintic(
+			&[0., 1., -1., 2., 2.5, 0.],
+			clamped(0., 0.),
+			Boundary::default(),
+		);
+
+		for i in 1..5 {
+			let t = f64::from(i);
+			let h = 1e-6;
+			let before =
+				(waypoints.acceleration_at(t).unwrap() - waypoints.acceleration_at(t - h).unwrap()) / h;
+			let after = jerk_after(&waypoints, t);
+
+			assert_f64_roughly_eq!(before, after, 1e-3);
+		}
+
+		// A free end has no jerk.
+		let end = waypoints.len() as f64 - 1.;
+		let h = 1e-6;
+		let jerk_at_end =
+			(waypoints.acceleration_at(end).unwrap() - waypoints.acceleration_at(end - h).unwrap()) / h;
+		assert_f64_roughly_eq!(jerk_at_end, 0., 1e-3);
+	}
+
+	#[test]
+	fn quintic_partial_boundaries() {
+		let start = Boundary {
+			velocity: Some(1.),
+			acceleration: None,
+		};
+		let end = Boundary {
+			velocity: None,
+			acceleration: Some(-1.),
+		};
+
+		let waypoints = quintic(&[0., 2., 1., 3.], start, end);
+
+		assert_f64_roughly_eq!(waypoints[0].velocity, 1.);
+		assert_f64_roughly_eq!(waypoints[3].acceleration, -1.);
+
+		// With only the velocity held, the jerk at the start is free to vanish.
+		assert_f64_roughly_eq!(jerk_after(&waypoints, 0.), 0., 1e-3);
+	}
+
+	#[test]
+	fn quintic_minimizes_jerk() {
+		let positions = [0., 1., -1., 2.];
+		let waypoints = quintic(&positions, clamped(0., 0.), clamped(0., 0.));
+
+		let energy = |waypoints: &Vec<Pose3<f64>>| -> f64 {
+			let steps = 3000;
+			let h = 3. / f64::from(steps);
+			(0..steps)
+				.map(|i| jerk_after(waypoints, f64::from(i) * h + h / 2.).powi(2) * h)
+				.sum()
+		};
+
+		let optimum = energy(&waypoints);
+
+		for i in 1..3 {
+			for delta in &[-0.1, 0.1] {
+				let mut perturbed = waypoints.clone();
+				perturbed[i].velocity += delta;
+				assert!(energy(&perturbed) > optimum);
+
+				let mut perturbed = waypoints.clone();
+				perturbed[i].acceleration += delta;
+				assert!(energy(&perturbed) > optimum);
+			}
+		}
+	}
+
+	#[test]
+	fn quintic_degenerate_lengths() {
+		assert_eq!(
+			quintic::<f64>(&[], Boundary::default(), Boundary::default()),
+			Vec::new()
+		);
+		assert_eq!(
+			quintic(&[1.], clamped(2., 3.), Boundary::default()),
+			vec![Pose3 {
+				position: 1.,
+				velocity: 2.,
+				acceleration: 3.
+			}]
+		);
+
+		let line = quintic(&[1., 3.], Boundary::default(), Boundary::default());
+		assert_f64_roughly_eq!(line[0].velocity, 2., 1e-12);
+		assert_f64_roughly_eq!(line[1].acceleration, 0., 1e-12);
+	}
+
+	#[test]
+	fn quintic_vectors() {
+		let positions = [Vec3d(0., 0., 0.), Vec3d(1., 2., 0.), Vec3d(2., 4., 1.)];
+		let waypoints = quintic(&positions, Boundary::default(), Boundary::default());
+
+		for (i, position) in positions.iter().enumerate() {
+			assert_eq!(waypoints.position_at(i as f64), Some(*position));
+		}
+	}
+}
@@ -0,0 +1,319 @@
+//! Tangent estimation for waypoints given only by their positions.
+//!
+//! Each builder here takes a list of positions and estimates a velocity at
+//! every one of them from its neighbours, producing waypoints that can be used
+//! directly as a [`Trajectory2`](crate::Trajectory2).  Segments have unit
+//! duration, so the differences between consecutive positions are also the
+//! average velocities over each segment.
+//!
+//! The first and last positions have only one neighbour.  Builders treat them
+//! as if the curve continued in a straight line past the end, so the missing
+//! difference is taken to be the same as the one that exists.
+
+use core::ops::{Add, Mul};
+
+use super::Pose2;
+
+/// The tension, continuity and bias parameters of a Kochanek–Bartels spline.
+///
+/// All three are zero for a Catmull-Rom spline.  Tension shortens (positive)
+/// or lengthens (negative) the tangents, continuity makes the corner at a
+/// waypoint sharper (nonzero values), and bias skews the tangent towards the
+/// incoming (positive) or outgoing (negative) direction.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tcb {
+	pub tension: f64,
+	pub continuity: f64,
+	pub bias: f64,
+}
+
+/// The kind of finite difference used by [`finite_difference`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difference {
+	/// The difference to the next position.
+	Forward,
+	/// The difference from the previous position.
+	Backward,
+	/// The mean of the forward and backward differences.
+	Central,
+}
+
+/// Computes the differences into and out of every position, extrapolating
+/// past the ends.
+fn differences<V>(positions: &[V]) -> Vec<(V, V)>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let secants: Vec<V> = positions
+		.windows(2)
+		.map(|pair| pair[1] + pair[0] * -1.)
+		.collect();
+
+	match positions.len() {
+		0 => Vec::new(),
+		1 => vec![(positions[0] * 0., positions[0] * 0.)],
+		length => (0..length)
+			.map(|i| {
+				let incoming = secants[i.saturating_sub(1)];
+				let outgoing = secants[i.min(length - 2)];
+
+				(incoming, outgoing)
+			})
+			.collect(),
+	}
+}
+
+/// Pairs each position with a velocity.
+fn with_velocities<V>(positions: &[V], velocities: impl Iterator<Item = V>) -> Vec<Pose2<V>>
+where
+	V: Copy,
+{
+	positions
+		.iter()
+		.zip(velocities)
+		.map(|(&position, velocity)| Pose2 { position, velocity })
+		.collect()
+}
+
+/// Builds waypoints whose velocities follow a Catmull-Rom spline.
+///
+/// The velocity at each waypoint is half the difference between its two
+/// neighbours.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::tangents::catmull_rom;
+/// use motion_planning::Trajectory2;
+///
+/// let waypoints = catmull_rom(&[0., 1., 4., 9.]);
+///
+/// assert_eq!(waypoints[1].velocity, 2.);
+/// assert_eq!(waypoints.position_at(2.), Some(4.));
+/// ```
+pub fn catmull_rom<V>(positions: &[V]) -> Vec<Pose2<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	cardinal(positions, 0.)
+}
+
+/// Builds waypoints whose velocities follow a cardinal spline with the given
+/// `tension`.
+///
+/// A tension of `0` gives a Catmull-Rom spline, and a tension of `1` gives
+/// zero velocity at every waypoint.
+pub fn cardinal<V>(positions: &[V], tension: f64) -> Vec<Pose2<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let scale = (1. - tension) / 2.;
+
+	with_velocities(
+		positions,
+		differences(positions)
+			.into_iter()
+			.map(|(incoming, outgoing)| (incoming + outgoing) * scale),
+	)
+}
+
+/// Computes the incoming and outgoing tangents of a Kochanek–Bartels spline
+/// at each position.
+///
+/// The incoming tangent is the one with which the curve arrives at a
+/// position, and the outgoing tangent is the one with which it leaves.  They
+/// differ only when `tcb.continuity` is nonzero.
+pub fn kochanek_bartels_tangents<V>(positions: &[V], tcb: &Tcb) -> Vec<(V, V)>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	let Tcb {
+		tension: t,
+		continuity: c,
+		bias: b,
+	} = *tcb;
+
+	differences(positions)
+		.into_iter()
+		.map(|(incoming, outgoing)| {
+			let arriving = (incoming * ((1. - t) * (1. + b) * (1. - c) / 2.))
+				+ (outgoing * ((1. - t) * (1. - b) * (1. + c) / 2.));
+			let leaving = (incoming * ((1. - t) * (1. + b) * (1. + c) / 2.))
+				+ (outgoing * ((1. - t) * (1. - b) * (1. - c) / 2.));
+
+			(arriving, leaving)
+		})
+		.collect()
+}
+
+/// Builds waypoints whose velocities follow a Kochanek–Bartels spline.
+///
+/// A [`Pose2`] holds a single velocity, so it can only represent the corner
+/// that a nonzero `tcb.continuity` puts at each waypoint approximately.  The
+/// waypoints take the outgoing tangents; use [`kochanek_bartels_tangents`] to
+/// get both sides exactly.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::tangents::{catmull_rom, kochanek_bartels, Tcb};
+///
+/// let positions = [0., 1., 4., 9.];
+///
+/// assert_eq!(
+///     kochanek_bartels(&positions, &Tcb::default()),
+///     catmull_rom(&positions)
+/// );
+/// ```
+pub fn kochanek_bartels<V>(positions: &[V], tcb: &Tcb) -> Vec<Pose2<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	with_velocities(
+		positions,
+		kochanek_bartels_tangents(positions, tcb)
+			.into_iter()
+			.map(|(_, leaving)| leaving),
+	)
+}
+
+/// Builds waypoints whose velocities are finite differences of the positions.
+///
+/// Because segments have unit duration, [`Difference::Central`] gives the
+/// same velocities as [`catmull_rom`].
+pub fn finite_difference<V>(positions: &[V], difference: Difference) -> Vec<Pose2<V>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	with_velocities(
+		positions,
+		differences(positions)
+			.into_iter()
+			.map(|(incoming, outgoing)| match difference {
+				Difference::Forward => outgoing,
+				Difference::Backward => incoming,
+				Difference::Central => (incoming + outgoing) * 0.5,
+			}),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::vec::Vec3d;
+	use super::super::{assert_f64_roughly_eq, Trajectory2};
+	use super::*;
+
+	const SQUARES: [f64; 4] = [0., 1., 4., 9.];
+
+	fn velocities(waypoints: &[Pose2<f64>]) -> Vec<f64> {
+		waypoints.iter().map(|pose| pose.velocity).collect()
+	}
+
+	#[test]
+	fn empty_and_single() {
+		assert_eq!(catmull_rom::<f64>(&[]), Vec::new());
+		assert_eq!(
+			catmull_rom(&[3.]),
+			vec![Pose2 {
+				position: 3.,
+				velocity: 0.
+			}]
+		);
+	}
+
+	#[test]
+	fn catmull_rom_velocities() {
+		assert_eq!(velocities(&catmull_rom(&SQUARES)), vec![1., 2., 4., 5.]);
+	}
+
+	#[test]
+	fn catmull_rom_interpolates_vectors() {
+		let positions = [
+			Vec3d(0., 0., 0.),
+			Vec3d(1., 0., 0.),
+			Vec3d(1., 1., 0.),
+			Vec3d(0., 1., 1.),
+		];
+		let waypoints = catmull_rom(&positions);
+
+		assert_eq!(waypoints[1].velocity, Vec3d(0.5, 0.5, 0.));
+
+		for (i, position) in positions.iter().enumerate() {
+			assert_eq!(waypoints.position_at(i as f64), Some(*position));
+		}
+	}
+
+	#[test]
+	fn cardinal_tension() {
+		assert_eq!(velocities(&cardinal(&SQUARES, 0.5)), vec![0.5, 1., 2., 2.5]);
+		assert_eq!(velocities(&cardinal(&SQUARES, 1.)), vec![0., 0., 0., 0.]);
+	}
+
+	#[test]
+	fn kochanek_bartels_bias() {
+		let tcb = Tcb {
+			bias: 1.,
+			..Tcb::default()
+		};
+
+		// Full bias takes the tangent entirely from the incoming difference.
+		assert_eq!(
+			velocities(&kochanek_bartels(&SQUARES, &tcb)),
+			vec![1., 1., 3., 5.]
+		);
+	}
+
+	#[test]
+	fn kochanek_bartels_continuity_splits_tangents() {
+		let tcb = Tcb {
+			continuity: -1.,
+			..Tcb::default()
+		};
+
+		// Continuity of -1 makes each side follow its own difference.
+		let tangents = kochanek_bartels_tangents(&SQUARES, &tcb);
+		assert_eq!(tangents[1], (1., 3.));
+		assert_eq!(tangents[2], (3., 5.));
+
+		assert_eq!(
+			velocities(&kochanek_bartels(&SQUARES, &tcb)),
+			vec![1., 3., 5., 5.]
+		);
+	}
+
+	#[test]
+	fn kochanek_bartels_tension() {
+		let tcb = Tcb {
+			tension: 0.5,
+			..Tcb::default()
+		};
+
+		assert_eq!(kochanek_bartels(&SQUARES, &tcb), cardinal(&SQUARES, 0.5));
+	}
+
+	#[test]
+	fn finite_differences() {
+		assert_eq!(
+			velocities(&finite_difference(&SQUARES, Difference::Forward)),
+			vec![1., 3., 5., 5.]
+		);
+		assert_eq!(
+			velocities(&finite_difference(&SQUARES, Difference::Backward)),
+			vec![1., 1., 3., 5.]
+		);
+		assert_eq!(
+			finite_difference(&SQUARES, Difference::Central),
+			catmull_rom(&SQUARES)
+		);
+	}
+
+	#[test]
+	fn straight_line_is_reproduced_exactly() {
+		let waypoints = catmull_rom(&[0., 2., 4., 6.]);
+
+		for i in 0..=30 {
+			let t = f64::from(i) / 10.;
+			assert_f64_roughly_eq!(waypoints.position_at(t).unwrap(), 2. * t, 1e-12);
+		}
+	}
+}
@@ -169,3 +169,59 @@ fn all_set_points_hit() {
 	assert_eq!(segment.position_at(2.0), Some(segment[2].position));
 	assert_eq!(segment.velocity_at(2.0), Some(segment[2].velocity));
 }
+
+#[test]
+fn septic_all_set_points_hit() {
+	let segment = vec![
+		Pose4 {
+			position: Vec3d(0.0f64, 0.0, 0.0),
+			velocity: Vec3d(0.0, 1.0, 0.0),
+			acceleration: Vec3d(1.0, 0.0, 0.0),
+			jerk: Vec3d(0.0, 0.0, 1.0),
+		},
+		Pose4 {
+			position: Vec3d(1.0f64, 1.0, 0.0),
+			velocity: Vec3d(1.0, 0.0, 0.0),
+			acceleration: Vec3d(0.0, -1.0, 0.0),
+			jerk: Vec3d(0.0, 0.0, 0.0),
+		},
+	];
+
+	for (t, pose) in [(0.0, &segment[0]), (1.0, &segment[1])].iter() {
+		assert_eq!(segment.position_at(*t), Some(pose.position));
+		assert_eq!(segment.velocity_at(*t), Some(pose.velocity));
+		assert_eq!(segment.acceleration_at(*t), Some(pose.acceleration));
+		assert_eq!(segment.jerk_at(*t), Some(pose.jerk));
+	}
+}
+
+#[test]
+fn septic_position_correct_straight_line() {
+	let segment = vec![
+		Pose4 {
+			position: Vec3d(0.0f64, 0.0, 0.0),
+			velocity: Vec3d(0.0, 0.0, 0.0),
+			acceleration: Vec3d(0.0, 0.0, 0.0),
+			jerk: Vec3d(0.0, 0.0, 0.0),
+		},
+		Pose4 {
+			position: Vec3d(0.0f64, 1.0, 0.0),
+			velocity: Vec3d(0.0, 0.0, 0.0),
+			acceleration: Vec3d(0.0, 0.0, 0.0),
+			jerk: Vec3d(0.0, 0.0, 0.0),
+		},
+	];
+
+	assert_eq!(segment.position_at(0.5), Some(Vec3d(0.0, 0.5, 0.0)));
+}
+
+#[test]
+fn septic_length_zero() {
+	let segment: Vec<Pose4<Vec3d<f64>>> = Vec::new();
+
+	assert_eq!(segment.get_segment(0.0), None);
+	assert_eq!(segment.position_at(0.0), None);
+	assert_eq!(segment.velocity_at(0.0), None);
+	assert_eq!(segment.acceleration_at(0.0), None);
+	assert_eq!(segment.jerk_at(0.0), None);
+}
@@ -0,0 +1,476 @@
+//! Trajectories whose waypoints are reached at given times.
+//!
+//! A plain `Vec<Pose3<V>>` gives every segment unit duration, so `t` doubles
+//! as a waypoint index.  Wrapping each waypoint in [`Timed`] instead lets the
+//! segments last however long they need to, with `t` measured in the same
+//! units as the waypoint times.  Velocities and accelerations are then true
+//! time-derivatives, whatever the durations.
+
+use core::ops::{Add, Mul};
+
+use super::hermite::{
+	cubic::{h_3, h_3p},
+	quintic::{h_5, h_5p, h_5pp},
+	septic::{h_7, h_7p, h_7pp, h_7ppp},
+};
+use super::{eval2, eval3, eval4};
+use super::{Pose2, Pose3, Pose4};
+use super::{Segment2, Segment3, Segment4};
+use super::{Trajectory2, Trajectory3, Trajectory4};
+
+/// A waypoint `pose`, reached at `time`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timed<P> {
+	pub time: f64,
+	pub pose: P,
+}
+
+/// Gives each waypoint in `poses` a time equal to its index, which is how a
+/// plain waypoint vector is timed.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::timed::timed;
+/// use motion_planning::Pose2;
+///
+/// let waypoints = timed(&[Pose2 { position: 0., velocity: 1. }; 3]);
+///
+/// assert_eq!(waypoints[2].time, 2.);
+/// ```
+pub fn timed<P: Copy>(poses: &[P]) -> Vec<Timed<P>> {
+	poses
+		.iter()
+		.enumerate()
+		.map(|(i, &pose)| Timed {
+			time: i as f64,
+			pose,
+		})
+		.collect()
+}
+
+/// Finds the segment of `waypoints` containing `t`.
+///
+/// Returns the indices of the waypoints that begin and end the segment, the
+/// fraction of the way through the segment that `t` falls, and the segment's
+/// duration.
+/// At a waypoint's time, the waypoint itself is returned as a segment of no
+/// length, so that evaluating there gives back the waypoint exactly.  Times
+/// outside the trajectory give `None`.
+///
+/// Use this where every derivative evaluated is one the waypoints give, so
+/// that the waypoint is the answer; otherwise use [`find_segment`].
+pub(crate) fn locate<P>(waypoints: &[Timed<P>], t: f64) -> Option<(usize, usize, f64, f64)> {
+	let first = waypoints.first()?;
+	let last = waypoints.last()?;
+
+	if !(first.time..=last.time).contains(&t) {
+		return None;
+	}
+
+	// The index of the first waypoint strictly after `t`.
+	let succ = waypoints.partition_point(|waypoint| waypoint.time <= t);
+	let prec = succ - 1;
+
+	if waypoints[prec].time == t || succ == waypoints.len() {
+		return Some((prec, prec, 0., 1.));
+	}
+
+	let duration = waypoints[succ].time - waypoints[prec].time;
+
+	Some((prec, succ, (t - waypoints[prec].time) / duration, duration))
+}
+
+/// Finds the segment of `waypoints` containing `t`, like [`locate`], but
+/// always as a segment between two waypoints.
+///
+/// At a waypoint's time the segment that begins there is chosen, or for the
+/// last waypoint the one that ends there.  Use this where something beyond
+/// the derivatives a waypoint gives is evaluated, such as a quadrotor's jerk
+/// or a car's steering, as those may jump at the waypoint and a segment of
+/// no length has none.
+pub(crate) fn find_segment<P>(waypoints: &[Timed<P>], t: f64) -> Option<(usize, usize, f64, f64)> {
+	let first = waypoints.first()?;
+	let last = waypoints.last()?;
+
+	if !(first.time..=last.time).contains(&t) {
+		return None;
+	}
+
+	if waypoints.len() == 1 {
+		return Some((0, 0, 0., 1.));
+	}
+
+	// The first waypoint after `t`, or the last waypoint.
+	let inner = &waypoints[1..waypoints.len() - 1];
+	let succ = 1 + inner.partition_point(|waypoint| waypoint.time <= t);
+	let prec = succ - 1;
+	let duration = waypoints[succ].time - waypoints[prec].time;
+
+	if duration <= 0. {
+		return Some((prec, prec, 0., 1.));
+	}
+
+	Some((prec, succ, (t - waypoints[prec].time) / duration, duration))
+}
+
+/// Finds the segment containing `t` among `count` waypoints timed by their
+/// indices, as in a plain waypoint vector, choosing the same segment at a
+/// waypoint as [`find_segment`] does.
+pub(crate) fn find_untimed_segment(count: usize, t: f64) -> Option<(usize, usize, f64, f64)> {
+	if count == 0 || !(0_f64..=(count - 1) as f64).contains(&t) {
+		return None;
+	}
+
+	if count == 1 {
+		return Some((0, 0, 0., 1.));
+	}
+
+	let prec = (t.floor() as usize).min(count - 2);
+
+	Some((prec, prec + 1, t - prec as f64, 1.))
+}
+
+impl<V> Trajectory2<V> for Vec<Timed<Pose2<V>>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	fn get_segment(&self, t: f64) -> Option<Segment2<'_, V>> {
+		let (prec, succ, u, _) = locate(self, t)?;
+
+		Some(Segment2(u, &self[prec].pose, &self[succ].pose))
+	}
+
+	fn position_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval2(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_3,
+			0,
+		))
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval2(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_3p,
+			1,
+		))
+	}
+}
+
+impl<V> Trajectory3<V> for Vec<Timed<Pose3<V>>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	fn get_segment(&self, t: f64) -> Option<Segment3<'_, V>> {
+		let (prec, succ, u, _) = locate(self, t)?;
+
+		Some(Segment3(u, &self[prec].pose, &self[succ].pose))
+	}
+
+	fn position_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval3(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_5,
+			0,
+		))
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval3(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_5p,
+			1,
+		))
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval3(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_5pp,
+			2,
+		))
+	}
+}
+
+impl<V> Trajectory4<V> for Vec<Timed<Pose4<V>>>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	fn get_segment(&self, t: f64) -> Option<Segment4<'_, V>> {
+		let (prec, succ, u, _) = locate(self, t)?;
+
+		Some(Segment4(u, &self[prec].pose, &self[succ].pose))
+	}
+
+	fn position_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval4(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_7,
+			0,
+		))
+	}
+
+	fn velocity_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval4(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_7p,
+			1,
+		))
+	}
+
+	fn acceleration_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval4(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_7pp,
+			2,
+		))
+	}
+
+	fn jerk_at(&self, t: f64) -> Option<V> {
+		let (prec, succ, u, duration) = locate(self, t)?;
+
+		Some(eval4(
+			u,
+			&self[prec].pose,
+			&self[succ].pose,
+			duration,
+			h_7ppp,
+			3,
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::vec::Vec3d;
+	use super::*;
+
+	fn waypoints() -> Vec<Pose3<Vec3d<f64>>> {
+		vec![
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(0., 1., 0.),
+				acceleration: Vec3d(1., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(1., 1., 0.),
+				velocity: Vec3d(1., 0., 0.),
+				acceleration: Vec3d(0., -1., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 0., 1.),
+				velocity: Vec3d(0., -1., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+		]
+	}
+
+	#[test]
+	fn unit_times_match_untimed() {
+		let untimed = waypoints();
+		let timed = timed(&untimed);
+
+		for i in 0..=20 {
+			let t = f64::from(i) / 10.;
+
+			assert_eq!(timed.position_at(t), untimed.position_at(t));
+			assert_eq!(timed.velocity_at(t), untimed.velocity_at(t));
+			assert_eq!(timed.acceleration_at(t), untimed.acceleration_at(t));
+		}
+	}
+
+	#[test]
+	fn hits_waypoints_at_their_times() {
+		let poses = waypoints();
+		let timed: Vec<Timed<Pose3<Vec3d<f64>>>> = poses
+			.iter()
+			.zip(&[1., 1.5, 4.])
+			.map(|(&pose, &time)| Timed { time, pose })
+			.collect();
+
+		for waypoint in &timed {
+			assert_eq!(
+				timed.position_at(waypoint.time),
+				Some(waypoint.pose.position)
+			);
+			assert_eq!(
+				timed.velocity_at(waypoint.time),
+				Some(waypoint.pose.velocity)
+			);
+			assert_eq!(
+				timed.acceleration_at(waypoint.time),
+				Some(waypoint.pose.acceleration)
+			);
+		}
+
+		assert_eq!(timed.position_at(0.5), None);
+		assert_eq!(timed.position_at(4.5), None);
+	}
+
+	#[test]
+	fn derivatives_are_scaled_by_duration() {
+		// A parabola over two seconds, represented exactly.
+		let timed = vec![
+			Timed {
+				time: 0.,
+				pose: Pose3 {
+					position: 0.,
+					velocity: 0.,
+					acceleration: 2.,
+				},
+			},
+			Timed {
+				time: 2.,
+				pose: Pose3 {
+					position: 4.,
+					velocity: 4.,
+					acceleration: 2.,
+				},
+			},
+		];
+
+		for i in 0..=10 {
+			let t = f64::from(i) / 5.;
+
+			assert_f64_roughly_eq!(timed.position_at(t).unwrap(), t * t, 1e-12);
+			assert_f64_roughly_eq!(timed.velocity_at(t).unwrap(), 2. * t, 1e-12);
+			assert_f64_roughly_eq!(timed.acceleration_at(t).unwrap(), 2., 1e-12);
+		}
+	}
+
+	#[test]
+	fn septic_and_cubic_timed() {
+		let cubic = vec![
+			Timed {
+				time: 0.,
+				pose: Pose2 {
+					position: 0.,
+					velocity: 0.,
+				},
+			},
+			Timed {
+				time: 3.,
+				pose: Pose2 {
+					position: 27.,
+					velocity: 27.,
+				},
+			},
+		];
+
+		assert_f64_roughly_eq!(cubic.position_at(1.5).unwrap(), 1.5_f64.powi(3), 1e-12);
+		assert_f64_roughly_eq!(cubic.velocity_at(1.5).unwrap(), 6.75, 1e-12);
+		assert_eq!(
+			cubic.get_segment(1.5),
+			Some(Segment2(0.5, &cubic[0].pose, &cubic[1].pose))
+		);
+
+		let septic = vec![
+			Timed {
+				time: 0.,
+				pose: Pose4 {
+					position: 0.,
+					velocity: 0.,
+					acceleration: 0.,
+					jerk: 6.,
+				},
+			},
+			Timed {
+				time: 2.,
+				pose: Pose4 {
+					position: 8.,
+					velocity: 12.,
+					acceleration: 12.,
+					jerk: 6.,
+				},
+			},
+		];
+
+		assert_f64_roughly_eq!(septic.position_at(1.).unwrap(), 1., 1e-12);
+		assert_f64_roughly_eq!(septic.velocity_at(1.).unwrap(), 3., 1e-12);
+		assert_f64_roughly_eq!(septic.acceleration_at(1.).unwrap(), 6., 1e-12);
+		assert_f64_roughly_eq!(septic.jerk_at(1.).unwrap(), 6., 1e-9);
+		assert_eq!(
+			septic.get_segment(2.),
+			Some(Segment4(0., &septic[1].pose, &septic[1].pose))
+		);
+	}
+
+	#[test]
+	fn segment_choice_at_waypoints() {
+		let timed: Vec<Timed<()>> = [0., 1., 3., 4.]
+			.iter()
+			.map(|&time| Timed { time, pose: () })
+			.collect();
+
+		assert_eq!(locate(&timed, 1.), Some((1, 1, 0., 1.)));
+		assert_eq!(find_segment(&timed, 1.), Some((1, 2, 0., 2.)));
+		assert_eq!(find_segment(&timed, 2.), Some((1, 2, 0.5, 2.)));
+		assert_eq!(find_segment(&timed, 4.), Some((2, 3, 1., 1.)));
+		assert_eq!(find_segment(&timed, 4.5), None);
+
+		assert_eq!(find_untimed_segment(4, 1.), Some((1, 2, 0., 1.)));
+		assert_eq!(find_untimed_segment(4, 3.), Some((2, 3, 1., 1.)));
+		assert_eq!(find_untimed_segment(4, 3.5), None);
+		assert_eq!(find_untimed_segment(1, 0.), Some((0, 0, 0., 1.)));
+	}
+
+	#[test]
+	fn empty_and_single() {
+		let empty: Vec<Timed<Pose3<f64>>> = Vec::new();
+		assert_eq!(empty.position_at(0.), None);
+
+		let single = vec![Timed {
+			time: 1.,
+			pose: Pose3 {
+				position: 1.,
+				velocity: 2.,
+				acceleration: 3.,
+			},
+		}];
+		assert_eq!(single.velocity_at(1.), Some(2.));
+		assert_eq!(single.velocity_at(1.1), None);
+	}
+}
@@ -0,0 +1,568 @@
+//! Transforms that produce a new trajectory from an existing one.
+//!
+//! Reversing a trajectory traverses the same path backwards, and scaling it
+//! in time traverses the same path faster or slower.  Both are exact: they
+//! only rewrite the waypoints, and the segments between them come out as the
+//! original segments played backwards or stretched.
+//!
+//! A [`Transform`] moves a trajectory in space instead, such as mirroring it
+//! across a field or re-anchoring it in another frame.  Positions receive the
+//! whole transform, while velocities and higher derivatives, being
+//! differences of positions, receive only its linear part.
+
+use core::ops::{Add, Mul};
+
+use super::timed::Timed;
+use super::vec::Vec3d;
+use super::{Pose2, Pose3, Pose4};
+
+/// A waypoint whose time-derivatives can be rewritten for a change of time.
+pub trait TimeTransform: Sized {
+	/// Rewrites the waypoint for time running backwards, which negates its odd
+	/// derivatives.
+	fn reversed(&self) -> Self;
+
+	/// Rewrites the waypoint for time running `k` times slower, which divides
+	/// its `n`th derivative by `k` to the `n`.
+	fn time_scaled(&self, k: f64) -> Self;
+}
+
+impl<V> TimeTransform for Pose2<V>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	fn reversed(&self) -> Self {
+		Pose2 {
+			position: self.position,
+			velocity: self.velocity * -1.,
+		}
+	}
+
+	fn time_scaled(&self, k: f64) -> Self {
+		Pose2 {
+			position: self.position,
+			velocity: self.velocity * k.recip(),
+		}
+	}
+}
+
+impl<V> TimeTransform for Pose3<V>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	fn reversed(&self) -> Self {
+		Pose3 {
+			position: self.position,
+			velocity: self.velocity * -1.,
+			acceleration: self.acceleration,
+		}
+	}
+
+	fn time_scaled(&self, k: f64) -> Self {
+		Pose3 {
+			position: self.position,
+			velocity: self.velocity * k.recip(),
+			acceleration: self.acceleration * k.powi(-2),
+		}
+	}
+}
+
+impl<V> TimeTransform for Pose4<V>
+where
+	V: Add<V, Output = V> + Copy + Mul<f64, Output = V>,
+{
+	fn reversed(&self) -> Self {
+		Pose4 {
+			position: self.position,
+			velocity: self.velocity * -1.,
+			acceleration: self.acceleration,
+			jerk: self.jerk * -1.,
+		}
+	}
+
+	fn time_scaled(&self, k: f64) -> Self {
+		Pose4 {
+			position: self.position,
+			velocity: self.velocity * k.recip(),
+			acceleration: self.acceleration * k.powi(-2),
+			jerk: self.jerk * k.powi(-3),
+		}
+	}
+}
+
+/// Reverses a waypoint vector, so that it starts where it used to end and
+/// follows the same path back.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::transform::reverse;
+/// use motion_planning::{Pose2, Trajectory2};
+///
+/// let outbound = vec![
+///     Pose2 { position: 0., velocity: 1. },
+///     Pose2 { position: 2., velocity: 3. },
+/// ];
+/// let home = reverse(&outbound);
+///
+/// assert_eq!(home[0], Pose2 { position: 2., velocity: -3. });
+/// assert_eq!(home.position_at(0.25), outbound.position_at(0.75));
+/// ```
+pub fn reverse<P: TimeTransform>(waypoints: &[P]) -> Vec<P> {
+	waypoints
+		.iter()
+		.rev()
+		.map(TimeTransform::reversed)
+		.collect()
+}
+
+/// Reverses timed waypoints, like [`reverse`], keeping the trajectory within
+/// the same span of time.
+pub fn reverse_timed<P: TimeTransform>(waypoints: &[Timed<P>]) -> Vec<Timed<P>> {
+	let span = match (waypoints.first(), waypoints.last()) {
+		(Some(first), Some(last)) => first.time + last.time,
+		_ => return Vec::new(),
+	};
+
+	waypoints
+		.iter()
+		.rev()
+		.map(|waypoint| Timed {
+			time: span - waypoint.time,
+			pose: waypoint.pose.reversed(),
+		})
+		.collect()
+}
+
+/// Stretches timed waypoints to take `k` times as long, so that the same path
+/// is followed at `1 / k` times the speed.
+///
+/// Times are scaled about the first waypoint's.  Returns `None` unless `k` is
+/// positive and finite.
+///
+/// # Examples
+///
+/// ```rust
+/// use motion_planning::timed::timed;
+/// use motion_planning::transform::scale_time;
+/// use motion_planning::{Pose3, Trajectory3};
+///
+/// let plan = timed(&[
+///     Pose3 { position: 0., velocity: 0., acceleration: 2. },
+///     Pose3 { position: 1., velocity: 2., acceleration: 2. },
+/// ]);
+/// let slow = scale_time(&plan, 2.).unwrap();
+///
+/// assert_eq!(slow[1].time, 2.);
+/// assert_eq!(slow.velocity_at(2.), Some(1.));
+/// assert_eq!(slow.acceleration_at(2.), Some(0.5));
+/// ```
+pub fn scale_time<P: TimeTransform + Copy>(
+	waypoints: &[Timed<P>],
+	k: f64,
+) -> Option<Vec<Timed<P>>> {
+	if !(k > 0. && k.is_finite()) {
+		return None;
+	}
+
+	let start = waypoints.first().map_or(0., |first| first.time);
+
+	Some(
+		waypoints
+			.iter()
+			.map(|waypoint| Timed {
+				time: start + (waypoint.time - start) * k,
+				pose: waypoint.pose.time_scaled(k),
+			})
+			.collect(),
+	)
+}
+
+/// An affine transform of space: a linear map followed by a translation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+	/// The linear part, as the rows of a matrix.
+	pub linear: [Vec3d<f64>; 3],
+	pub translation: Vec3d<f64>,
+}
+
+impl Transform {
+	/// The transform that leaves everything in place.
+	pub fn identity() -> Self {
+		Transform {
+			linear: [Vec3d(1., 0., 0.), Vec3d(0., 1., 0.), Vec3d(0., 0., 1.)],
+			translation: Vec3d(0., 0., 0.),
+		}
+	}
+
+	/// Moves everything by `offset`.
+	pub fn translation(offset: Vec3d<f64>) -> Self {
+		Transform {
+			translation: offset,
+			..Transform::identity()
+		}
+	}
+
+	/// Rotates everything by `angle` radians about `axis` through the origin,
+	/// anticlockwise when looking back along the axis.
+	///
+	/// Returns `None` if `axis` has no length.
+	pub fn rotation(axis: Vec3d<f64>, angle: f64) -> Option<Self> {
+		let length = axis.norm();
+
+		if length == 0. || !length.is_finite() {
+			return None;
+		}
+
+		let Vec3d(x, y, z) = axis * length.recip();
+		let (s, c) = angle.sin_cos();
+		let k = 1. - c;
+
+		Some(Transform {
+			linear: [
+				Vec3d(c + x * x * k, x * y * k - z * s, x * z * k + y * s),
+				Vec3d(y * x * k + z * s, c + y * y * k, y * z * k - x * s),
+				Vec3d(z * x * k - y * s, z * y * k + x * s, c + z * z * k),
+			],
+			translation: Vec3d(0., 0., 0.),
+		})
+	}
+
+	/// Scales everything by `factor` about the origin.
+	pub fn scale(factor: f64) -> Self {
+		Transform {
+			linear: [
+				Vec3d(factor, 0., 0.),
+				Vec3d(0., factor, 0.),
+				Vec3d(0., 0., factor),
+			],
+			translation: Vec3d(0., 0., 0.),
+		}
+	}
+
+	/// Reflects everything across the plane through `point` perpendicular to
+	/// `normal`.
+	///
+	/// Returns `None` if `normal` has no length.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use motion_planning::transform::Transform;
+	/// use motion_planning::vec::Vec3d;
+	///
+	/// // Mirror a field 16 m long about its centre line.
+	/// let mirror = Transform::mirror(Vec3d(8., 0., 0.), Vec3d(1., 0., 0.)).unwrap();
+	///
+	/// assert_eq!(mirror.point(Vec3d(1., 2., 0.)), Vec3d(15., 2., 0.));
+	/// assert_eq!(mirror.vector(Vec3d(1., 2., 0.)), Vec3d(-1., 2., 0.));
+	/// ```
+	pub fn mirror(point: Vec3d<f64>, normal: Vec3d<f64>) -> Option<Self> {
+		let length = normal.norm();
+
+		if length == 0. || !length.is_finite() {
+			return None;
+		}
+
+		let n = normal * length.recip();
+		let row = |e: Vec3d<f64>| e + n * (-2. * n.dot(&e));
+
+		Some(Transform {
+			linear: [
+				row(Vec3d(1., 0., 0.)),
+				row(Vec3d(0., 1., 0.)),
+				row(Vec3d(0., 0., 1.)),
+			],
+			translation: n * (2. * n.dot(&point)),
+		})
+	}
+
+	/// Composes two transforms into one that applies `self` and then `next`.
+	pub fn then(&self, next: &Transform) -> Self {
+		// The images of the unit vectors are the columns of the product.
+		let c0 = next.vector(self.vector(Vec3d(1., 0., 0.)));
+		let c1 = next.vector(self.vector(Vec3d(0., 1., 0.)));
+		let c2 = next.vector(self.vector(Vec3d(0., 0., 1.)));
+
+		Transform {
+			linear: [
+				Vec3d(c0.0, c1.0, c2.0),
+				Vec3d(c0.1, c1.1, c2.1),
+				Vec3d(c0.2, c1.2, c2.2),
+			],
+			translation: next.point(self.translation),
+		}
+	}
+
+	/// Applies the transform to a position.
+	pub fn point(&self, p: Vec3d<f64>) -> Vec3d<f64> {
+		self.vector(p) + self.translation
+	}
+
+	/// Applies the linear part of the transform to a velocity, acceleration or
+	/// other difference of positions.
+	pub fn vector(&self, v: Vec3d<f64>) -> Vec3d<f64> {
+		let [r0, r1, r2] = &self.linear;
+
+		Vec3d(r0.dot(&v), r1.dot(&v), r2.dot(&v))
+	}
+}
+
+/// A waypoint that can be moved in space by a [`Transform`].
+pub trait SpatialTransform: Sized {
+	fn transformed(&self, transform: &Transform) -> Self;
+}
+
+impl SpatialTransform for Pose2<Vec3d<f64>> {
+	fn transformed(&self, transform: &Transform) -> Self {
+		Pose2 {
+			position: transform.point(self.position),
+			velocity: transform.vector(self.velocity),
+		}
+	}
+}
+
+impl SpatialTransform for Pose3<Vec3d<f64>> {
+	fn transformed(&self, transform: &Transform) -> Self {
+		Pose3 {
+			position: transform.point(self.position),
+			velocity: transform.vector(self.velocity),
+			acceleration: transform.vector(self.acceleration),
+		}
+	}
+}
+
+impl SpatialTransform for Pose4<Vec3d<f64>> {
+	fn transformed(&self, transform: &Transform) -> Self {
+		Pose4 {
+			position: transform.point(self.position),
+			velocity: transform.vector(self.velocity),
+			acceleration: transform.vector(self.acceleration),
+			jerk: transform.vector(self.jerk),
+		}
+	}
+}
+
+impl<P: SpatialTransform> SpatialTransform for Timed<P> {
+	fn transformed(&self, transform: &Transform) -> Self {
+		Timed {
+			time: self.time,
+			pose: self.pose.transformed(transform),
+		}
+	}
+}
+
+/// Applies `transform` to every waypoint, timed or not.
+///
+/// Because the transform is affine, the trajectory between the waypoints is
+/// transformed exactly too.
+pub fn transform<P: SpatialTransform>(waypoints: &[P], transform: &Transform) -> Vec<P> {
+	waypoints
+		.iter()
+		.map(|waypoint| waypoint.transformed(transform))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::super::timed::timed;
+	use super::super::vec::Vec3d;
+	use super::super::{Trajectory3, Trajectory4};
+	use super::*;
+
+	use core::f64::consts::FRAC_PI_2;
+
+	fn waypoints() -> Vec<Pose3<Vec3d<f64>>> {
+		vec![
+			Pose3 {
+				position: Vec3d(0., 0., 0.),
+				velocity: Vec3d(0., 1., 0.),
+				acceleration: Vec3d(1., 0., 0.),
+			},
+			Pose3 {
+				position: Vec3d(1., 1., 0.),
+				velocity: Vec3d(1., 0., 0.),
+				acceleration: Vec3d(0., -1., 0.),
+			},
+			Pose3 {
+				position: Vec3d(2., 0., 1.),
+				velocity: Vec3d(0., -1., 0.),
+				acceleration: Vec3d(0., 0., 0.),
+			},
+		]
+	}
+
+	fn assert_vec_roughly_eq(a: Vec3d<f64>, b: Vec3d<f64>) {
+		assert_f64_roughly_eq!((a + b * -1.).norm(), 0., 1e-9);
+	}
+
+	#[test]
+	fn reverse_retraces_path() {
+		let outbound = waypoints();
+		let home = reverse(&outbound);
+
+		for i in 0..=20 {
+			let t = f64::from(i) / 10.;
+
+			assert_vec_roughly_eq(
+				home.position_at(t).unwrap(),
+				outbound.position_at(2. - t).unwrap(),
+			);
+			assert_vec_roughly_eq(
+				home.velocity_at(t).unwrap(),
+				outbound.velocity_at(2. - t).unwrap() * -1.,
+			);
+			assert_vec_roughly_eq(
+				home.acceleration_at(t).unwrap(),
+				outbound.acceleration_at(2. - t).unwrap(),
+			);
+		}
+
+		assert_eq!(reverse(&home), outbound);
+	}
+
+	#[test]
+	fn reverse_timed_keeps_span() {
+		let outbound: Vec<_> = timed(&waypoints())
+			.into_iter()
+			.zip(&[1., 1.5, 4.])
+			.map(|(waypoint, &time)| Timed { time, ..waypoint })
+			.collect();
+		let home = reverse_timed(&outbound);
+
+		assert_eq!(
+			home.iter().map(|w| w.time).collect::<Vec<_>>(),
+			vec![1., 3.5, 4.]
+		);
+
+		for i in 0..=30 {
+			let t = 1. + f64::from(i) / 10.;
+
+			assert_vec_roughly_eq(
+				home.position_at(t).unwrap(),
+				outbound.position_at(5. - t).unwrap(),
+			);
+		}
+	}
+
+	#[test]
+	fn scale_time_slows_down() {
+		let fast = timed(&waypoints());
+		let slow = scale_time(&fast, 3.).unwrap();
+
+		for i in 0..=20 {
+			let t = f64::from(i) / 10.;
+
+			assert_vec_roughly_eq(
+				slow.position_at(3. * t).unwrap(),
+				fast.position_at(t).unwrap(),
+			);
+			assert_vec_roughly_eq(
+				slow.velocity_at(3. * t).unwrap() * 3.,
+				fast.velocity_at(t).unwrap(),
+			);
+			assert_vec_roughly_eq(
+				slow.acceleration_at(3. * t).unwrap() * 9.,
+				fast.acceleration_at(t).unwrap(),
+			);
+		}
+
+		assert_eq!(scale_time(&fast, 0.), None);
+		assert_eq!(scale_time(&fast, f64::NAN), None);
+	}
+
+	#[test]
+	fn septic_jerk_transforms() {
+		let pose = Pose4 {
+			position: 1.,
+			velocity: 2.,
+			acceleration: 4.,
+			jerk: 8.,
+		};
+		let plan = timed(&[pose, pose.time_scaled(0.5)]);
+
+		assert_eq!(
+			pose.time_scaled(2.),
+			Pose4 {
+				position: 1.,
+				velocity: 1.,
+				acceleration: 1.,
+				jerk: 1.,
+			}
+		);
+		assert_eq!(reverse_timed(&plan)[1].pose.jerk, -8.);
+		assert_eq!(plan.jerk_at(0.), Some(8.));
+	}
+
+	#[test]
+	fn rotation_quarter_turn() {
+		let turn = Transform::rotation(Vec3d(0., 0., 2.), FRAC_PI_2).unwrap();
+
+		assert_vec_roughly_eq(turn.point(Vec3d(1., 0., 0.)), Vec3d(0., 1., 0.));
+		assert_vec_roughly_eq(turn.point(Vec3d(0., 1., 5.)), Vec3d(-1., 0., 5.));
+		assert_eq!(Transform::rotation(Vec3d(0., 0., 0.), 1.), None);
+	}
+
+	#[test]
+	fn composition_matches_sequence() {
+		let a = Transform::rotation(Vec3d(1., 2., 3.), 0.7)
+			.unwrap()
+			.then(&Transform::translation(Vec3d(1., -2., 0.5)));
+		let b = Transform::mirror(Vec3d(0., 1., 0.), Vec3d(1., 1., 0.))
+			.unwrap()
+			.then(&Transform::scale(2.));
+		let both = a.then(&b);
+
+		let p = Vec3d(0.3, -1.2, 4.);
+
+		assert_vec_roughly_eq(both.point(p), b.point(a.point(p)));
+		assert_vec_roughly_eq(both.vector(p), b.vector(a.vector(p)));
+		assert_eq!(Transform::identity().then(&a), a);
+	}
+
+	#[test]
+	fn mirror_is_involution() {
+		let mirror = Transform::mirror(Vec3d(1., 2., 3.), Vec3d(0., 3., 4.)).unwrap();
+		let twice = mirror.then(&mirror);
+		let p = Vec3d(-2., 5., 1.);
+
+		assert_vec_roughly_eq(twice.point(p), p);
+		assert_vec_roughly_eq(mirror.point(Vec3d(1., 2., 3.)), Vec3d(1., 2., 3.));
+		assert_eq!(
+			Transform::mirror(Vec3d(0., 0., 0.), Vec3d(0., 0., 0.)),
+			None
+		);
+	}
+
+	#[test]
+	fn transform_whole_trajectory() {
+		let original = waypoints();
+		let rigid = Transform::rotation(Vec3d(0., 1., 1.), 1.2)
+			.unwrap()
+			.then(&Transform::translation(Vec3d(3., 0., -1.)));
+		let moved = transform(&original, &rigid);
+
+		for i in 0..=20 {
+			let t = f64::from(i) / 10.;
+
+			assert_vec_roughly_eq(
+				moved.position_at(t).unwrap(),
+				rigid.point(original.position_at(t).unwrap()),
+			);
+			assert_vec_roughly_eq(
+				moved.velocity_at(t).unwrap(),
+				rigid.vector(original.velocity_at(t).unwrap()),
+			);
+			assert_vec_roughly_eq(
+				moved.acceleration_at(t).unwrap(),
+				rigid.vector(original.acceleration_at(t).unwrap()),
+			);
+		}
+
+		let scaled = transform(&timed(&original), &Transform::scale(2.));
+		assert_eq!(scaled[2].time, 2.);
+		assert_eq!(scaled[2].pose.position, Vec3d(4., 0., 2.));
+		assert_eq!(scaled[2].pose.velocity, Vec3d(0., -2., 0.));
+	}
+}
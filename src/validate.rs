@@ -0,0 +1,484 @@
+//! Checking a trajectory against the limits of the robot following it.
+//!
+//! Validation finds every stretch of time over which a limit is broken, and
+//! how badly, from the exact polynomial form of each segment; see
+//! [`extrema`](super::extrema).
+
+use core::fmt;
+
+use super::extrema::{pieces, Axis, Derivative, Extremum, Piece};
+use super::polynomial::Polynomial;
+use super::timed::{timed, Timed};
+use super::vec::Vec3d;
+use super::Pose3;
+
+/// Bounds on one derivative of a trajectory.  `None` leaves it unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bound {
+	/// The largest magnitude allowed for each component.
+	pub axis: Option<Vec3d<f64>>,
+	/// The largest norm allowed.
+	pub norm: Option<f64>,
+}
+
+/// Limits to check a trajectory against.  `None` leaves a quantity unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+	/// The least and greatest corners of the box the position must stay in.
+	pub position: Option<(Vec3d<f64>, Vec3d<f64>)>,
+	pub velocity: Bound,
+	pub acceleration: Bound,
+	pub jerk: Bound,
+}
+
+/// The limit that a [`Violation`] breaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Constraint {
+	/// A bound on one component of a derivative.
+	Axis(Derivative, Axis),
+	/// A bound on the norm of a derivative.
+	Norm(Derivative),
+}
+
+impl fmt::Display for Constraint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = |derivative| match derivative {
+			Derivative::Position => "position",
+			Derivative::Velocity => "velocity",
+			Derivative::Acceleration => "acceleration",
+			Derivative::Jerk => "jerk",
+		};
+
+		match self {
+			Constraint::Axis(derivative, axis) => {
+				let axis = match axis {
+					Axis::X => "x",
+					Axis::Y => "y",
+					Axis::Z => "z",
+				};
+
+				write!(f, "{} {}", name(*derivative), axis)
+			}
+			Constraint::Norm(derivative) => write!(f, "{} norm", name(*derivative)),
+		}
+	}
+}
+
+/// Which side of a limit a [`Violation`] breaks it on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+	/// Over the greatest value allowed.  Norms can only be broken this way.
+	Upper,
+	/// Under the least value allowed.
+	Lower,
+}
+
+impl fmt::Display for Side {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Side::Upper => write!(f, "over the maximum"),
+			Side::Lower => write!(f, "under the minimum"),
+		}
+	}
+}
+
+/// A stretch of time over which a limit is broken.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Violation {
+	pub constraint: Constraint,
+	pub side: Side,
+	pub start: f64,
+	pub end: f64,
+	/// The time at which the limit is broken by most, and by how much.
+	pub worst: Extremum,
+}
+
+/// Every limit violation along a trajectory, grouped by constraint and in
+/// order of time within each group.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report {
+	pub violations: Vec<Violation>,
+}
+
+impl Report {
+	/// Whether the trajectory stays within its limits throughout.
+	pub fn is_ok(&self) -> bool {
+		self.violations.is_empty()
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.is_ok() {
+			return writeln!(f, "no limits violated");
+		}
+
+		for v in &self.violations {
+			writeln!(
+				f,
+				"{} violated from {} to {}, {} by up to {} at {}",
+				v.constraint, v.start, v.end, v.side, v.worst.value, v.worst.time
+			)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Checking against [`Limits`].
+pub trait Validate {
+	fn validate(&self, limits: &Limits) -> Report;
+}
+
+impl Validate for Vec<Timed<Pose3<Vec3d<f64>>>> {
+	fn validate(&self, limits: &Limits) -> Report {
+		let mut violations = Vec::new();
+
+		if let Some((min, max)) = limits.position {
+			let pieces = pieces(self, Derivative::Position);
+
+			for axis in Axis::ALL {
+				scan_axis(
+					&mut violations,
+					(Derivative::Position, axis),
+					&pieces,
+					(axis.of(min), axis.of(max)),
+				);
+			}
+		}
+
+		for (derivative, bound) in [
+			(Derivative::Velocity, limits.velocity),
+			(Derivative::Acceleration, limits.acceleration),
+			(Derivative::Jerk, limits.jerk),
+		] {
+			if bound.axis.is_none() && bound.norm.is_none() {
+				continue;
+			}
+
+			let pieces = pieces(self, derivative);
+
+			if let Some(limit) = bound.axis {
+				for axis in Axis::ALL {
+					scan_axis(
+						&mut violations,
+						(derivative, axis),
+						&pieces,
+						(-axis.of(limit), axis.of(limit)),
+					);
+				}
+			}
+
+			if let Some(limit) = bound.norm {
+				let constraint = Constraint::Norm(derivative);
+
+				scan(&mut violations, constraint, Side::Upper, &pieces, |piece| {
+					let [x, y, z] = piece.axes.clone();
+
+					(x.clone() * x + y.clone() * y + z.clone() * z, limit, 2.)
+				});
+			}
+		}
+
+		Report { violations }
+	}
+}
+
+impl Validate for Vec<Pose3<Vec3d<f64>>> {
+	fn validate(&self, limits: &Limits) -> Report {
+		timed(self).validate(limits)
+	}
+}
+
+/// Finds where one component of `pieces` leaves the range from `min` to
+/// `max`, and records each stretch in `violations` in order of time.
+fn scan_axis(
+	violations: &mut Vec<Violation>,
+	(derivative, axis): (Derivative, Axis),
+	pieces: &[Piece],
+	(min, max): (f64, f64),
+) {
+	let constraint = Constraint::Axis(derivative, axis);
+	let mut found = Vec::new();
+
+	scan(&mut found, constraint, Side::Upper, pieces, |piece| {
+		(piece.axes[axis as usize].clone(), max, 1.)
+	});
+	scan(&mut found, constraint, Side::Lower, pieces, |piece| {
+		(piece.axes[axis as usize].clone() * -1., -min, 1.)
+	});
+
+	found.sort_by(|a, b| a.start.total_cmp(&b.start));
+	violations.extend(found);
+}
+
+/// Finds where the polynomial that `select` picks out of each piece rises
+/// above its limit, and records each stretch in `violations`.
+///
+/// `select` also gives the power that the polynomial raises the quantity to,
+/// so that a squared norm can be compared with the squared limit and the
+/// excess reported in the quantity's own units.
+fn scan(
+	violations: &mut Vec<Violation>,
+	constraint: Constraint,
+	side: Side,
+	pieces: &[Piece],
+	select: impl Fn(&Piece) -> (Polynomial, f64, f64),
+) {
+	for piece in pieces {
+		let (p, limit, power) = select(piece);
+
+		for (start, end, worst) in exceedances(&p, limit.powf(power)) {
+			// A squared norm can only dip below zero by rounding, but an axis
+			// or position may be negative and is compared as it is.
+			let value = p.eval(worst);
+			let value = if power == 2. {
+				value.max(0.).powf(power.recip())
+			} else {
+				value
+			};
+
+			let violation = Violation {
+				constraint,
+				side,
+				start: piece.time(start),
+				end: piece.time(end),
+				worst: Extremum {
+					time: piece.time(worst),
+					value: value - limit,
+				},
+			};
+
+			match violations.last_mut() {
+				// A stretch carrying on from the previous segment.
+				Some(last)
+					if last.constraint == constraint && last.side == side && last.end == violation.start =>
+				{
+					last.end = violation.end;
+
+					if violation.worst.value > last.worst.value {
+						last.worst = violation.worst;
+					}
+				}
+				_ => violations.push(violation),
+			}
+		}
+	}
+}
+
+/// Finds the intervals of `[0, 1]` over which `p` is greater than
+/// `threshold`, along with the point in each where it is greatest.
+fn exceedances(p: &Polynomial, threshold: f64) -> Vec<(f64, f64, f64)> {
+	let excess = p.clone() + Polynomial(vec![-threshold]);
+	let stationary = p.derivative().roots(0., 1.);
+
+	let mut cuts = vec![0.];
+	cuts.extend(excess.roots(0., 1.));
+	cuts.push(1.);
+	cuts.dedup();
+
+	let mut intervals: Vec<(f64, f64, f64)> = Vec::new();
+
+	for pair in cuts.windows(2) {
+		let (a, b) = (pair[0], pair[1]);
+
+		if excess.eval(a + (b - a) / 2.) <= 0. {
+			continue;
+		}
+
+		let worst = [a, b]
+			.iter()
+			.chain(stationary.iter().filter(|&&u| a <= u && u <= b))
+			.copied()
+			.fold(
+				a,
+				|worst, u| if p.eval(u) > p.eval(worst) { u } else { worst },
+			);
+
+		match intervals.last_mut() {
+			// Where `p` only touches the threshold, the stretch carries on.
+			Some(last) if last.1 == a => {
+				last.1 = b;
+
+				if p.eval(worst) > p.eval(last.2) {
+					last.2 = worst;
+				}
+			}
+			_ => intervals.push((a, b, worst)),
+		}
+	}
+
+	intervals
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::assert_f64_roughly_eq;
+	use super::*;
+
+	fn rest(x: f64) -> Pose3<Vec3d<f64>> {
+		Pose3 {
+			position: Vec3d(x, 0., 0.),
+			velocity: Vec3d(0., 0., 0.),
+			acceleration: Vec3d(0., 0., 0.),
+		}
+	}
+
+	#[test]
+	fn within_limits() {
+		let waypoints = vec![rest(0.), rest(1.)];
+		let limits = Limits {
+			position: Some((Vec3d(0., 0., 0.), Vec3d(1., 0., 0.))),
+			velocity: Bound {
+				axis: Some(Vec3d(2., 2., 2.)),
+				norm: Some(2.),
+			},
+			acceleration: Bound {
+				axis: None,
+				norm: Some(6.),
+			},
+			jerk: Bound {
+				axis: Some(Vec3d(60., 60., 60.)),
+				norm: None,
+			},
+		};
+
+		let report = waypoints.validate(&limits);
+
+		assert!(report.is_ok(), "{}", report);
+		assert_eq!(report.to_string(), "no limits violated\n");
+	}
+
+	#[test]
+	fn velocity_interval() {
+		// The velocity is 30u²(1 - u)², which exceeds 1.5 in the middle.
+		let waypoints = vec![rest(0.), rest(1.)];
+		let limits = Limits {
+			velocity: Bound {
+				axis: None,
+				norm: Some(1.5),
+			},
+			..Limits::default()
+		};
+
+		let report = waypoints.validate(&limits);
+		assert_eq!(report.violations.len(), 1);
+
+		let v = report.violations[0];
+		assert_eq!(v.constraint, Constraint::Norm(Derivative::Velocity));
+		assert_f64_roughly_eq!(v.start, 1. - v.end, 1e-9);
+		assert_f64_roughly_eq!(v.worst.time, 0.5, 1e-9);
+		assert_f64_roughly_eq!(v.worst.value, 0.375, 1e-9);
+
+		let u = v.start;
+		assert_f64_roughly_eq!(30. * u * u * (1. - u) * (1. - u), 1.5, 1e-9);
+	}
+
+	#[test]
+	fn acceleration_both_signs() {
+		let waypoints = vec![rest(0.), rest(1.), rest(0.)];
+		let limits = Limits {
+			acceleration: Bound {
+				axis: Some(Vec3d(5., 5., 5.)),
+				norm: None,
+			},
+			..Limits::default()
+		};
+
+		let report = waypoints.validate(&limits);
+		let constraints: Vec<_> = report.violations.iter().map(|v| v.constraint).collect();
+
+		// Each segment speeds up and slows down too hard, and in each direction.
+		assert_eq!(report.violations.len(), 4);
+		assert!(constraints
+			.iter()
+			.all(|&c| c == Constraint::Axis(Derivative::Acceleration, Axis::X)));
+
+		let excess = 10. * 3_f64.sqrt() / 3. - 5.;
+		for v in &report.violations {
+			assert_f64_roughly_eq!(v.worst.value, excess, 1e-9);
+		}
+
+		// Out and back, so over, under, under and over again, in time order.
+		let sides: Vec<_> = report.violations.iter().map(|v| v.side).collect();
+		assert_eq!(sides, [Side::Upper, Side::Lower, Side::Lower, Side::Upper]);
+		assert!(report
+			.violations
+			.windows(2)
+			.all(|pair| pair[0].end <= pair[1].start));
+		assert!(report
+			.to_string()
+			.lines()
+			.nth(1)
+			.unwrap()
+			.contains(", under the minimum by up to "));
+	}
+
+	#[test]
+	fn joins_across_waypoints() {
+		let fast = Pose3 {
+			velocity: Vec3d(2., 0., 0.),
+			..rest(1.)
+		};
+		let waypoints = vec![rest(0.), fast, rest(2.)];
+		let limits = Limits {
+			velocity: Bound {
+				axis: None,
+				norm: Some(1.5),
+			},
+			..Limits::default()
+		};
+
+		let report = waypoints.validate(&limits);
+
+		assert_eq!(report.violations.len(), 1);
+		assert!(report.violations[0].start < 1.);
+		assert!(report.violations[0].end > 1.);
+		assert!(report.violations[0].worst.value >= 0.5);
+	}
+
+	#[test]
+	fn position_box_away_from_origin() {
+		let waypoints = vec![rest(0.5), rest(1.)];
+		let limits = Limits {
+			position: Some((Vec3d(2., -1., -1.), Vec3d(3., 1., 1.))),
+			..Limits::default()
+		};
+
+		let report = waypoints.validate(&limits);
+
+		assert_eq!(report.violations.len(), 1);
+
+		let v = report.violations[0];
+		assert_eq!(
+			v.constraint,
+			Constraint::Axis(Derivative::Position, Axis::X)
+		);
+		assert_eq!(v.side, Side::Lower);
+		assert_eq!((v.start, v.end), (0., 1.));
+		assert_f64_roughly_eq!(v.worst.time, 0., 1e-9);
+		assert_f64_roughly_eq!(v.worst.value, 1.5, 1e-9);
+	}
+
+	#[test]
+	fn position_box() {
+		let overshoot = Pose3 {
+			velocity: Vec3d(2., 0., 0.),
+			..rest(1.)
+		};
+		let waypoints = vec![rest(0.), overshoot, rest(1.)];
+		let limits = Limits {
+			position: Some((Vec3d(0., -1., -1.), Vec3d(1., 1., 1.))),
+			..Limits::default()
+		};
+
+		let report = waypoints.validate(&limits);
+
+		assert_eq!(report.violations.len(), 1);
+		assert_eq!(report.violations[0].start, 1.);
+		assert!(report.violations[0].end > 1.);
+		assert!(report
+			.to_string()
+			.starts_with("position x violated from 1 to "));
+	}
+}
@@ -6,6 +6,7 @@ use super::assert_f64_roughly_eq;
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3d<V>(pub V, pub V, pub V);
 
 impl<V> fmt::Display for Vec3d<V>
@@ -67,6 +68,20 @@ where
 	}
 }
 
+impl Vec3d<f64> {
+	pub fn norm(&self) -> f64 {
+		self.dot(self).sqrt()
+	}
+
+	pub fn cross(&self, other: &Vec3d<f64>) -> Vec3d<f64> {
+		Vec3d(
+			self.1 * other.2 - self.2 * other.1,
+			self.2 * other.0 - self.0 * other.2,
+			self.0 * other.1 - self.1 * other.0,
+		)
+	}
+}
+
 #[test]
 fn vec_addition() {
 	let a: Vec3d<f32> = Vec3d(1.0, 2.0, 3.0);
@@ -120,3 +135,19 @@ fn vec_dot() {
 
 	assert_f64_roughly_eq!(a.dot(&b), 22.0_f64);
 }
+
+#[test]
+fn vec_norm() {
+	let a: Vec3d<f64> = Vec3d(2., 3., 6.);
+
+	assert_f64_roughly_eq!(a.norm(), 7.0_f64);
+}
+
+#[test]
+fn vec_cross() {
+	let a: Vec3d<f64> = Vec3d(1., 2., 3.);
+	let b: Vec3d<f64> = Vec3d(5., 4., 3.);
+
+	assert_eq!(a.cross(&b), Vec3d(-6., 12., -6.));
+	assert_f64_roughly_eq!(a.cross(&b).dot(&a), 0.0_f64);
+}